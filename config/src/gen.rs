@@ -1,9 +1,11 @@
 use std::collections::VecDeque;
 
-use crypto_lib::{Algorithm, ed25519, hash::ser_and_hash};
-use types::{DbsContext, Keypair, Replica, Result};
+use crypto_lib::{Algorithm, ed25519};
+use types::{BlsKeypair, CanonicalHash, DbsContext, Keypair, Replica, Result, error::Error};
 use crate::{Node, Reconfig, cert};
+use crypto::{DSSSecretKey, SecretValue};
 use fnv::FnvHashMap as HashMap;
+use crypto::rand::Rng;
 
 pub fn generate_configs(num_nodes: usize, 
     num_faults: usize, 
@@ -11,6 +13,9 @@ pub fn generate_configs(num_nodes: usize,
     base_port: u16,
     num_clients: usize,
     cli_base_port: u16,
+    proof_mode: crypto::ProofMode,
+    generator_seed: &str,
+    test_seed: Option<u64>,
 // ) -> Result<(VecDeque<Node>, Reconfig)> 
 ) -> Result<(VecDeque<Node>, VecDeque<Reconfig>)> 
 {
@@ -27,40 +32,47 @@ pub fn generate_configs(num_nodes: usize,
     // PVSS public keys and secret keys
     let mut pvss_pk_map = Vec::new();
     let mut pvss_sk_map = Vec::new();
+    let mut pvss_pop_map = Vec::new();
 
-    let mut rng = crypto::std_rng();
-    let h2 = crypto::rand_h2_generator::<_,types::E>(&mut rng);
-    let h1 = crypto::rand_h1_generator::<_,types::E>(&mut rng);
+    // TEST ONLY: With a test seed, all the keys and pre-sharings are the same in every run
+    // The TLS certificates are always fresh
+    let mut rng = crypto::rng_for(test_seed, 0, b"genconfig");
 
-    for _i in 0..num_nodes+num_clients {
-        let pvss_keypair = Keypair::generate_keypair(&mut rng);
+    for i in 0..num_nodes+num_clients {
+        let (pvss_keypair, pop) = Keypair::generate_keypair_with_pop(i, &mut rng);
         pvss_sk_map.push(pvss_keypair.0);
         pvss_pk_map.push(pvss_keypair.1);
+        pvss_pop_map.push(pop);
     }
 
     let mut pvss_ctx_map:HashMap<_,_> = HashMap::default();
     for i in 0..num_nodes+num_clients {
-        let ctx = DbsContext::new(&mut rng, 
-            h2, 
-            h1,
+        let mut ctx = DbsContext::new_from_seed(&mut rng, 
+            generator_seed,
             num_nodes, 
             num_faults, 
             i, 
             pvss_pk_map.clone(), 
             pvss_sk_map[i].clone(),
-        );
+        )
+        .with_key_pops(pvss_pop_map.clone())
+        .map_err(|e| Error::ParseInvalidKeyPops(format!("{:?}", e)))?;
+        // The pre-sharings below are created with this context, so it must use the configured proof format
+        ctx.set_proof_mode(proof_mode);
         pvss_ctx_map.insert(i, ctx);
     }
     // let reconf = Reconfig::new(sk_bytes, dbs_ctx);
     for i in 0..num_nodes {
-        let kp = ed25519::Keypair::generate();
-        keypairs.insert(i, crypto_lib::Keypair::Ed25519(kp.clone()));
+        let kp = new_dss_keypair(&mut rng);
+        keypairs.insert(i, DSSSecretKey::new(crypto_lib::Keypair::Ed25519(kp.clone())));
         pk.insert(i as Replica, kp.public().encode().to_vec());
         let new_node = Node::new(kp.encode().to_vec(), pvss_ctx_map.remove(&i).unwrap());
         
         node.push_back(new_node);
 
         node[i].crypto_alg = Algorithm::ED25519;
+        node[i].proof_mode = proof_mode;
+        node[i].test_seed = test_seed;
 
         node[i].delta = delay;
         node[i].id = i as Replica;
@@ -81,11 +93,11 @@ pub fn generate_configs(num_nodes: usize,
 
         node[i].root_cert = cert.to_der()?;
         node[i].my_cert = new_cert.to_der()?;
-        node[i].my_cert_key = new_pkey.private_key_to_der()?;
+        node[i].my_cert_key = SecretValue::new(new_pkey.private_key_to_der()?);
     }
     for i in 0..num_clients {
-        let kp = ed25519::Keypair::generate();
-        keypairs.insert(num_nodes+i, crypto_lib::Keypair::Ed25519(kp.clone()));
+        let kp = new_dss_keypair(&mut rng);
+        keypairs.insert(num_nodes+i, DSSSecretKey::new(crypto_lib::Keypair::Ed25519(kp.clone())));
         // the servers don't need the client's information
         // pk.insert(i+num_nodes as Replica, kp.public().encode().to_vec());
         let new_node = Reconfig::new(
@@ -96,6 +108,8 @@ pub fn generate_configs(num_nodes: usize,
         clients.push_back(new_node);
 
         clients[i].crypto_alg = Algorithm::ED25519;
+        clients[i].proof_mode = proof_mode;
+        clients[i].test_seed = test_seed;
 
         clients[i].delta = delay;
         clients[i].id = i + num_nodes as Replica;
@@ -111,13 +125,15 @@ pub fn generate_configs(num_nodes: usize,
 
         clients[i].root_cert = cert.to_der()?;
         clients[i].my_cert = new_cert.to_der()?;
-        clients[i].my_cert_key = new_pkey.private_key_to_der()?;
+        clients[i].my_cert_key = SecretValue::new(new_pkey.private_key_to_der()?);
     }
 
     for i in 0..num_nodes {
         node[i].set_pk_map_data(pk.clone());
         node[i].net_map = ip.clone();
     }
+    // A separate rng, so that the other keys do not change for a test seed
+    set_bls_keys(&mut node, &mut crypto::rng_for(test_seed, 0, b"genconfig-bls"));
     for i in 0..num_clients {
         clients[i].set_pk_map_data(pk.clone());
         clients[i].net_map = cli_ip.clone();
@@ -138,8 +154,9 @@ pub fn generate_configs(num_nodes: usize,
     let indices = [1, 2]; // We will throw this away anyways
     for i in 0..num_nodes {
         // Generate PVSS Sharing for node i
-        let sh1 = node[i].pvss_ctx.generate_shares(&keypairs[&i], &mut rng);
-        let sh2 = node[i].pvss_ctx.generate_shares(&keypairs[&i], &mut rng);
+        // The pre-sharings are dealt during setup, i.e., in epoch 0
+        let sh1 = node[i].pvss_ctx.generate_shares(0, &keypairs[&i], &mut rng);
+        let sh2 = node[i].pvss_ctx.generate_shares(0, &keypairs[&i], &mut rng);
         let pvec = vec![sh1, sh2];
         let (combined_pvss,_) = node[i].pvss_ctx.aggregate(&indices, pvec);
         // Put combined_pvss in everyone's buffers, i.e., in rand_queue for node i
@@ -152,12 +169,12 @@ pub fn generate_configs(num_nodes: usize,
         let mut queue = Vec::new();
         let mut indices = Vec::new();
         for j in 0..num_faults+1 {
-            let sh = node[j].pvss_ctx.generate_shares(&keypairs[&j], &mut rng);
+            let sh = node[j].pvss_ctx.generate_shares(0, &keypairs[&j], &mut rng);
             queue.push(sh);
-            indices.push(i);
+            indices.push(j);
         }
         let (agg, decom) = node[i].pvss_ctx.aggregate(&indices, queue);
-        let hash = ser_and_hash(&agg);
+        let hash = agg.canonical_hash();
         node[i].leader_beacon_queue.push_back((agg.clone(), decom.clone()));
         for j in 0..num_nodes {
             node[j].pool_of_verified_shares.insert(hash, agg.clone());
@@ -166,6 +183,9 @@ pub fn generate_configs(num_nodes: usize,
     Ok((node, clients))
 }
 
+/// The seed for the generators in test configs
+pub const TEST_GENERATOR_SEED: &str = "optrand-test";
+
 /// Test configs do not generate pre-sharings and certificates
 pub fn generate_test_configs(num_nodes: usize, num_faults: usize, delay: u64, base_port: u16) -> Result<VecDeque<Node>> {
     let mut node: VecDeque<Node> = VecDeque::with_capacity(num_nodes);
@@ -177,34 +197,35 @@ pub fn generate_test_configs(num_nodes: usize, num_faults: usize, delay: u64, ba
     // PVSS public keys and secret keys
     let mut pvss_pk_map = Vec::new();
     let mut pvss_sk_map = Vec::new();
+    let mut pvss_pop_map = Vec::new();
 
     let mut rng = crypto::std_rng();
-    let h2 = crypto::rand_h2_generator::<_,types::E>(&mut rng);
-    let h1 = crypto::rand_h1_generator::<_,types::E>(&mut rng);
 
-    for _i in 0..num_nodes {
-        let pvss_keypair = Keypair::generate_keypair(&mut rng);
+    for i in 0..num_nodes {
+        let (pvss_keypair, pop) = Keypair::generate_keypair_with_pop(i, &mut rng);
         pvss_sk_map.push(pvss_keypair.0);
         pvss_pk_map.push(pvss_keypair.1);
+        pvss_pop_map.push(pop);
     }
 
     let mut pvss_ctx_map:HashMap<_,_> = HashMap::default();
     for i in 0..num_nodes {
-        let ctx = DbsContext::new(&mut rng, 
-            h2, 
-            h1,
+        let ctx = DbsContext::new_from_seed(&mut rng, 
+            TEST_GENERATOR_SEED,
             num_nodes, 
             num_faults, 
             i, 
             pvss_pk_map.clone(), 
             pvss_sk_map[i].clone(),
-        );
+        )
+        .with_key_pops(pvss_pop_map.clone())
+        .map_err(|e| Error::ParseInvalidKeyPops(format!("{:?}", e)))?;
         pvss_ctx_map.insert(i, ctx);
     }
 
     for i in 0..num_nodes {
         let kp = ed25519::Keypair::generate();
-        keypairs.insert(i, crypto_lib::Keypair::Ed25519(kp.clone()));
+        keypairs.insert(i, DSSSecretKey::new(crypto_lib::Keypair::Ed25519(kp.clone())));
         pk.insert(i as Replica, kp.public().encode().to_vec());
         let new_node = Node::new(kp.encode().to_vec(), pvss_ctx_map.remove(&i).unwrap());
         node.push_back(new_node);
@@ -226,6 +247,7 @@ pub fn generate_test_configs(num_nodes: usize, num_faults: usize, delay: u64, ba
         node[i].set_pk_map_data(pk.clone());
         node[i].net_map = ip.clone();
     }
+    set_bls_keys(&mut node, &mut rng);
 
     for i in 0..num_nodes {
         for j in 0..num_nodes {
@@ -238,3 +260,23 @@ pub fn generate_test_configs(num_nodes: usize, num_faults: usize, delay: u64, ba
 
     Ok(node)
 }
+
+/// Generates a BLS key pair with a proof of possession for every node, and gives all the public keys to every node
+fn set_bls_keys<R: Rng>(node: &mut VecDeque<Node>, rng: &mut R) {
+    let (keypairs, pops): (Vec<_>, Vec<_>) = (0..node.len())
+        .map(|i| BlsKeypair::generate_with_pop(i, rng))
+        .unzip();
+    let pks: Vec<_> = keypairs.iter().map(|kp| kp.pk).collect();
+    for (i, kp) in keypairs.iter().enumerate() {
+        node[i].set_bls_keys(kp, pks.clone(), pops.clone());
+    }
+}
+
+/// Generates an ed25519 key pair using the rng
+fn new_dss_keypair<R: Rng>(rng: &mut R) -> ed25519::Keypair {
+    let mut bytes = [0u8; 32];
+    rng.fill_bytes(&mut bytes);
+    let sk = ed25519::SecretKey::from_bytes(&mut bytes)
+        .expect("Any 32 bytes are a valid ed25519 secret key");
+    ed25519::Keypair::from(sk)
+}
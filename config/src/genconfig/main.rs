@@ -3,6 +3,7 @@
 
 use clap::{load_yaml, App};
 use config::{OutputType, generate_configs};
+use crypto::{PrecomputeConfig, ProofMode};
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -43,15 +44,33 @@ fn main() -> Result<(), Box<dyn Error>> {
     let cli_base_port = m.value_of("client_base_port")
         .unwrap_or("5000")
         .parse()?;
+    let proof_mode = match m.value_of("proof_mode").unwrap_or("signed") {
+        "sok" => ProofMode::SignatureOfKnowledge,
+        _ => ProofMode::Signed,
+    };
+    let generator_seed = m.value_of("seed").unwrap_or("optrand");
+    let test_seed = match m.value_of("test_seed") {
+        Some(x) => Some(x.parse()?),
+        None => None,
+    };
+    let precompute = PrecomputeConfig {
+        cache_path: m.value_of("precompute_cache").map(|s| s.to_string()),
+        memory_budget: match m.value_of("precompute_budget") {
+            Some(x) => Some(x.parse()?),
+            None => None,
+        },
+    };
     
-    let (nodes, clients) = generate_configs(num_nodes, num_faults, delay, base_port, num_clients, cli_base_port)?;
+    let (mut nodes, mut clients) = generate_configs(num_nodes, num_faults, delay, base_port, num_clients, cli_base_port, proof_mode, generator_seed, test_seed)?;
 
     // Write all the files
     for i in 0..num_nodes {
+        nodes[i].precompute = precompute.clone();
         nodes[i].validate()?;
         nodes[i].write_file(out, target);
     }
     for i in 0..num_clients {
+        clients[i].precompute = precompute.clone();
         clients[i].validate()?;
         clients[i].write_file(out, target);
     }
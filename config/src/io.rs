@@ -55,8 +55,9 @@ impl Node {
         log::debug!("Talking to servers: {:?}", self.net_map);
     }
 
+    /// Writes the config, along with the secrets of the node, to a file in basename
     pub fn write_file(&self, out: OutputType, basename: &str) {
-        match out {
+        crypto::export_secret(|| match out {
             OutputType::JSON => {
                 let filename = format!("{}/nodes-{}.json", basename, self.id);
                 write_json(filename, self);
@@ -73,13 +74,49 @@ impl Node {
                 let filename = format!("{}/nodes-{}.yml", basename, self.id);
                 write_yaml(filename, self);
             }
-        }
+        })
+    }
 
+    /// Writes my current PVSS context (with the pending and the retired keys) back to the file this config was loaded from
+    /// The rest of the file is kept, so the command line overrides (e.g., delta) are not written
+    /// Does nothing if the config was not loaded from a file
+    pub fn persist_pvss_ctx(&self) {
+        let filename = match &self.config_file {
+            None => return,
+            Some(filename) => filename,
+        };
+        let mut saved: Node = match extension(filename) {
+            "json" => serde_json::from_reader(File::open(filename).unwrap()).unwrap(),
+            "dat" => bincode::deserialize_from(File::open(filename).unwrap()).unwrap(),
+            "toml" => toml::from_str(&std::fs::read_to_string(filename).unwrap()).unwrap(),
+            "yaml" => serde_yaml::from_reader(File::open(filename).unwrap()).unwrap(),
+            _ => panic!("Invalid config file extension"),
+        };
+        saved.pvss_ctx = self.pvss_ctx.clone();
+        // Write to a temporary file first, so that a crash does not leave a partial config
+        let tmp = format!("{}.tmp", filename);
+        crypto::export_secret(|| match extension(filename) {
+            "json" => write_json(tmp.clone(), &saved),
+            "dat" => write_bin(tmp.clone(), &saved),
+            "toml" => write_toml(tmp.clone(), &saved),
+            _ => write_yaml(tmp.clone(), &saved),
+        });
+        if let Err(e) = std::fs::rename(&tmp, filename) {
+            log::error!("Failed to write the PVSS keys to {}: {}", filename, e);
+        }
     }
 }
 
 use util::io::*;
 
+/// The extension of a config file, which gives its format
+fn extension(filename: &str) -> &str {
+    std::path::Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum OutputType {
     JSON,
@@ -141,8 +178,9 @@ impl Reconfig {
         log::debug!("Talking to servers: {:?}", self.net_map);
     }
 
+    /// Writes the config, along with the secrets of the node, to a file in basename
     pub fn write_file(&self, out: OutputType, basename: &str) {
-        match out {
+        crypto::export_secret(|| match out {
             OutputType::JSON => {
                 let filename = format!("{}/nodes-{}.json", basename, self.id);
                 write_json(filename, self);
@@ -159,7 +197,6 @@ impl Reconfig {
                 let filename = format!("{}/nodes-{}.yml", basename, self.id);
                 write_yaml(filename, self);
             }
-        }
-
+        })
     }
 }
@@ -15,4 +15,9 @@ pub use reconfig::*;
 
 pub(crate) mod cert;
 
-pub const INITIAL_PVSS_BUFFER: usize = 10;
\ No newline at end of file
+pub const INITIAL_PVSS_BUFFER: usize = 10;
+
+/// The curve used by configs generated before the curve was recorded
+pub(crate) fn legacy_curve() -> String {
+    "bls12-381".to_string()
+}
\ No newline at end of file
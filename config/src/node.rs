@@ -2,9 +2,11 @@ use std::collections::VecDeque;
 use types::{DecompositionProof, Result, error::Error};
 use serde::{Deserialize, Serialize};
 use fnv::FnvHashMap as HashMap;
-use types::{AggregatePVSS, DbsContext, Replica};
+use types::{AggregatePVSS, BlsKeyMap, BlsKeypair, BlsPublicKey, BlsSecretKey, DbsContext, Epoch, KeyPop, Replica};
 use crypto_lib::Algorithm;
 use crypto::hash::Hash;
+use crypto::{DSSSecretKey, SecretValue};
+use crypto::rand::rngs::StdRng;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Node {
@@ -26,8 +28,39 @@ pub struct Node {
     // Authentication primitives
     pub crypto_alg: crypto_lib::Algorithm,
 
+    /// The pairing curve used to generate the PVSS configs
+    /// Configs without a curve were generated with bls12-381
+    #[serde(default = "crate::legacy_curve")]
+    pub curve: String,
+    /// The format of the DLEQ proofs in the PVSS sharings and decryptions
+    /// Configs without a proof mode use signed proofs
+    #[serde(default)]
+    pub proof_mode: crypto::ProofMode,
+    /// Where to cache the PVSS precomputation, and how much memory it can use
+    #[serde(default)]
+    pub precompute: crypto::PrecomputeConfig,
+    /// TEST ONLY: A seed for all the protocol randomness of this node (PVSS sharings, DLEQ nonces, precomputation codewords)
+    /// Runs with the same seed are reproducible, but anyone who knows the seed knows all the secrets
+    #[serde(default)]
+    pub test_seed: Option<u64>,
+    /// Rotate the PVSS key (in a block that I propose) if it was not rotated in this many epochs
+    /// The key is never rotated if this is not set
+    #[serde(default)]
+    pub key_rotation_interval: Option<Epoch>,
     /// PVSS configs - Contains the generators, pvss public keys, pvss secret keys, etc.
     pub pvss_ctx: DbsContext,
+    /// The file this config was loaded from, if any
+    /// The PVSS keys are written back to it when they change (see `persist_pvss_ctx`)
+    #[serde(skip)]
+    pub config_file: Option<String>,
+    /// The BLS public keys of all the nodes for aggregate certificates, along with their proofs of possession
+    /// Configs without BLS keys cannot use aggregate certificates
+    #[serde(default)]
+    #[serde(serialize_with = "crypto::canonical_serialize")]
+    #[serde(deserialize_with = "crypto::canonical_deserialize")]
+    pub bls_public_keys: Vec<BlsPublicKey>,
+    #[serde(default)]
+    pub bls_key_pops: Vec<KeyPop>,
 
     // Beacon data structures
 
@@ -42,13 +75,21 @@ pub struct Node {
 
     /// OpenSSL Certificate Details
     pub my_cert: Vec<u8>,
-    pub my_cert_key: Vec<u8>,
+    #[serde(serialize_with = "crypto::serialize_secret")]
+    #[serde(deserialize_with = "crypto::deserialize_secret")]
+    pub my_cert_key: SecretValue<Vec<u8>>,
     pub root_cert: Vec<u8>,
 
     // Caches
     my_ip_addr: String,
     pk_map_internal: HashMap<Replica, Vec<u8>>,
-    secret_key_bytes_internal: Vec<u8>,
+    #[serde(serialize_with = "crypto::serialize_secret")]
+    #[serde(deserialize_with = "crypto::deserialize_secret")]
+    secret_key_bytes_internal: SecretValue<Vec<u8>>,
+    #[serde(default)]
+    #[serde(serialize_with = "crypto::serialize_canonical_secret")]
+    #[serde(deserialize_with = "crypto::deserialize_canonical_secret")]
+    bls_secret_key_internal: SecretValue<BlsSecretKey>,
 }
 
 impl Node {
@@ -59,20 +100,29 @@ impl Node {
             crypto_alg: crypto_lib::Algorithm::ED25519,
             delta: 50,
             cli_port:0,
+            curve: types::CURVE.to_string(),
+            proof_mode: crypto::ProofMode::default(),
+            precompute: crypto::PrecomputeConfig::default(),
+            test_seed: None,
+            key_rotation_interval: None,
             pvss_ctx: dbs_ctx,
+            config_file: None,
+            bls_public_keys: Vec::default(),
+            bls_key_pops: Vec::default(),
             num_nodes: 1,
             id: usize::default(),
             net_map: HashMap::default(),
             num_faults: usize::default(),
             pk_map_internal: HashMap::default(),
-            secret_key_bytes_internal: sk_bytes,
+            secret_key_bytes_internal: SecretValue::new(sk_bytes),
+            bls_secret_key_internal: SecretValue::default(),
             rand_beacon_queue: HashMap::default(),
             leader_beacon_queue: VecDeque::default(),
             pool_of_verified_shares: HashMap::default(),
             my_ip_addr: String::default(),
             my_cert:Vec::default(),
             root_cert:Vec::default(),
-            my_cert_key:Vec::default(),
+            my_cert_key:SecretValue::default(),
         }
     }
 
@@ -81,21 +131,49 @@ impl Node {
         std::mem::swap(&mut self.pk_map_internal, &mut map);
     }
 
+    /// Sets my BLS key pair, and the BLS public keys of all the nodes with their proofs of possession - Only to be used when generating configs
+    pub fn set_bls_keys(&mut self, kp: &BlsKeypair, pks: Vec<BlsPublicKey>, pops: Vec<KeyPop>) {
+        self.bls_secret_key_internal = SecretValue::new(*kp.secret().expose_secret());
+        self.bls_public_keys = pks;
+        self.bls_key_pops = pops;
+    }
+
     /// Init intializes all the caches such as my_ip, etc
     pub fn init(mut self) -> Self {
         self.my_ip_addr = self.net_map.get(&self.id)
             .expect("Attempted to init a config without assigning an IP to self")
             .clone();
-        self.pvss_ctx.init(&mut crypto::std_rng());
+        if self.test_seed.is_some() {
+            log::warn!("Using a test seed for the protocol randomness; never do this in deployments");
+        }
+        self.pvss_ctx.init_with(&self.precompute, &mut self.rng(b"precompute"));
+        self.pvss_ctx.set_proof_mode(self.proof_mode);
         self
     }
 
+    /// Returns the rng for the purpose label
+    /// It is deterministic if there is a test seed in this config (see crypto::seeded_rng)
+    pub fn rng(&self, label: &[u8]) -> StdRng {
+        crypto::rng_for(self.test_seed, self.id, label)
+    }
+
     /// Returns a copy of the secret key (KEYPAIR) in this config
-    pub fn get_secret_key(&self) -> crypto_lib::Keypair {
+    pub fn get_secret_key(&self) -> DSSSecretKey {
+        let mut bytes = self.secret_key_bytes_internal.clone();
         let sk = 
-        crypto_lib::ed25519::Keypair::decode(&mut self.secret_key_bytes_internal.clone())
+        crypto_lib::ed25519::Keypair::decode(bytes.expose_secret_mut())
             .expect("Failed to recover secret key from config");
-        crypto_lib::Keypair::Ed25519(sk)
+        DSSSecretKey::new(crypto_lib::Keypair::Ed25519(sk))
+    }
+
+    /// Returns a copy of my BLS key pair
+    pub fn get_bls_keypair(&self) -> BlsKeypair {
+        BlsKeypair::from_secret(*self.bls_secret_key_internal.expose_secret())
+    }
+
+    /// Returns the BLS public keys of the nodes, after checking their proofs of possession
+    pub fn get_bls_key_map(&self) -> Result<BlsKeyMap> {
+        BlsKeyMap::new(&self.bls_public_keys, &self.bls_key_pops)
     }
 
     /// Returns the public key map from this config
@@ -119,6 +197,29 @@ impl Node {
     ///
     /// Called when loading a fresh config or checking when generating a new config
     pub fn validate(&self) -> Result<()> {
+        // The config must be for the curve this binary was built with
+        if self.curve != types::CURVE {
+            return Err(Error::ParseCurveMismatch(types::CURVE, self.curve.clone()));
+        }
+        // The PVSS generators must be derived from a public seed, so that nobody knows their discrete logs
+        if let Some(e) = self.pvss_ctx.check_generators() {
+            return Err(Error::ParseInvalidGenerators(format!("{:?}", e)));
+        }
+        // Every PVSS public key must come with a proof of possession
+        if let Some(e) = self.pvss_ctx.check_key_pops() {
+            return Err(Error::ParseInvalidKeyPops(format!("{:?}", e)));
+        }
+        // The BLS keys (if any) must be for every node, come with proofs of possession, and include mine
+        if !self.bls_public_keys.is_empty() {
+            if self.bls_public_keys.len() != self.num_nodes {
+                return Err(Error::ParseInvalidMapLen(self.num_nodes, self.bls_public_keys.len()));
+            }
+            self.get_bls_key_map()
+                .map_err(|e| Error::ParseInvalidBlsKeys(format!("{}", e)))?;
+            if self.get_bls_keypair().pk != self.bls_public_keys[self.id] {
+                return Err(Error::ParseInvalidBlsKeys(format!("My BLS key is not the key of {}", self.id)));
+            }
+        }
         // a valid config has n > 2f or 2f < n
         if 2 * self.num_faults >= self.num_nodes {
             return Err(Error::ParseIncorrectFaults(self.num_faults, self.num_nodes));
@@ -144,8 +245,8 @@ impl Node {
                         return Err(Error::ParseInvalidPkSize(repl.1.len()));
                     }
                 }
-                if self.secret_key_bytes_internal.len() != crypto_lib::ED25519_PVT_SIZE {
-                    return Err(Error::ParseInvalidSkSize(self.secret_key_bytes_internal.len()));
+                if self.secret_key_bytes_internal.expose_secret().len() != crypto_lib::ED25519_PVT_SIZE {
+                    return Err(Error::ParseInvalidSkSize(self.secret_key_bytes_internal.expose_secret().len()));
                 }
             }
             // Intentionally disabled for performance
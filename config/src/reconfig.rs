@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use fnv::FnvHashMap as HashMap;
 use types::{DbsContext, Replica};
 use crypto_lib::Algorithm;
+use crypto::{DSSSecretKey, SecretValue};
+use crypto::rand::rngs::StdRng;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Reconfig {
@@ -23,6 +25,21 @@ pub struct Reconfig {
     // Authentication primitives
     pub crypto_alg: crypto_lib::Algorithm,
 
+    /// The pairing curve used to generate the PVSS configs
+    /// Configs without a curve were generated with bls12-381
+    #[serde(default = "crate::legacy_curve")]
+    pub curve: String,
+    /// The format of the DLEQ proofs in the PVSS sharings and decryptions
+    /// Configs without a proof mode use signed proofs
+    #[serde(default)]
+    pub proof_mode: crypto::ProofMode,
+    /// Where to cache the PVSS precomputation, and how much memory it can use
+    #[serde(default)]
+    pub precompute: crypto::PrecomputeConfig,
+    /// TEST ONLY: A seed for all the protocol randomness of this node (PVSS sharings, DLEQ nonces, precomputation codewords)
+    /// Runs with the same seed are reproducible, but anyone who knows the seed knows all the secrets
+    #[serde(default)]
+    pub test_seed: Option<u64>,
     /// PVSS configs - Contains the generators, pvss public keys, pvss secret keys, etc.
     pub pvss_ctx: DbsContext,
 
@@ -30,13 +47,17 @@ pub struct Reconfig {
 
     /// OpenSSL Certificate Details
     pub my_cert: Vec<u8>,
-    pub my_cert_key: Vec<u8>,
+    #[serde(serialize_with = "crypto::serialize_secret")]
+    #[serde(deserialize_with = "crypto::deserialize_secret")]
+    pub my_cert_key: SecretValue<Vec<u8>>,
     pub root_cert: Vec<u8>,
 
     // Caches
     my_ip_addr: String,
     pk_map_internal: HashMap<Replica, Vec<u8>>,
-    secret_key_bytes_internal: Vec<u8>,
+    #[serde(serialize_with = "crypto::serialize_secret")]
+    #[serde(deserialize_with = "crypto::deserialize_secret")]
+    secret_key_bytes_internal: SecretValue<Vec<u8>>,
 }
 
 impl Reconfig {
@@ -46,17 +67,21 @@ impl Reconfig {
         Self {
             crypto_alg: crypto_lib::Algorithm::ED25519,
             delta: 50,
+            curve: types::CURVE.to_string(),
+            proof_mode: crypto::ProofMode::default(),
+            precompute: crypto::PrecomputeConfig::default(),
+            test_seed: None,
             pvss_ctx: dbs_ctx,
             num_nodes: 1,
             id: usize::default(),
             net_map: HashMap::default(),
             num_faults: usize::default(),
             pk_map_internal: HashMap::default(),
-            secret_key_bytes_internal: sk_bytes,
+            secret_key_bytes_internal: SecretValue::new(sk_bytes),
             my_ip_addr: String::default(),
             my_cert:Vec::default(),
             root_cert:Vec::default(),
-            my_cert_key:Vec::default(),
+            my_cert_key:SecretValue::default(),
         }
     }
 
@@ -67,16 +92,27 @@ impl Reconfig {
 
     /// Init intializes all the caches such as my_ip, etc
     pub fn init(mut self) -> Self {
-        self.pvss_ctx.init(&mut crypto::std_rng());
+        if self.test_seed.is_some() {
+            log::warn!("Using a test seed for the protocol randomness; never do this in deployments");
+        }
+        self.pvss_ctx.init_with(&self.precompute, &mut self.rng(b"precompute"));
+        self.pvss_ctx.set_proof_mode(self.proof_mode);
         self
     }
 
+    /// Returns the rng for the purpose label
+    /// It is deterministic if there is a test seed in this config (see crypto::seeded_rng)
+    pub fn rng(&self, label: &[u8]) -> StdRng {
+        crypto::rng_for(self.test_seed, self.id, label)
+    }
+
     /// Returns a copy of the secret key (KEYPAIR) in this config
-    pub fn get_secret_key(&self) -> crypto_lib::Keypair {
+    pub fn get_secret_key(&self) -> DSSSecretKey {
+        let mut bytes = self.secret_key_bytes_internal.clone();
         let sk = 
-        crypto_lib::ed25519::Keypair::decode(&mut self.secret_key_bytes_internal.clone())
+        crypto_lib::ed25519::Keypair::decode(bytes.expose_secret_mut())
             .expect("Failed to recover secret key from config");
-        crypto_lib::Keypair::Ed25519(sk)
+        DSSSecretKey::new(crypto_lib::Keypair::Ed25519(sk))
     }
 
     /// Returns the public key map from this config
@@ -100,6 +136,18 @@ impl Reconfig {
     ///
     /// Called when loading a fresh config or checking when generating a new config
     pub fn validate(&self) -> Result<()> {
+        // The config must be for the curve this binary was built with
+        if self.curve != types::CURVE {
+            return Err(Error::ParseCurveMismatch(types::CURVE, self.curve.clone()));
+        }
+        // The PVSS generators must be derived from a public seed, so that nobody knows their discrete logs
+        if let Some(e) = self.pvss_ctx.check_generators() {
+            return Err(Error::ParseInvalidGenerators(format!("{:?}", e)));
+        }
+        // Every PVSS public key must come with a proof of possession
+        if let Some(e) = self.pvss_ctx.check_key_pops() {
+            return Err(Error::ParseInvalidKeyPops(format!("{:?}", e)));
+        }
         // a valid config has n > 2f or 2f < n
         if 2 * self.num_faults >= self.num_nodes {
             return Err(Error::ParseIncorrectFaults(self.num_faults, self.num_nodes));
@@ -125,8 +173,8 @@ impl Reconfig {
                         return Err(Error::ParseInvalidPkSize(repl.1.len()));
                     }
                 }
-                if self.secret_key_bytes_internal.len() != crypto_lib::ED25519_PVT_SIZE {
-                    return Err(Error::ParseInvalidSkSize(self.secret_key_bytes_internal.len()));
+                if self.secret_key_bytes_internal.expose_secret().len() != crypto_lib::ED25519_PVT_SIZE {
+                    return Err(Error::ParseInvalidSkSize(self.secret_key_bytes_internal.expose_secret().len()));
                 }
             }
             // Intentionally disabled for performance
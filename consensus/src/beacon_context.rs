@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+
+use crypto::{DSSPublicKey, PvssScheme};
+use fnv::FnvHashMap as HashMap;
+use types::{DbsContext, Epoch, Replica, Result};
+
+/// The beacon shares and the beacons of the epochs, used by both state machines
+pub(crate) struct BeaconContext<S: PvssScheme = DbsContext> {
+    /// These are the beacons for every epoch 
+    /// Specified as O_r in the protocol
+    epoch_beacons: HashMap<Epoch, S::Beacon>,
+    /// The pvss that we will be checking decryptions against in every epoch
+    epoch_pvss: HashMap<Epoch, S::Aggregate>,
+    /// Unverified shares received for epochs before we reach the epoch
+    unverified_epoch_shares: HashMap<Epoch, VecDeque<(Replica, S::Decryption)>>,
+    /// The (unverified) shares for an epoch that we are in, indexed by the sender
+    /// OPTIMIZATION: We only verify them if the optimistic reconstruction fails
+    epoch_shares: HashMap<Epoch, Vec<Option<S::Decryption>>>,
+    /// Number of shares received for an epoch
+    num_shares: HashMap<Epoch, usize>,
+    /// The shares that we know are valid, for the epochs where the optimistic reconstruction failed
+    /// From then on, we verify every share once when it arrives
+    verified_shares: HashMap<Epoch, Vec<bool>>,
+}
+
+impl<S: PvssScheme> Default for BeaconContext<S> {
+    fn default() -> Self {
+        Self {
+            epoch_beacons: HashMap::default(),
+            epoch_pvss: HashMap::default(),
+            unverified_epoch_shares: HashMap::default(),
+            epoch_shares: HashMap::default(),
+            num_shares: HashMap::default(),
+            verified_shares: HashMap::default(),
+        }
+    }
+}
+
+impl<S> BeaconContext<S>
+where S: PvssScheme,
+    S::Beacon: Clone,
+    S::Decryption: Clone,
+{
+    pub(crate) fn add_epoch_pvss(&mut self, 
+        e: Epoch,
+        pvec: S::Aggregate,
+        num_nodes: usize,
+    ) 
+    {
+        self.epoch_pvss.insert(e, pvec);
+        // Others may have already added shares onto this and we may be entering the epoch at a later time
+        if !self.unverified_epoch_shares.contains_key(&e) {
+            self.unverified_epoch_shares.insert(e, VecDeque::new());
+        }
+        self.num_shares.insert(e, 0);
+
+        let mut deq = Vec::with_capacity(num_nodes);
+        deq.resize(num_nodes, None);
+        self.epoch_shares.insert(e, deq);
+    }
+
+    /// `thresh` should be f
+    pub(crate) fn add_beacon_share(&mut self,
+        pvss_ctx: &S,
+        pk_map: &HashMap<Replica, DSSPublicKey>,
+        e: Epoch,
+        from: Replica,
+        dec: S::Decryption,
+        num_faults: usize,
+    ) -> Result<Option<S::Beacon>>
+    {
+        // Already finished this epoch, move on
+        if self.epoch_beacons.contains_key(&e) {
+            return Ok(None);
+        }
+
+        // Are we ready for Epoch e
+        if !self.epoch_pvss.contains_key(&e) {
+            // We are not ready for epoch e
+            if self.unverified_epoch_shares.contains_key(&e) {
+                self.unverified_epoch_shares
+                    .get_mut(&e)
+                    .ok_or(format!("We just added a vec if there wasn't one"))?
+                    .push_back((from, dec));
+            } else {
+                let mut vec = VecDeque::new();
+                vec.push_back((from, dec));
+                self.unverified_epoch_shares.insert(e, vec);
+            }
+            return Ok(None)
+        }
+        // We are ready for epoch e (Then the shares for e are set)
+        // 1) Add this share and the shares received before we were ready to the shares for the epoch
+        // 2) Once there are t+1 shares, try the optimistic reconstruction, and forget the shares of the nodes it blames
+        // 3) If it failed before, only verify the new shares and reconstruct from the valid ones
+        let shares = self.epoch_shares
+            .get_mut(&e)
+            .ok_or(
+                format!("We are ready for e, we must have inserted it in add_epoch_pvss")
+            )?;
+        let num_shares = self.num_shares
+            .get_mut(&e)
+            .ok_or(
+                format!("We are ready for e, we must have inserted it in add_epoch_pvss")
+            )?;
+
+        // Step 1
+        let mut pending = self.unverified_epoch_shares
+            .remove(&e)
+            .unwrap_or_default();
+        pending.push_back((from, dec));
+        for (from, dec) in pending {
+            if from >= shares.len() || shares[from].is_some() {
+                log::warn!("Ignoring an extra beacon share from {} for {}", from, e);
+                continue;
+            }
+            shares[from] = Some(dec);
+            *num_shares += 1;
+        }
+        if *num_shares <= num_faults {
+            return Ok(None);
+        }
+
+        let pvss = self.epoch_pvss
+            .get(&e)
+            .ok_or(
+                format!("We just checked if have the epoch pvss or not")
+            )?;
+
+        // Step 2
+        let verified = match self.verified_shares.get_mut(&e) {
+            Some(verified) => verified,
+            None => {
+                let (beacon, blamed) = pvss_ctx.reconstruct_optimistic(e as u64, pvss, shares, pk_map);
+                for culprit in blamed {
+                    log::warn!("Invalid beacon share from {} for {}", culprit, e);
+                    shares[culprit] = None;
+                    *num_shares -= 1;
+                }
+                if let Some(beacon) = beacon {
+                    return self.finish_epoch(e, beacon);
+                }
+                // Unfortunately we did not succeed in reconstructing
+                // The reconstruction has verified all the shares, so the ones that are left are valid
+                let verified = shares.iter().map(|s| s.is_some()).collect();
+                self.verified_shares.insert(e, verified);
+                return Ok(None);
+            }
+        };
+
+        // Step 3
+        for i in 0..shares.len() {
+            if verified[i] {
+                continue;
+            }
+            let dec = match &shares[i] {
+                Some(dec) => dec,
+                None => continue,
+            };
+            let valid = match (pk_map.get(&i), pvss_ctx.encryption(pvss, i)) {
+                (Some(pk), Some(enc)) => pvss_ctx.verify_share(i, e as u64, enc, dec, pk).is_none(),
+                _ => false,
+            };
+            if valid {
+                verified[i] = true;
+            } else {
+                log::warn!("Invalid beacon share from {} for {}", i, e);
+                shares[i] = None;
+                *num_shares -= 1;
+            }
+        }
+        if *num_shares <= num_faults {
+            return Ok(None);
+        }
+        let decs: Vec<_> = shares.iter()
+            .map(|s| s.as_ref().map(|d| pvss_ctx.share_of(d)))
+            .collect();
+        let beacon = pvss_ctx.reconstruct(&decs);
+        self.finish_epoch(e, beacon)
+    }
+
+    /// Call after reconstructing the beacon for epoch e
+    fn finish_epoch(&mut self, e: Epoch, beacon: S::Beacon) -> Result<Option<S::Beacon>>
+    {
+        self.epoch_beacons.insert(e, beacon.clone());
+        self.epoch_pvss.remove(&e);
+        self.epoch_shares.remove(&e);
+        self.num_shares.remove(&e);
+        self.verified_shares.remove(&e);
+        self.unverified_epoch_shares.remove(&e);
+        return Ok(Some(beacon))
+    }
+}
@@ -12,6 +12,9 @@ pub(crate) use ev_queue::*;
 mod optimization;
 pub use optimization::*;
 
+mod beacon_context;
+pub(crate) use beacon_context::*;
+
 // pub mod reconfig;
 
 use types::{ProtocolMsg, ReconfigurationMsg, Replica};
@@ -1,2 +0,0 @@
-mod beacon_context;
-pub(crate) use beacon_context::*;
\ No newline at end of file
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use crypto::PvssScheme;
 use types::{Decryption, Epoch, ProtocolMsg, Replica, Result};
 use crate::{Event, NewMessage, OutMsg, ev_queue::EventQueue};
 use super::OptRandStateMachine;
@@ -25,7 +26,13 @@ impl OptRandStateMachine {
         log::debug!("Getting PVSS vec for {}", self.leader_ctx.current_leader());
         let pvss = self.storage.cleave_beacon_share(self.leader_ctx.current_leader())?;
 
-        let my_share = self.config.pvss_ctx.decrypt_share(&pvss.encs[self.config.id], &self.sk, &mut self.rng);
+        // The pvss may be encrypted under the keys from before a rotation
+        let pvss_ctx = self.config.pvss_ctx
+            .for_key_version(pvss.key_version)
+            .ok_or(format!("We no longer have the PVSS keys of version {} for {}", pvss.key_version, e))?;
+        let my_enc = pvss_ctx.encryption(&pvss, self.config.id)
+            .ok_or(format!("The PVSS for {} has no share for me", e))?;
+        let my_share = PvssScheme::decrypt_share(pvss_ctx, e as u64, my_enc, &self.sk, &mut self.rng);
         self.beacon_ctx.add_epoch_pvss(e, pvss, self.config.num_nodes);
         // Send my shares to all the nodes
         let msg = self.new_beacon_share_msg(e, my_share.clone());
@@ -50,7 +57,6 @@ impl OptRandStateMachine {
         ev_queue: &mut EventQueue,
     ) -> Result<()> {
         let beacon_opt = self.beacon_ctx.add_beacon_share(&self.config.pvss_ctx, 
-            self.config.id,
             &self.pk_map, 
             e, 
             from, 
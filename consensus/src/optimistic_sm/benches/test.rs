@@ -13,8 +13,8 @@ where R: Rng,
     let (c1, c2) = (configs.pop_front().unwrap(), configs.pop_front().unwrap());
     let (sk1, sk2) = (c1.get_secret_key(), c2.get_secret_key());
     let (pvec1, pvec2) = (
-        c1.pvss_ctx.generate_shares(&sk1, rng),
-        c2.pvss_ctx.generate_shares(&sk2, rng),
+        c1.pvss_ctx.generate_shares(1, &sk1, rng),
+        c2.pvss_ctx.generate_shares(1, &sk2, rng),
     );
     let indices = [0 as usize,1];
     let pvec = [pvec1, pvec2].to_vec();
@@ -30,7 +30,7 @@ pub fn dummy_vote(_n:usize) -> Vote {
 
 pub fn dummy_certificate(n:usize) -> Certificate<Vote> 
 {
-    let sk = crypto_lib::Keypair::generate_ed25519();
+    let sk = crypto::DSSSecretKey::generate_ed25519();
     let vote = dummy_vote(n);
     let mut cert = Certificate::new_cert(&vote, 1, &sk).unwrap();
     for i in 1..n {
@@ -75,7 +75,7 @@ pub fn dummy_direct_proposal_proof(n:usize) -> Proof<DirectProposal>
     let (acc, _,_) = prop_acc_builder.set_n(n)
         .set_f(reed_solomon_threshold(n)-1)
         .build(&prop).unwrap();
-    let sk = crypto_lib::Keypair::generate_ed25519();
+    let sk = crypto::DSSSecretKey::generate_ed25519();
     // let proof = {
         let sign = Certificate::new_cert(&(1, acc.clone()),1, &sk).unwrap();
     //     let mut proof = ProofBuilder::default(); 
@@ -171,7 +171,7 @@ fn dummy_resp_cert_prop(n: usize) -> (types::Proposal<types::RespCertData>, Proo
     let mut resp_cert_acc_builder = MTAccumulatorBuilder::new();
     resp_cert_acc_builder.set_f(reed_solomon_threshold(n)-1)
     .set_n(n);
-    let sk = crypto_lib::Keypair::generate_ed25519();
+    let sk = crypto::DSSSecretKey::generate_ed25519();
     let cert = dummy_certificate(n);
         let prop = {
             let mut prop_builder = ProposalBuilder::default();
@@ -127,6 +127,9 @@ impl OptRandStateMachine {
             }
         } 
 
+        // Switch to the PVSS keys rotated for this epoch
+        self.on_key_rotation(e)?;
+
         // Do beacon business
         self.on_beacon_share(e, ev_queue)
     }
@@ -43,6 +43,9 @@ pub(crate) use leader::*;
 mod beacon_share;
 pub use beacon_share::*;
 
+mod key_rotation;
+pub use key_rotation::*;
+
 mod reactor;
 pub use reactor::*;
 
@@ -61,9 +64,6 @@ pub use resp_cert::*;
 mod deliver_resp_cert;
 pub use deliver_resp_cert::*;
 
-mod beacon;
-pub(crate) use beacon::*;
-
 /// Expose public functions to benchmark
 pub mod benches;
 
@@ -1,8 +1,7 @@
 use std::sync::Arc;
 
 use crate::{ThreadRecvMsg, ThreadSendMsg, ev_queue::EventQueue, optimistic_sm::OptRandStateMachine};
-use crypto::{hash::ser_and_hash};
-use types::{AggregatePVSS, DecompositionProof, ProtocolMsg, Replica, Result};
+use types::{AggregatePVSS, CanonicalHash, DecompositionProof, ProtocolMsg, Replica, Result};
 
 impl OptRandStateMachine {
     pub(crate) fn on_optimizer_event(&mut self, 
@@ -23,7 +22,7 @@ impl OptRandStateMachine {
             // Some other node's share is ready for use
             ThreadRecvMsg::VerifiedAggregateSharing(_from, agg) => {
                 // Store it in a buffer
-                let hash = ser_and_hash(&agg);
+                let hash = agg.canonical_hash();
                 self.config.pool_of_verified_shares.insert(hash, agg);
             }
         }
@@ -36,6 +35,12 @@ impl OptRandStateMachine {
         decomp: DecompositionProof,
     ) -> Result<()>
     {
+        // Only sharings dealt in this or the previous epoch can enter the pool, so old sharings cannot be replayed
+        let e = self.epoch as u64;
+        if agg.epoch.saturating_add(1) < e || agg.epoch > e {
+            log::warn!("Got an aggregate sharing from {} for epoch {} in epoch {}", from, agg.epoch, e);
+            return Ok(());
+        }
         self.leader_thread_sender.send(
             ThreadSendMsg::NewAggregateSharing(from, agg, decomp)
         ).map_err(|e| 
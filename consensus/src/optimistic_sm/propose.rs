@@ -13,15 +13,21 @@ impl OptRandStateMachine {
         self.rnd_ctx.start_timer();
 
         // Aggregate the shares
-        let (agg, decom) = if let Some(x) = self.config.leader_beacon_queue.pop_front() {
-            x
-        } else {
-            return Err(
-                Error::Generic(
-                    format!("Did not receive t+1 pvss vecs to agg in time")
-                )
-            );
+        let (agg, decom) = loop {
+            match self.config.leader_beacon_queue.pop_front() {
+                // Skip the aggregates encrypted under keys that we retired
+                Some((agg, _)) if self.config.pvss_ctx.for_key_version(agg.key_version).is_none() => {
+                    log::warn!("Dropping an aggregate for the retired key version {}", agg.key_version);
+                }
+                Some(x) => break x,
+                None => return Err(
+                    Error::Generic(
+                        format!("Did not receive t+1 pvss vecs to agg in time")
+                    )
+                ),
+            }
         };
+        let key_rotation = self.next_key_rotation();
         log::info!("Proposing Aggregated PVSS");
 
         // Build the proposal 
@@ -34,6 +40,7 @@ impl OptRandStateMachine {
                 .aggregate_pvss(agg)
                 .aggregate_proof(decom)
                 .proposer(self.config.id)
+                .key_rotation(key_rotation)
                 .build()?
         };
         let prop = {
@@ -1,63 +1,78 @@
 use std::sync::Arc;
 
-use crypto::{DSSPublicKey, DSSSecretKey, std_rng};
+use crypto::{DSSPublicKey, DSSSecretKey, PvssScheme, rand::rngs::StdRng};
 use fnv::FnvHashMap;
 use tokio::sync::mpsc::UnboundedSender;
-use types::{AggregatePVSS, DbsContext, DecompositionProof, PVSSVec, Replica};
+use types::{DbsContext, Replica};
 
-pub type PvecReceiver = tokio::sync::mpsc::Receiver<PVSSVec>;
-pub type ThreadSender = tokio::sync::mpsc::UnboundedSender<ThreadSendMsg>;
-pub type ThreadReceiver = tokio::sync::mpsc::UnboundedReceiver<ThreadRecvMsg>;
+// The threads work with any PVSS scheme, and the state machines use them with `DbsContext`
+pub type PvecReceiver<S = DbsContext> = tokio::sync::mpsc::Receiver<<S as PvssScheme>::Sharing>;
+pub type ThreadSender<S = DbsContext> = tokio::sync::mpsc::UnboundedSender<ThreadSendMsg<S>>;
+pub type ThreadReceiver<S = DbsContext> = tokio::sync::mpsc::UnboundedReceiver<ThreadRecvMsg<S>>;
 
-pub fn spawn_generator_thread(
+/// Generates one sharing per epoch, starting from start_epoch
+pub fn spawn_generator_thread<S>(
     num_nodes: usize,
-    dbs_ctx: DbsContext,
+    pvss_ctx: S,
     sk: DSSSecretKey,
-) -> PvecReceiver
+    start_epoch: u64,
+    mut rng: StdRng,
+) -> PvecReceiver<S>
+where S: PvssScheme + Send + 'static,
+    S::Sharing: Send,
 {
     let (ch_in, ch_out) = tokio::sync::mpsc::channel(num_nodes);
     tokio::spawn(async move {
-        let mut rng= std_rng();
-        loop {
-            let pvec = dbs_ctx.generate_shares(&sk, &mut rng);
+        for epoch in start_epoch.. {
+            let pvec = pvss_ctx.generate_shares(epoch, &sk, &mut rng);
             ch_in.send(pvec).await
-                .expect("Failed to send a freshly generated pvec");
+                .unwrap_or_else(|_| panic!("Failed to send a freshly generated pvec"));
         }
     });
     ch_out
 }
 
 #[derive(Debug)]
-pub enum ThreadSendMsg {
-    NewContribution(Replica, PVSSVec),
-    NewAggregateSharing(Replica, AggregatePVSS, DecompositionProof),
+pub enum ThreadSendMsg<S: PvssScheme = DbsContext> {
+    NewContribution(Replica, S::Sharing),
+    NewAggregateSharing(Replica, S::Aggregate, S::DecompProof),
+    /// The context after a key rotation
+    NewKeys(S),
 }
 
 #[derive(Debug, Clone)]
-pub enum ThreadRecvMsg {
-    AggregateReady(AggregatePVSS, DecompositionProof),
-    VerifiedAggregateSharing(Replica, AggregatePVSS),
+pub enum ThreadRecvMsg<S: PvssScheme = DbsContext> {
+    AggregateReady(S::Aggregate, S::DecompProof),
+    VerifiedAggregateSharing(Replica, S::Aggregate),
 }
 
 /// The job of this thread is to take shares and verify them
-pub fn spawn_leader_thread(
+pub fn spawn_leader_thread<S>(
     num_faults: usize,
-    dbs_ctx: DbsContext,
+    dbs_ctx: S,
     pk_map: FnvHashMap<Replica, DSSPublicKey>,
-) -> (ThreadSender, ThreadReceiver)
+) -> (ThreadSender<S>, ThreadReceiver<S>)
+where S: PvssScheme + Send + Sync + 'static,
+    S::Sharing: Send,
+    S::Aggregate: Send,
+    S::DecompProof: Send,
 {
-    let dbs_ctx = Arc::new(dbs_ctx);
+    let mut dbs_ctx = Arc::new(dbs_ctx);
     let pk_map = Arc::new(pk_map);
     let (sh_sender, mut sh_recv) = tokio::sync::mpsc::unbounded_channel();
     let (agg_sender, agg_recv) = tokio::sync::mpsc::unbounded_channel();
     let (internal_send, mut internal_recv) = tokio::sync::mpsc::unbounded_channel();
     tokio::spawn(async move {
-        let mut buffer = Vec::with_capacity(num_faults+1);
-        let mut indices = Vec::with_capacity(num_faults + 1);
+        // The verified vectors and their dealers, for every epoch and key version
+        let mut buffers: FnvHashMap<(u64, u64), (Vec<S::Sharing>, Vec<Replica>)> = FnvHashMap::default();
         loop {
             tokio::select! {
                 ev = sh_recv.recv() => {
                     let ev = ev.unwrap();
+                    if let ThreadSendMsg::NewKeys(ctx) = ev {
+                        dbs_ctx = Arc::new(ctx);
+                        continue;
+                    }
                     if let ThreadSendMsg::NewContribution(from, sh) = ev
                     {
                         let dbs_ctx = dbs_ctx.clone();
@@ -93,23 +108,29 @@ pub fn spawn_leader_thread(
                 job_opt = internal_recv.recv() => {
                     match job_opt.unwrap() {
                         InternalMsg::CorrectPVec(from, pvec) => {
+                        // Only aggregate vectors dealt in the same epoch and encrypted under the same keys
+                        // The state machine only forwards vectors for its current or next epoch, so there are few buffers
+                        let key = dbs_ctx.sharing_tag(&pvec);
+                        let (buffer, indices) = buffers.entry(key).or_default();
+                        if indices.contains(&from) {
+                            continue;
+                        }
                         buffer.push(pvec);
                         indices.push(from);
-                        let (pvec, inds) = if buffer.len() > num_faults {
-                            (
-                                std::mem::take(&mut buffer), 
-                                std::mem::take(&mut indices)
-                            )
-                        } else {
+                        if buffer.len() <= num_faults {
                             continue;
-                        };
-                        let data = dbs_ctx.aggregate(&inds, pvec);
+                        }
+                        let (pvec, inds) = buffers.remove(&key)
+                            .expect("The buffer was just filled");
+                        // The older epochs will not be aggregated anymore
+                        buffers.retain(|(epoch, _), _| *epoch > key.0);
+                        let data = PvssScheme::aggregate(&*dbs_ctx, &inds, pvec);
                         agg_sender.send(
                             ThreadRecvMsg::AggregateReady(
                                 data.0, 
                                 data.1
                             )
-                        ).expect("Failed to send aggregated values");
+                        ).unwrap_or_else(|_| panic!("Failed to send aggregated values"));
                         },
                         // _ => continue,
                     }
@@ -120,40 +141,42 @@ pub fn spawn_leader_thread(
     (sh_sender, agg_recv)
 }
 
-#[derive(Debug)]
-enum InternalMsg {
-    CorrectPVec(Replica, PVSSVec),
+enum InternalMsg<S: PvssScheme> {
+    CorrectPVec(Replica, S::Sharing),
 }
 
-fn handle_new_contribution(
-    dbs_ctx: Arc<DbsContext>, 
+fn handle_new_contribution<S>(
+    pvss_ctx: Arc<S>, 
     pk_map: Arc<FnvHashMap<Replica, DSSPublicKey>>,
     from: Replica,
-    sh: PVSSVec,
-    internal_send: UnboundedSender<InternalMsg>,
+    sh: S::Sharing,
+    internal_send: UnboundedSender<InternalMsg<S>>,
 )
+where S: PvssScheme,
 {
-    if let None = dbs_ctx.verify_sharing(&sh, &pk_map[&from]) {
-        internal_send.send(InternalMsg::CorrectPVec(from, sh)).unwrap();
+    if let None = pvss_ctx.verify_sharing(from, &sh, &pk_map[&from]) {
+        internal_send.send(InternalMsg::CorrectPVec(from, sh))
+            .unwrap_or_else(|_| panic!("Failed to send a verified pvec"));
     } else {
         log::warn!("Got an invalid pvec from {}", from);
         
     }
 }
 
-fn handle_new_aggregation(dbs_ctx: Arc<DbsContext>, 
+fn handle_new_aggregation<S>(pvss_ctx: Arc<S>, 
     pk_map: Arc<FnvHashMap<Replica, DSSPublicKey>>,
-    agg: AggregatePVSS,
-    decom: DecompositionProof,
+    agg: S::Aggregate,
+    decom: S::DecompProof,
     from: Replica,
-    agg_sender: tokio::sync::mpsc::UnboundedSender<ThreadRecvMsg>,
+    agg_sender: tokio::sync::mpsc::UnboundedSender<ThreadRecvMsg<S>>,
 )
+where S: PvssScheme,
 {
-    if let Some(x) = dbs_ctx.pverify(&agg) {
+    if let Some(x) = pvss_ctx.pverify(&agg) {
         log::warn!("Got an invalid agg sharing from {} with {:?}", from, x);
         return;
     } 
-    if let Some(x) = dbs_ctx.decomp_verify(&agg, &decom, &pk_map) {
+    if let Some(x) = pvss_ctx.decomp_verify(&agg, &decom, &pk_map) {
         log::warn!("Got an invalid decomp from {} with {:?}", from, x);
         return;
     }
@@ -162,5 +185,5 @@ fn handle_new_aggregation(dbs_ctx: Arc<DbsContext>,
             from, 
             agg
         )
-    ).expect("Failed to send verified agg sharing");
+    ).unwrap_or_else(|_| panic!("Failed to send verified agg sharing"));
 }
\ No newline at end of file
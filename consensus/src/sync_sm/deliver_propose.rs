@@ -97,6 +97,8 @@ impl OptRandStateMachine {
         } else {
             return Ok(());
         };
+        // The block is committed (and its key rotation applied) without going through the checks of a direct proposal
+        prop.block().check_key_rotation(self.epoch, &self.config.pvss_ctx)?;
         // Add proposal to storage
         let block = prop.block().clone();
         self.storage.add_proposal(prop, sh.acc, sh.sign)?;
@@ -0,0 +1,25 @@
+use types::{Epoch, KeyRotation, Result};
+use crate::ThreadSendMsg;
+use super::OptRandStateMachine;
+
+impl OptRandStateMachine {
+    /// Switches to the PVSS keys rotated in the committed blocks for epoch e, and forgets the old keys that we no longer need
+    pub(crate) fn on_key_rotation(&mut self, e: Epoch) -> Result<()> {
+        if self.key_rotations.switch_keys(&mut self.storage, &mut self.config.pvss_ctx, e) {
+            self.config.persist_pvss_ctx();
+            // The leader thread checks the sharings with its own copy of the context
+            self.leader_thread_sender.send(
+                ThreadSendMsg::NewKeys(self.config.pvss_ctx.clone())
+            ).map_err(|e| format!("Failed to send to leader thread: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Returns a rotation of my PVSS key for the block that I propose, if it is time to rotate
+    /// The new secret key is written to the config file first, so that a restart before the switch keeps it
+    pub(crate) fn next_key_rotation(&mut self) -> Option<KeyRotation> {
+        let rot = self.key_rotations.next_rotation(&mut self.config.pvss_ctx, self.epoch, &mut self.rng)?;
+        self.config.persist_pvss_ctx();
+        Some(rot)
+    }
+}
@@ -43,8 +43,8 @@ pub(crate) use leader::*;
 mod beacon_share;
 pub use beacon_share::*;
 
-mod beacon_context;
-pub(crate) use beacon_context::*;
+mod key_rotation;
+pub use key_rotation::*;
 
 mod reactor;
 pub use reactor::*;
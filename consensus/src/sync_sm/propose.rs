@@ -2,8 +2,7 @@ use std::sync::Arc;
 
 use crate::{Event, EventQueue, NewMessage, OutMsg, TimeOutEvent};
 use super::OptRandStateMachine;
-use crypto::hash::ser_and_hash;
-use types::{BlockBuilder, Certificate, DirectProposal, Epoch, Proof, ProofBuilder, ProposalBuilder, ProposalData, ProtocolMsg, Replica, Result, error::Error};
+use types::{BlockBuilder, CanonicalHash, Certificate, DirectProposal, Epoch, Proof, ProofBuilder, ProposalBuilder, ProposalData, ProtocolMsg, Replica, Result, error::Error};
 use types_upstream::WireReady;
 
 impl OptRandStateMachine {
@@ -13,15 +12,21 @@ impl OptRandStateMachine {
         log::info!("Time to propose");
 
         // Aggregate the shares
-        let (agg, decom) = if let Some(x) = self.config.leader_beacon_queue.pop_front() {
-            x
-        } else {
-            return Err(
-                Error::Generic(
-                    format!("Did not receive t+1 pvss vecs to agg in time")
-                )
-            );
+        let (agg, decom) = loop {
+            match self.config.leader_beacon_queue.pop_front() {
+                // Skip the aggregates encrypted under keys that we retired
+                Some((agg, _)) if self.config.pvss_ctx.for_key_version(agg.key_version).is_none() => {
+                    log::warn!("Dropping an aggregate for the retired key version {}", agg.key_version);
+                }
+                Some(x) => break x,
+                None => return Err(
+                    Error::Generic(
+                        format!("Did not receive t+1 pvss vecs to agg in time")
+                    )
+                ),
+            }
         };
+        let key_rotation = self.next_key_rotation();
         log::info!("Proposing Aggregated PVSS");
 
         // Build the proposal 
@@ -34,6 +39,7 @@ impl OptRandStateMachine {
                 .aggregate_pvss(agg)
                 .aggregate_proof(decom)
                 .proposer(self.config.id)
+                .key_rotation(key_rotation)
                 .build()?
         };
         let prop = {
@@ -120,7 +126,7 @@ impl OptRandStateMachine {
             &self.pk_map)?;
 
         // Check if the agg is already stored and ready
-        let h = ser_and_hash(prop.block().pvss());
+        let h = prop.block().pvss().canonical_hash();
         if !self.config.pool_of_verified_shares.contains_key(&h) {
             log::error!("We don't have the agg verified and ready");
             return Err(format!("Agg not ready").into());
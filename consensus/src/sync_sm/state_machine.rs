@@ -1,18 +1,19 @@
 use config::Node;
-use crypto::{rand::prelude::StdRng, std_rng};
-use crypto_lib::{Keypair, PublicKey};
-use types::{Block, Certificate, DirectProposal, Epoch, MTAccumulatorBuilder, Replica, START_EPOCH, Storage, SyncCertProposal, Vote, reed_solomon_threshold};
+use crypto::rand::prelude::StdRng;
+use crypto::DSSSecretKey;
+use crypto_lib::PublicKey;
+use types::{Block, Certificate, DirectProposal, Epoch, KeyRotations, MTAccumulatorBuilder, Replica, START_EPOCH, Storage, SyncCertProposal, Vote, reed_solomon_threshold};
 use fnv::FnvHashMap as HashMap;
 
-use crate::{ThreadReceiver, ThreadSender};
+use crate::{BeaconContext, ThreadReceiver, ThreadSender};
 
-use super::{BeaconContext, LeaderContext, RoundContext};
+use super::{LeaderContext, RoundContext};
 
 /// Builds all the messages for the protocol
 pub struct OptRandStateMachine {
     // General data
     pub(crate) config: Node,
-    pub(crate) sk: Keypair,
+    pub(crate) sk: DSSSecretKey,
     pub(crate) pk_map: HashMap<Replica, PublicKey>,
 
     // Round state
@@ -38,6 +39,9 @@ pub struct OptRandStateMachine {
     // Leader context
     pub(crate) leader_ctx: LeaderContext,
 
+    /// When to rotate my PVSS key, and switching to the rotated keys
+    pub(crate) key_rotations: KeyRotations,
+
     // Randomness for Crypto
     pub(crate) rng: StdRng,
 
@@ -55,7 +59,7 @@ impl OptRandStateMachine {
         let (storage, gen_arc) = {
             let rand_queue = std::mem::take(&mut config.rand_beacon_queue);
             let mut t = Storage::new(config.num_nodes, rand_queue);
-            t.add_delivered_block(Block::GENESIS_BLOCK);
+            t.add_delivered_block(Block::genesis());
             let gen_arc = t.get_delivered_block_by_height(Block::GENESIS_BLOCK.height()).expect("Could not find genesis block even after adding it to the storage");
             t.commit_block(gen_arc.clone())
                 .expect("Failed to commit the genesis block");
@@ -70,13 +74,15 @@ impl OptRandStateMachine {
             .set_n(config.num_nodes)
             .set_f(f);
         let leader_ctx = LeaderContext::new(config.num_nodes);
+        let rng = config.rng(b"state-machine");
+        let key_rotations = KeyRotations::new(config.key_rotation_interval);
         
         Self {
             config,
             epoch: START_EPOCH,
             sk: sk.clone(),
             highest_certificate: (Certificate::default(), gen_arc, Vote::GENESIS),
-            rng: std_rng(),
+            rng,
             rnd_ctx: RoundContext::default(),
             pk_map,
             storage,
@@ -84,6 +90,7 @@ impl OptRandStateMachine {
             sync_cert_acc_builder,
             beacon_ctx: BeaconContext::default(),
             leader_ctx,
+            key_rotations,
             // share_generator: ch,
             leader_thread_sender: ch.0,
             leader_thread_receiver: ch.1,
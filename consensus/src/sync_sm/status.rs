@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use crypto::PvssScheme;
 use types::{Certificate, PVSSVec, ProtocolMsg, Replica, Result, START_EPOCH, Vote};
 
 use crate::{Event, NewMessage, OutMsg, ThreadSendMsg, TimeOutEvent, ev_queue::EventQueue};
@@ -38,6 +39,11 @@ impl OptRandStateMachine {
                 );
             }
         }
+        // Only sharings for this or the next epoch can enter the buffer of the leader thread
+        let e = self.epoch as u64;
+        if pvec.epoch != e && pvec.epoch != e.saturating_add(1) {
+            return Err(format!("Got a sharing from {} for epoch {} in epoch {}", from, pvec.epoch, e).into());
+        }
         self.leader_thread_sender.send(
             ThreadSendMsg::NewContribution(from, pvec),
         ).map_err(|e| format!("Sending error: {}", e))?;
@@ -71,7 +77,7 @@ impl OptRandStateMachine {
         // } else {
         //     self.config.pvss_ctx.generate_shares(&self.sk, &mut self.rng)
         // };
-        let shares = self.config.pvss_ctx.generate_shares(&self.sk, &mut self.rng);
+        let shares = PvssScheme::generate_shares(&self.config.pvss_ctx, self.epoch as u64, &self.sk, &mut self.rng);
         let highest_cert_data = self.highest_certified_data().clone();
         let highest_cert = self.highest_certificate().clone();
         if self.leader_ctx.is_leader(self.config.id) {
@@ -0,0 +1,145 @@
+// Checks the BLS aggregate certificates with the keys from the generated configs
+use crypto::hash::Hash;
+use serde::Serialize;
+use types::{AggregateCertificate, BlsKeyMap, BlsKeypair, BlsPublicKey, BlsSignature, CanonicalHash, Epoch, KeyPop, Replica, SignerBitmap, Type, Vote, VoteBuilder, error::Error};
+
+const NUM_NODES: usize = 4;
+
+fn vote() -> Vote {
+    vote_at(5)
+}
+
+fn vote_at(e: Epoch) -> Vote {
+    VoteBuilder::default()
+        .epoch(e)
+        .prop_hash([7u8; 32])
+        .tp(Type::Responsive)
+        .build()
+        .unwrap()
+}
+
+/// The BLS key pairs of the nodes, and their public keys with the proofs of possession
+fn bls_keys() -> (Vec<BlsKeypair>, Vec<BlsPublicKey>, Vec<KeyPop>) {
+    let confs = config::generate_test_configs(NUM_NODES, 1, 50, 5000).unwrap();
+    for c in &confs {
+        c.validate().unwrap();
+    }
+    let kps = confs.iter().map(|c| c.get_bls_keypair()).collect();
+    (kps, confs[0].bls_public_keys.clone(), confs[0].bls_key_pops.clone())
+}
+
+/// Merges the votes of the signers on the vote
+fn certificate(kps: &[BlsKeypair], signers: &[Replica]) -> AggregateCertificate<Vote> {
+    let mut cert = AggregateCertificate::new_cert(&vote(), signers[0], &kps[signers[0]]);
+    for &i in &signers[1..] {
+        let v = AggregateCertificate::new_cert(&vote(), i, &kps[i]);
+        cert.merge(&v).unwrap();
+    }
+    cert
+}
+
+/// The wire format of an aggregate certificate, to build certificates for signers we do not have the keys of
+#[derive(Serialize)]
+struct RawCertificate {
+    hash: Hash,
+    signers: SignerBitmap,
+    #[serde(serialize_with = "crypto::canonical_serialize")]
+    sig: BlsSignature,
+}
+
+#[test]
+fn test_round_trip() {
+    let (kps, pks, pops) = bls_keys();
+    let key_map = BlsKeyMap::new(&pks, &pops).unwrap();
+    let cert = certificate(&kps, &[0, 2, 3]);
+
+    let bytes = bincode::serialize(&cert).unwrap();
+    let cert2: AggregateCertificate<Vote> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(3, cert2.len());
+    assert_eq!(cert.signers(), cert2.signers());
+    cert2.is_valid(&vote(), &key_map).unwrap();
+
+    // Votes with common signers cannot be merged
+    let mut cert3 = cert2.clone();
+    assert!(matches!(cert3.merge(&certificate(&kps, &[1, 2])), Err(Error::CertificateOverlappingSigners)));
+}
+
+#[test]
+fn test_missing_signer() {
+    let (kps, pks, pops) = bls_keys();
+    // Node 3 has no key in this map
+    let key_map = BlsKeyMap::new(&pks[..3], &pops[..3]).unwrap();
+    certificate(&kps, &[0, 1, 2]).is_valid(&vote(), &key_map).unwrap();
+    assert!(matches!(
+        certificate(&kps, &[0, 3]).is_valid(&vote(), &key_map),
+        Err(Error::CertificateUnknownOrigin(3))
+    ));
+}
+
+#[test]
+fn test_signer_bitmap() {
+    let mut signers = SignerBitmap::default();
+    for i in [1, 9, 12] {
+        signers.insert(i);
+    }
+    assert_eq!(signers.max(), Some(12));
+    assert_eq!(signers.iter().collect::<Vec<_>>(), vec![1, 9, 12]);
+    let bytes = bincode::serialize(&signers).unwrap();
+    assert_eq!(bincode::deserialize::<SignerBitmap>(&bytes).unwrap(), signers);
+
+    // Every set has a single encoding
+    assert!(bincode::deserialize::<SignerBitmap>(&bincode::serialize(&vec![0b10u8, 0]).unwrap()).is_err());
+
+    // A signer beyond the nodes
+    let (kps, pks, pops) = bls_keys();
+    let key_map = BlsKeyMap::new(&pks, &pops).unwrap();
+    let mut signers = SignerBitmap::default();
+    signers.insert(NUM_NODES);
+    let hash = vote().canonical_hash();
+    let raw = RawCertificate { hash, signers, sig: kps[0].sign(&hash) };
+    let forged: AggregateCertificate<Vote> = bincode::deserialize(&bincode::serialize(&raw).unwrap()).unwrap();
+    assert!(matches!(forged.is_valid(&vote(), &key_map), Err(Error::CertificateUnknownOrigin(NUM_NODES))));
+}
+
+#[test]
+fn test_bad_signature() {
+    let (kps, pks, pops) = bls_keys();
+    let key_map = BlsKeyMap::new(&pks, &pops).unwrap();
+
+    // Node 1 signs with the key of node 2
+    let mut cert = certificate(&kps, &[0]);
+    cert.merge(&AggregateCertificate::new_cert(&vote(), 1, &kps[2])).unwrap();
+    assert!(matches!(cert.is_valid(&vote(), &key_map), Err(Error::CertificateInvalidAggregate)));
+
+    // A valid certificate for another message
+    assert!(matches!(certificate(&kps, &[0, 1]).is_valid(&vote_at(6), &key_map), Err(Error::CertificateHashMismatch)));
+}
+
+#[test]
+fn test_rogue_key() {
+    let mut rng = crypto::std_rng();
+    let (kps, mut pks, mut pops) = bls_keys();
+    let hash = vote().canonical_hash();
+
+    // Node 3 picks its key so that the keys of all the nodes add up to a key that it knows
+    let (rogue, _) = BlsKeypair::generate_with_pop(3, &mut rng);
+    pks[3] = rogue.pk - (pks[0] + pks[1] + pks[2]);
+    let mut signers = SignerBitmap::default();
+    for i in 0..NUM_NODES {
+        signers.insert(i);
+    }
+    let raw = RawCertificate { hash, signers, sig: rogue.sign(&hash) };
+    let forged: AggregateCertificate<Vote> = bincode::deserialize(&bincode::serialize(&raw).unwrap()).unwrap();
+    // The signature checks out under the sum of the keys, so it would pass as a signature by every node
+    assert!(crypto::bls_verify::<types::E>(&hash, &rogue.sign(&hash), &crypto::bls_aggregate_keys::<types::E, _>(&pks)));
+
+    // But node 3 cannot prove that it knows the secret key for its rogue key
+    pops[3] = KeyPop::prove_in(&KeyPop::bls_transcript(3), rogue.secret().expose_secret(), &pks[3], &mut rng);
+    assert!(matches!(BlsKeyMap::new(&pks, &pops), Err(Error::CertificateInvalidKeyPop(3))));
+
+    // And against the real keys, the forged certificate is invalid
+    let (_, real_pks, real_pops) = bls_keys();
+    let key_map = BlsKeyMap::new(&real_pks, &real_pops).unwrap();
+    assert!(kps[3].pk != pks[3]);
+    assert!(matches!(forged.is_valid(&vote(), &key_map), Err(Error::CertificateInvalidAggregate)));
+}
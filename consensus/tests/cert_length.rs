@@ -0,0 +1,97 @@
+// Checks the size of certificates on the wire
+use fnv::FnvHashMap as HashMap;
+use types::{CanonicalHash, Certificate, Replica, Signature, Type, Vote, VoteBuilder, resp_threshold};
+
+const NODES: [usize; 7] = [3, 10, 20, 30, 50, 75, 100];
+const ED25519_SIG_SIZE: usize = 64;
+
+fn vote() -> Vote {
+    VoteBuilder::default()
+        .epoch(5)
+        .prop_hash([7u8; 32])
+        .tp(Type::Responsive)
+        .build()
+        .unwrap()
+}
+
+/// A responsive certificate on the vote, and the public keys of the signers
+fn resp_cert(n: usize) -> (Certificate<Vote>, HashMap<Replica, crypto_lib::PublicKey>) {
+    let v = vote();
+    let mut cert = Certificate::default();
+    let mut pks = HashMap::default();
+    for i in 0..n {
+        let sk = crypto::DSSSecretKey::generate_ed25519();
+        pks.insert(i, sk.public());
+        if i < resp_threshold(n) {
+            cert.add_signature(i, Signature::new_signature(&v, &sk).unwrap());
+        }
+    }
+    (cert, pks)
+}
+
+/// The size with the earlier encoding, i.e., the hash and the signature map
+fn map_encoding_size(cert: &Certificate<Vote>) -> usize {
+    let sigs: HashMap<Replica, Vec<u8>> = cert.sigs.iter()
+        .map(|(id, sig)| (*id, sig.get_sig()))
+        .collect();
+    bincode::serialize(&(vote().canonical_hash(), sigs)).unwrap().len()
+}
+
+#[test]
+fn test_cert_length() {
+    for n in NODES {
+        let (cert, pks) = resp_cert(n);
+        let bytes = bincode::serialize(&cert).unwrap();
+        // The bitmap (up to the last signer) and the signatures, with a length prefix each
+        let expected = 8 + (cert.len()+7)/8 + 8 + ED25519_SIG_SIZE * cert.len();
+        assert_eq!(bytes.len(), expected, "n = {}", n);
+        // The hash and the map framing, and a key and a length prefix for every signature
+        assert_eq!(map_encoding_size(&cert), 32 + 8 + (8 + 8 + ED25519_SIG_SIZE) * cert.len());
+        assert!(bytes.len() < map_encoding_size(&cert), "n = {}", n);
+
+        let cert2: Certificate<Vote> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(cert2.len(), cert.len());
+        for (id, sig) in &cert.sigs {
+            assert_eq!(cert2.sigs[id].get_sig(), sig.get_sig());
+        }
+        cert2.is_valid(&vote(), &pks).unwrap();
+        // The encoding does not depend on the order of the map
+        assert_eq!(bincode::serialize(&cert2).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn test_empty_cert() {
+    let cert = Certificate::<Vote>::default();
+    let bytes = bincode::serialize(&cert).unwrap();
+    let cert2: Certificate<Vote> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(cert2.len(), 0);
+}
+
+#[test]
+fn test_malformed_cert() {
+    let (cert, _) = resp_cert(10);
+    let mut bytes = bincode::serialize(&cert).unwrap();
+    // Drop the last byte of the last signature, and fix the length prefix of the signatures
+    bytes.pop();
+    let sigs_len_at = 8 + (cert.len()+7)/8;
+    let sigs_len = (ED25519_SIG_SIZE * cert.len() - 1) as u64;
+    bytes[sigs_len_at..sigs_len_at+8].copy_from_slice(&sigs_len.to_le_bytes());
+    assert!(bincode::deserialize::<Certificate<Vote>>(&bytes).is_err());
+}
+
+#[test]
+fn test_non_canonical_cert() {
+    // The bitmap and the signatures, as on the wire
+    let decode = |signers: Vec<u8>, sigs: Vec<u8>| {
+        bincode::deserialize::<Certificate<Vote>>(&bincode::serialize(&(signers, sigs)).unwrap())
+    };
+    assert_eq!(decode(vec![0b101], vec![1u8; 2*ED25519_SIG_SIZE]).unwrap().len(), 2);
+    // The same signers with a trailing zero byte
+    assert!(decode(vec![0b101, 0], vec![1u8; 2*ED25519_SIG_SIZE]).is_err());
+    // Two signatures of 65 bytes, or one signature of 128 bytes
+    assert!(decode(vec![0b101], vec![1u8; 2*(ED25519_SIG_SIZE+1)]).is_err());
+    assert!(decode(vec![0b1], vec![1u8; 2*ED25519_SIG_SIZE]).is_err());
+    // Signatures without signers
+    assert!(decode(vec![], vec![1u8; ED25519_SIG_SIZE]).is_err());
+}
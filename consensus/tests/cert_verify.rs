@@ -0,0 +1,99 @@
+// Checks the buffered verification of certificates
+use fnv::FnvHashMap as HashMap;
+use types::{CanonicalHash, Certificate, Replica, Signature, Storage, Type, Vote, VoteBuilder, error::Error};
+
+const NUM_NODES: usize = 4;
+
+fn vote() -> Vote {
+    VoteBuilder::default()
+        .epoch(5)
+        .prop_hash([7u8; 32])
+        .tp(Type::Responsive)
+        .build()
+        .unwrap()
+}
+
+/// The secret keys and the public keys of the nodes
+fn keys() -> (Vec<crypto::DSSSecretKey>, HashMap<Replica, crypto_lib::PublicKey>) {
+    let sks: Vec<_> = (0..NUM_NODES)
+        .map(|_| crypto::DSSSecretKey::generate_ed25519())
+        .collect();
+    let pks = sks.iter().enumerate()
+        .map(|(i, sk)| (i, sk.public()))
+        .collect();
+    (sks, pks)
+}
+
+/// A certificate on the vote with a signature from every node in signers, made with the key of the node in keys
+fn cert(sks: &[crypto::DSSSecretKey], signers: &[Replica], keys: &[Replica]) -> Certificate<Vote> {
+    let mut cert = Certificate::default();
+    for (from, key) in signers.iter().zip(keys) {
+        cert.add_signature(*from, Signature::new_signature(&vote(), &sks[*key]).unwrap());
+    }
+    cert
+}
+
+#[test]
+fn test_valid_batch() {
+    let (sks, pks) = keys();
+    let mut storage = Storage::new(NUM_NODES, Default::default());
+    let hash = vote().canonical_hash();
+
+    let c = cert(&sks, &[0, 1, 3], &[0, 1, 3]);
+    c.buffered_is_valid(&vote(), &pks, &mut storage).unwrap();
+    for i in [0, 1, 3] {
+        assert!(storage.is_already_verified(i, &hash));
+    }
+    assert!(!storage.is_already_verified(2, &hash));
+
+    // The buffered signatures are not checked again
+    let c = cert(&sks, &[1, 2], &[1, 2]);
+    c.buffered_is_valid(&vote(), &pks, &mut storage).unwrap();
+    assert!(storage.is_already_verified(2, &hash));
+}
+
+#[test]
+fn test_bad_signature() {
+    let (sks, pks) = keys();
+    let mut storage = Storage::new(NUM_NODES, Default::default());
+    let hash = vote().canonical_hash();
+
+    // Node 2 signs with the key of node 3, so the batch fails and the fallback finds node 2
+    let c = cert(&sks, &[0, 1, 2], &[0, 1, 3]);
+    assert!(matches!(c.buffered_is_valid(&vote(), &pks, &mut storage), Err(Error::CertificateInvalidSig(2))));
+    assert!(matches!(c.is_valid(&vote(), &pks), Err(Error::CertificateInvalidSig(2))));
+    // Nothing from an invalid certificate is buffered
+    for i in 0..NUM_NODES {
+        assert!(!storage.is_already_verified(i, &hash));
+    }
+}
+
+#[test]
+fn test_other_message() {
+    let (sks, pks) = keys();
+    let mut storage = Storage::new(NUM_NODES, Default::default());
+    let other = VoteBuilder::default()
+        .epoch(6)
+        .prop_hash([7u8; 32])
+        .tp(Type::Responsive)
+        .build()
+        .unwrap();
+
+    // A received certificate has no hash of its own, the signatures are checked against the message
+    let bytes = bincode::serialize(&cert(&sks, &[0, 1], &[0, 1])).unwrap();
+    let c: Certificate<Vote> = bincode::deserialize(&bytes).unwrap();
+    c.is_valid(&vote(), &pks).unwrap();
+    assert!(c.is_valid(&other, &pks).is_err());
+    assert!(c.buffered_is_valid(&other, &pks, &mut storage).is_err());
+    assert!(!storage.is_already_verified(0, &other.canonical_hash()));
+}
+
+#[test]
+fn test_unknown_signer() {
+    let (sks, pks) = keys();
+    let mut storage = Storage::new(NUM_NODES, Default::default());
+
+    // There is no node 7, and no room for it in the storage
+    let c = cert(&sks, &[0, 7], &[0, 1]);
+    assert!(matches!(c.buffered_is_valid(&vote(), &pks, &mut storage), Err(Error::CertificateUnknownOrigin(7))));
+}
@@ -0,0 +1,39 @@
+// Checks the key rotations in the blocks
+use types::{Block, BlockBuilder, Epoch, KeyRotation, Storage};
+
+const NUM_NODES: usize = 4;
+const EPOCH: Epoch = 5;
+
+fn block(proposer: usize, rot: KeyRotation) -> Block {
+    let genesis = Block::genesis();
+    BlockBuilder::default()
+        .parent_hash(*genesis.hash())
+        .height(1)
+        .proposer(proposer)
+        .aggregate_pvss(genesis.pvss().clone())
+        .aggregate_proof(genesis.proof().clone())
+        .key_rotation(Some(rot))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_switch_epoch() {
+    let mut rng = crypto::std_rng();
+    let mut confs = config::generate_test_configs(NUM_NODES, 1, 50, 5000).unwrap();
+    let storage = Storage::new(NUM_NODES, Default::default());
+    let pk_map = confs[0].get_public_key_map();
+    let min = confs[0].pvss_ctx.min_switch_epoch(EPOCH as u64);
+
+    let rot = confs[1].pvss_ctx.announce_rotation(min, &mut rng);
+    let b = block(1, rot.clone());
+    b.is_valid(EPOCH, &storage, &confs[0].pvss_ctx, &pk_map).unwrap();
+    // The same block in a later epoch switches too early
+    assert!(b.is_valid(EPOCH + 1, &storage, &confs[0].pvss_ctx, &pk_map).is_err());
+    // Only the proposer can rotate its key
+    assert!(block(2, rot).check_key_rotation(EPOCH, &confs[0].pvss_ctx).is_err());
+
+    // A rotation that switches before everyone commits the block
+    let early = confs[1].pvss_ctx.announce_rotation(min - 1, &mut rng);
+    assert!(block(1, early).is_valid(EPOCH, &storage, &confs[0].pvss_ctx, &pk_map).is_err());
+}
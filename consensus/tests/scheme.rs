@@ -0,0 +1,45 @@
+// Runs the leader thread with a PVSS scheme other than the one of the protocol
+use ark_ed_on_bls12_381::EdwardsProjective;
+use consensus::{ThreadRecvMsg, ThreadSendMsg, spawn_leader_thread};
+use crypto::{PvssScheme, ScrapeContext};
+use fnv::FnvHashMap as HashMap;
+
+type Scrape = ScrapeContext<EdwardsProjective>;
+
+const NUM_NODES: usize = 4;
+const NUM_FAULTS: usize = 1;
+
+#[tokio::test]
+async fn test_leader_thread_with_scrape() {
+    let mut rng = crypto::std_rng();
+    let keypairs: Vec<_> = (0..NUM_NODES)
+        .map(|_| Scrape::generate_keypair(&mut rng))
+        .collect();
+    let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+    let ctx: Vec<_> = (0..NUM_NODES).map(|i| {
+        Scrape::new_from_seed(&mut rng, "chain-1", NUM_NODES, NUM_FAULTS, i, public_keys.clone(), keypairs[i].0)
+    }).collect();
+    let dss: Vec<_> = (0..NUM_NODES).map(|_| crypto::DSSSecretKey::generate_ed25519()).collect();
+    let pk_map: HashMap<_,_> = (0..NUM_NODES).map(|i| (i, dss[i].public())).collect();
+
+    let (sender, mut receiver) = spawn_leader_thread(NUM_FAULTS, ctx[0].clone(), pk_map);
+    // A sharing for another epoch is not combined with the others
+    sender.send(ThreadSendMsg::NewContribution(3, ctx[3].generate_shares(2, &dss[3], &mut rng))).unwrap();
+    for i in 0..=NUM_FAULTS {
+        sender.send(ThreadSendMsg::NewContribution(i, ctx[i].generate_shares(1, &dss[i], &mut rng))).unwrap();
+    }
+    let (agg, proof) = match receiver.recv().await {
+        Some(ThreadRecvMsg::AggregateReady(agg, proof)) => (agg, proof),
+        m => panic!("Expected an aggregate, got {:?}", m),
+    };
+    let mut dealers = proof.clone();
+    dealers.sort_unstable();
+    assert_eq!(dealers, (0..=NUM_FAULTS).collect::<Vec<_>>());
+
+    // The aggregate checks out at the other nodes
+    sender.send(ThreadSendMsg::NewAggregateSharing(0, agg.clone(), proof)).unwrap();
+    match receiver.recv().await {
+        Some(ThreadRecvMsg::VerifiedAggregateSharing(0, verified)) => assert_eq!(verified, agg),
+        m => panic!("Expected a verified aggregate, got {:?}", m),
+    }
+}
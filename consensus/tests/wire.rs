@@ -0,0 +1,113 @@
+// Checks that malformed or incompatible messages from the network are rejected without panicking
+use types::{Block, Codeword, PROTOCOL_VERSION, ProtocolMsg, Type, Vote, VoteBuilder, Wire, encode_versioned, error::Error, from_codewords, generate_codewords};
+use types_upstream::WireReady;
+
+fn sync_vote() -> ProtocolMsg {
+    let vote = VoteBuilder::default()
+        .epoch(3)
+        .prop_hash([1u8; 32])
+        .tp(Type::Sync)
+        .build()
+        .unwrap();
+    let sk = crypto::DSSSecretKey::generate_ed25519();
+    let cert = types::Certificate::new_cert(&vote, 0, &sk).unwrap();
+    ProtocolMsg::SyncVote(vote, cert)
+}
+
+#[test]
+fn test_round_trip() {
+    let bytes = sync_vote().to_wire().unwrap();
+    assert_eq!(bytes[0], PROTOCOL_VERSION);
+    match ProtocolMsg::from_wire(&bytes).unwrap() {
+        ProtocolMsg::SyncVote(v, c) => {
+            assert_eq!(v.epoch(), 3);
+            assert!(c.is_vote());
+        }
+        m => panic!("Decoded the wrong message: {:?}", m),
+    }
+}
+
+#[test]
+fn test_version_mismatch() {
+    let mut bytes = sync_vote().to_wire().unwrap();
+    bytes[0] = PROTOCOL_VERSION.wrapping_add(1);
+    match ProtocolMsg::from_wire(&bytes) {
+        Err(Error::WireVersionMismatch(exp, got)) => {
+            assert_eq!(exp, PROTOCOL_VERSION);
+            assert_eq!(got, bytes[0]);
+        }
+        r => panic!("Accepted a message with the wrong version: {:?}", r),
+    }
+}
+
+#[test]
+fn test_malformed() {
+    assert!(matches!(ProtocolMsg::from_wire(&[]), Err(Error::WireEmptyMessage)));
+
+    let bytes = sync_vote().to_wire().unwrap();
+    // Truncated
+    assert!(ProtocolMsg::from_wire(&bytes[..bytes.len()-1]).is_err());
+    // Trailing bytes
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(ProtocolMsg::from_wire(&longer).is_err());
+    // Unknown variant
+    let mut unknown = vec![PROTOCOL_VERSION];
+    unknown.extend_from_slice(&u32::MAX.to_le_bytes());
+    assert!(ProtocolMsg::from_wire(&unknown).is_err());
+
+    assert!(Block::from_wire(&[PROTOCOL_VERSION, 1, 2, 3]).is_err());
+    // The infallible decoding turns malformed messages into invalid ones
+    assert!(matches!(ProtocolMsg::from_bytes(&unknown), ProtocolMsg::InvalidMessage));
+}
+
+/// A codeword with the bytes, as a peer could send it
+fn codeword(data: &[u8]) -> Option<Codeword<Vote>> {
+    Some(bincode::deserialize(&bincode::serialize(data).unwrap()).unwrap())
+}
+
+#[test]
+fn test_malformed_codewords() {
+    let vote = match sync_vote() {
+        ProtocolMsg::SyncVote(v, _) => v,
+        _ => unreachable!(),
+    };
+    let (n, f) = (4, 1);
+    let codes: Vec<_> = generate_codewords::<Vote>(&encode_versioned(&vote).unwrap(), n, f).unwrap()
+        .into_iter()
+        .map(Some)
+        .collect();
+    let mut received = codes.clone();
+    received[0] = None;
+    assert_eq!(from_codewords::<Vote>(received.clone(), n, f).unwrap().epoch(), vote.epoch());
+
+    // Too few shards
+    received[1] = None;
+    assert!(from_codewords::<Vote>(received, n, f).is_err());
+    // Too many shards, or more faults than nodes
+    let mut more = codes.clone();
+    more.push(codes[0].clone());
+    assert!(from_codewords::<Vote>(more, n, f).is_err());
+    assert!(from_codewords::<Vote>(codes.clone(), n, n+1).is_err());
+    // Shards of different lengths
+    let mut uneven = codes.clone();
+    uneven[3] = codeword(&[0u8; 1]);
+    uneven[2] = None;
+    assert!(from_codewords::<Vote>(uneven, n, f).is_err());
+    // The length prefix is longer than the data
+    let frame = vec![codeword(&[0xffu8; 4]); n];
+    assert!(matches!(from_codewords::<Vote>(frame, n, f), Err(Error::ShardInvalidFrame)));
+    // The data is not versioned
+    let unversioned: Vec<_> = generate_codewords::<Vote>(&bincode::serialize(&vote).unwrap(), n, f).unwrap()
+        .into_iter()
+        .map(Some)
+        .collect();
+    assert!(from_codewords::<Vote>(unversioned, n, f).is_err());
+}
+
+#[test]
+fn test_unexpected_variants() {
+    // Only we create these, so a peer sending them is misbehaving
+    assert!(matches!(ProtocolMsg::InvalidMessage.init(), ProtocolMsg::InvalidMessage));
+    assert!(matches!(sync_vote().init(), ProtocolMsg::SyncVote(..)));
+}
@@ -90,8 +90,248 @@ mod mnt6_753 {
     bench_curve!(MNT6_753, "MNT6-753");
 }
 
+/// Compares the verification costs of SCRAPE (on a curve without pairings) with DBS (on BLS12-381) at the same n
+/// Both run through `PvssScheme`, as in the consensus
+mod scrape {
+    use ark_bls12_381::Bls12_381;
+    use ark_ed_on_bls12_381::EdwardsProjective;
+    use criterion::{criterion_group, Bencher, BenchmarkId, Criterion};
+    use crypto::{DSSSecretKey, DbsContext, Keypair, PvssScheme, ScrapeContext};
+    use fnv::FnvHashMap as HashMap;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const SEED: u64 = 42;
+    static TEST_POINTS: [usize; 7] = [3, 10, 20, 30, 50, 75, 100];
+    const BENCH_COUNT: usize = 10;
+    const EPOCH: u64 = 1;
+
+    type Dbs = DbsContext<Bls12_381>;
+    type Scrape = ScrapeContext<EdwardsProjective>;
+
+    /// The contexts of the first t+1 nodes, and a sharing, an aggregate and t+1 shares for them
+    struct Run<S: PvssScheme> {
+        ctx: Vec<S>,
+        pk_map: HashMap<usize, crypto_lib::PublicKey>,
+        pvec: S::Sharing,
+        agg: S::Aggregate,
+        proof: S::DecompProof,
+        shares: Vec<Option<S::Share>>,
+    }
+
+    fn run<S: PvssScheme>(n: usize, ctx: Vec<S>, rng: &mut StdRng) -> Run<S> {
+        let dss: Vec<_> = ctx.iter().map(|_| DSSSecretKey::generate_ed25519()).collect();
+        let pk_map = dss.iter().enumerate().map(|(i, sk)| (i, sk.public())).collect();
+        let indices: Vec<_> = (0..ctx.len()).collect();
+        let pvec = ctx[0].generate_shares(EPOCH, &dss[0], rng);
+        let pvecs = indices.iter().map(|&i| ctx[i].generate_shares(EPOCH, &dss[i], rng)).collect();
+        let (agg, proof) = ctx[0].aggregate(&indices, pvecs);
+        let mut shares: Vec<_> = (0..n).map(|_| None).collect();
+        for j in indices {
+            let enc = ctx[j].encryption(&agg, j).expect("Every node has a share");
+            let dec = ctx[j].decrypt_share(EPOCH, enc, &dss[j], rng);
+            shares[j] = Some(ctx[j].share_of(&dec));
+        }
+        Run { ctx, pk_map, pvec, agg, proof, shares }
+    }
+
+    /// A node other than the dealer checks the sharing of node 0
+    fn verify_sharing_with<S: PvssScheme>(b: &mut Bencher, r: &Run<S>) {
+        b.iter(|| r.ctx[1].verify_sharing(0, &r.pvec, &r.pk_map[&0]));
+    }
+
+    fn verify_aggregate_with<S: PvssScheme>(b: &mut Bencher, r: &Run<S>) {
+        b.iter(|| r.ctx[0].pverify(&r.agg).or_else(|| r.ctx[0].decomp_verify(&r.agg, &r.proof, &r.pk_map)));
+    }
+
+    fn reconstruct_with<S: PvssScheme>(b: &mut Bencher, r: &Run<S>) {
+        b.iter(|| r.ctx[0].reconstruct(&r.shares));
+    }
+
+    fn dbs(n: usize, rng: &mut StdRng) -> Run<Dbs> {
+        let t = (n - 1) / 2;
+        let keypairs: Vec<_> = (0..n).map(|_| Keypair::<Bls12_381>::generate_keypair(rng)).collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let ctx = (0..t+1).map(|i| {
+            DbsContext::new_from_seed(rng, "bench", n, t, i, public_keys.clone(), keypairs[i].0)
+        }).collect();
+        run(n, ctx, rng)
+    }
+
+    fn scrape(n: usize, rng: &mut StdRng) -> Run<Scrape> {
+        let t = (n - 1) / 2;
+        let keypairs: Vec<_> = (0..n).map(|_| Scrape::generate_keypair(rng)).collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let ctx = (0..t+1).map(|i| {
+            ScrapeContext::new_from_seed(rng, "bench", n, t, i, public_keys.clone(), keypairs[i].0)
+        }).collect();
+        run(n, ctx, rng)
+    }
+
+    /// Runs the benchmark on both schemes, at every number of nodes
+    fn compare(c: &mut Criterion, name: &str, dbs_f: fn(&mut Bencher, &Run<Dbs>), scrape_f: fn(&mut Bencher, &Run<Scrape>)) {
+        let mut group = c.benchmark_group(name);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        for &n in TEST_POINTS.iter() {
+            let r = dbs(n, &mut rng);
+            group.bench_with_input(BenchmarkId::new("DBS", n), &r, dbs_f);
+            let r = scrape(n, &mut rng);
+            group.bench_with_input(BenchmarkId::new("SCRAPE", n), &r, scrape_f);
+        }
+        group.finish();
+    }
+
+    pub fn verify_sharing(c: &mut Criterion) {
+        compare(c, "scheme_verify_sharing", verify_sharing_with, verify_sharing_with);
+    }
+
+    /// The checks of an aggregate by the nodes other than the leader
+    pub fn verify_aggregate(c: &mut Criterion) {
+        compare(c, "scheme_pverify_decomp_verify", verify_aggregate_with, verify_aggregate_with);
+    }
+
+    pub fn reconstruct(c: &mut Criterion) {
+        compare(c, "scheme_reconstruct", reconstruct_with, reconstruct_with);
+    }
+
+    criterion_group!(
+        name = benches;
+        config = Criterion::default().sample_size(BENCH_COUNT);
+        targets = verify_sharing,
+        verify_aggregate,
+        reconstruct,
+    );
+}
+
+/// Compares the number of threads for the per-node loops (see the `parallel` feature)
+/// Run with `cargo bench --features parallel --bench bench_pvss threads`
+#[cfg(feature = "parallel")]
+mod threads {
+    use ark_bls12_381::Bls12_381;
+    use criterion::{criterion_group, BenchmarkGroup, BenchmarkId, Criterion};
+    use crypto::{DSSSecretKey, DbsContext, Keypair};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    type E = Bls12_381;
+    const SEED: u64 = 42;
+    const NUM_NODES: usize = 100;
+    static THREADS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+    const BENCH_COUNT: usize = 10;
+
+    fn setup() -> (Vec<DbsContext<E>>, Vec<DSSSecretKey>) {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let n = NUM_NODES;
+        let t = (n - 1) / 2;
+        let keypairs: Vec<_> = (0..n).map(|_| Keypair::<E>::generate_keypair(&mut rng)).collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let ctx = (0..t+1).map(|i| {
+            DbsContext::<E>::new_from_seed(&mut rng, "bench", n, t, i, public_keys.clone(), keypairs[i].0)
+        }).collect();
+        let dss = (0..t+1).map(|_| DSSSecretKey::generate_ed25519()).collect();
+        (ctx, dss)
+    }
+
+    /// The thread counts up to the number of cores
+    fn thread_counts() -> Vec<usize> {
+        THREADS.iter()
+            .cloned()
+            .filter(|&k| k <= rayon::current_num_threads())
+            .collect()
+    }
+
+    /// Runs f with a pool of every size in THREADS
+    fn bench_threads<F>(group: &mut BenchmarkGroup<criterion::measurement::WallTime>, f: F)
+    where F: Fn() + Sync,
+    {
+        for k in thread_counts() {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(k)
+                .build()
+                .expect("Failed to build the thread pool");
+            group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, &_k| {
+                b.iter(|| pool.install(|| f()));
+            });
+        }
+    }
+
+    pub fn threads_generation(c: &mut Criterion) {
+        let (ctx, dss) = setup();
+        let mut group = c.benchmark_group(format!("threads_pvss_generation_{}", NUM_NODES));
+        BenchmarkGroup::sampling_mode(&mut group, criterion::SamplingMode::Flat);
+        bench_threads(&mut group, || {
+            let mut rng = StdRng::seed_from_u64(SEED);
+            ctx[0].generate_shares(1, &dss[0], &mut rng);
+        });
+        group.finish();
+    }
+
+    pub fn threads_verification(c: &mut Criterion) {
+        let (ctx, dss) = setup();
+        let pvec = ctx[0].generate_shares(1, &dss[0], &mut StdRng::seed_from_u64(SEED));
+        let pk = dss[0].public();
+        let mut group = c.benchmark_group(format!("threads_pvss_verification_{}", NUM_NODES));
+        BenchmarkGroup::sampling_mode(&mut group, criterion::SamplingMode::Flat);
+        bench_threads(&mut group, || {
+            let _ = ctx[1].verify_sharing(0, &pvec, &pk);
+        });
+        group.finish();
+    }
+
+    pub fn threads_aggregation(c: &mut Criterion) {
+        let (ctx, dss) = setup();
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let indices: Vec<_> = (0..ctx.len()).collect();
+        let pvecs: Vec<_> = indices.iter().map(|&i| ctx[i].generate_shares(1, &dss[i], &mut rng)).collect();
+        let mut group = c.benchmark_group(format!("threads_pvss_aggregation_{}", NUM_NODES));
+        BenchmarkGroup::sampling_mode(&mut group, criterion::SamplingMode::Flat);
+        bench_threads(&mut group, || {
+            let _ = ctx[0].aggregate(&indices, pvecs.clone());
+        });
+        group.finish();
+    }
+
+    pub fn threads_pverify(c: &mut Criterion) {
+        let (ctx, dss) = setup();
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let indices: Vec<_> = (0..ctx.len()).collect();
+        let pvecs: Vec<_> = indices.iter().map(|&i| ctx[i].generate_shares(1, &dss[i], &mut rng)).collect();
+        let (agg, _) = ctx[0].aggregate(&indices, pvecs);
+        let mut group = c.benchmark_group(format!("threads_pvss_pverify_{}", NUM_NODES));
+        BenchmarkGroup::sampling_mode(&mut group, criterion::SamplingMode::Flat);
+        bench_threads(&mut group, || {
+            let _ = ctx[1].pverify(&agg);
+        });
+        group.finish();
+    }
+
+    criterion_group!(
+        name = benches;
+        config = Criterion::default().sample_size(BENCH_COUNT);
+        targets = threads_generation,
+        threads_verification,
+        threads_aggregation,
+        threads_pverify,
+    );
+}
+
+#[cfg(feature = "parallel")]
+criterion_main!(
+    threads::benches,
+    bls12_381::benches, 
+    scrape::benches,
+    bn254::benches,
+    bls12_377::benches,
+    bw6_761::benches,
+    cp6_782::benches,
+    mnt4_298::benches,
+    mnt6_298::benches,
+    mnt4_753::benches,
+    mnt6_753::benches,
+);
+
+#[cfg(not(feature = "parallel"))]
 criterion_main!(
     bls12_381::benches, 
+    scrape::benches,
     bn254::benches,
     bls12_377::benches,
     bw6_761::benches,
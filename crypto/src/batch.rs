@@ -0,0 +1,71 @@
+use std::convert::TryFrom;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use ed25519_dalek::{PublicKey as EdPublicKey, Signature as EdSignature};
+
+/// Verifies a batch of DSS signatures
+/// msgs[i] is signed by pks[i] with sigs[i]
+///
+/// OPTIMIZATION: If all the keys are ed25519 keys, we use a random linear combination to verify all the signatures at once
+/// Otherwise, we fall back to checking every signature individually
+/// We also fall back if a key or the R of a signature has a small order component (see `is_batchable`),
+/// since the batch equation and the individual checks can disagree on those, and the result must not depend on which is used
+/// Returns false if any of the signatures is invalid
+pub fn batch_verify_sigs(
+    msgs: &[&[u8]],
+    sigs: &[&[u8]],
+    pks: &[&crypto_lib::PublicKey],
+) -> bool
+{
+    if msgs.len() != sigs.len() || msgs.len() != pks.len() {
+        return false;
+    }
+    if msgs.is_empty() {
+        return true;
+    }
+    let mut ed_pks = Vec::with_capacity(pks.len());
+    let mut ed_sigs = Vec::with_capacity(sigs.len());
+    for i in 0..pks.len() {
+        let pk_bytes = match pks[i] {
+            crypto_lib::PublicKey::Ed25519(pk) => pk.encode(),
+            // Not an ed25519 key, so we cannot batch
+            _ => return individual_verify_sigs(msgs, sigs, pks),
+        };
+        if !is_batchable(&pk_bytes) || !sigs[i].get(..32).map_or(false, is_batchable) {
+            return individual_verify_sigs(msgs, sigs, pks);
+        }
+        let pk = EdPublicKey::from_bytes(&pk_bytes);
+        let sig = EdSignature::try_from(sigs[i]);
+        match (pk, sig) {
+            (Ok(pk), Ok(sig)) => {
+                ed_pks.push(pk);
+                ed_sigs.push(sig);
+            },
+            // A malformed key or signature can never verify
+            _ => return false,
+        }
+    }
+    ed25519_dalek::verify_batch(msgs, &ed_sigs, &ed_pks).is_ok()
+}
+
+/// Checks that the encoded point is valid and in the prime order subgroup, and is not the identity
+/// For such keys and R values, the batch equation holds exactly when every signature verifies on its own
+fn is_batchable(bytes: &[u8]) -> bool {
+    let point = match <[u8; 32]>::try_from(bytes) {
+        Ok(b) => CompressedEdwardsY(b).decompress(),
+        Err(_) => None,
+    };
+    match point {
+        Some(p) => !p.is_small_order() && p.is_torsion_free(),
+        None => false,
+    }
+}
+
+/// Verifies every signature one by one
+fn individual_verify_sigs(
+    msgs: &[&[u8]],
+    sigs: &[&[u8]],
+    pks: &[&crypto_lib::PublicKey],
+) -> bool
+{
+    (0..msgs.len()).all(|i| pks[i].verify(msgs[i], sigs[i]))
+}
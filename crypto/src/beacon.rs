@@ -0,0 +1,118 @@
+use ark_ec::PairingEngine;
+use ark_serialize::CanonicalSerialize;
+use rand::{Error, RngCore};
+use sha2::{Digest, Sha256};
+use crate::hash::{Hash, HASH_SIZE};
+use crate::{Beacon, Transcript};
+
+/// The domain for deriving bytes from a beacon
+/// Bump the version whenever the derivation changes
+pub const BEACON_OUTPUT_DOMAIN: &[u8] = b"beacon-output-v1";
+
+/// Turning a beacon into bytes
+///
+/// The seed is the hash of the transcript with domain BEACON_OUTPUT_DOMAIN and the message ("gt", canonical bytes of the beacon)
+/// The output stream is block_0 || block_1 || ..., where block_i = SHA256(seed || le64(i))
+///
+/// Everything is computed from the GT element, so anyone who has the beacon can derive the same bytes
+/// Check the beacon with `DbsContext::check_beacon` before using the bytes
+impl<E> Beacon<E> 
+where E: PairingEngine,
+{
+    /// Returns the canonical (compressed) serialization of the GT element
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.beacon.serialized_size());
+        self.beacon.serialize(&mut bytes)
+            .expect("Writing the beacon to a vector cannot fail");
+        bytes
+    }
+
+    /// Returns the seed for the output stream
+    pub fn seed(&self) -> Hash {
+        let mut t = Transcript::new(BEACON_OUTPUT_DOMAIN);
+        t.append_message(b"gt", &self.canonical_bytes());
+        t.hash()
+    }
+
+    /// Returns the first 32 bytes of the output stream
+    pub fn to_bytes32(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        self.stream().fill_bytes(&mut out);
+        out
+    }
+
+    /// Returns the first 64 bytes of the output stream
+    pub fn to_bytes64(&self) -> [u8; 64] {
+        let mut out = [0u8; 64];
+        self.stream().fill_bytes(&mut out);
+        out
+    }
+
+    /// Returns the output stream of this beacon
+    pub fn stream(&self) -> BeaconStream {
+        BeaconStream::new(self.seed())
+    }
+}
+
+/// An arbitrary length stream of bytes derived from a beacon
+#[derive(Debug, Clone)]
+pub struct BeaconStream {
+    seed: Hash,
+    counter: u64,
+    block: Hash,
+    /// The number of bytes of block already used
+    used: usize,
+}
+
+impl BeaconStream {
+    /// Returns the stream block_0 || block_1 || ..., where block_i = SHA256(seed || le64(i))
+    pub(crate) fn new(seed: Hash) -> Self {
+        Self {
+            seed,
+            counter: 0,
+            block: [0; HASH_SIZE],
+            used: HASH_SIZE,
+        }
+    }
+
+    fn next_block(&mut self) {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.seed);
+        hasher.update(&self.counter.to_le_bytes());
+        self.block = hasher.finalize().into();
+        self.counter += 1;
+        self.used = 0;
+    }
+}
+
+impl RngCore for BeaconStream {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        self.fill_bytes(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut filled = 0;
+        while filled < dest.len() {
+            if self.used == HASH_SIZE {
+                self.next_block();
+            }
+            let len = std::cmp::min(HASH_SIZE - self.used, dest.len() - filled);
+            dest[filled..filled+len].copy_from_slice(&self.block[self.used..self.used+len]);
+            self.used += len;
+            filled += len;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
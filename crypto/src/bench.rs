@@ -37,7 +37,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.push(dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -51,7 +51,7 @@ macro_rules! bench_curve {
                 group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &_n| {
                     b.iter(|| {
                         // Insert code that you want tested here
-                        dbs_ctx.generate_shares(&dss_kpair[0],&mut rng)
+                        dbs_ctx.generate_shares(1, &dss_kpair[0],&mut rng)
                     });
                 });
             }
@@ -77,7 +77,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.push(dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -87,14 +87,14 @@ macro_rules! bench_curve {
                 let dbs_ctx = DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys, secret_keys[0]);
                 let idx = 0;
                 let pvec = 
-                    dbs_ctx.generate_shares(&dss_kpair[idx], &mut rng);
+                    dbs_ctx.generate_shares(1, &dss_kpair[idx], &mut rng);
 
                 // We are ready to start testing now
                 group.throughput(Throughput::Bytes(n as u64));
                 group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &_n| {
                     b.iter(|| {
                         // Insert code that you want tested here
-                        let _ = dbs_ctx.verify_sharing(&pvec, &dss_pk[0]);
+                        let _ = dbs_ctx.verify_sharing(0, &pvec, &dss_pk[0]);
                     });
                 });
             }
@@ -118,7 +118,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.push(dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -126,12 +126,12 @@ macro_rules! bench_curve {
                 let h1 = G1::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng));
 
                 let dbs_ctx:Vec<_> = (0..t+1).map(|i| {
-                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys.clone(), secret_keys[i].clone())
+                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, i, public_keys.clone(), secret_keys[i].clone())
                 }).collect();
                 let mut pvecs = Vec::with_capacity(t+1); 
                 for i in 0..t+1 {
                     let pvec = 
-                    dbs_ctx[i].generate_shares(&dss_kpair[i], &mut rng);
+                    dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng);
                     pvecs.push(pvec);
                 }
 
@@ -166,7 +166,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.push(dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -174,12 +174,12 @@ macro_rules! bench_curve {
                 let h1 = G1::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng));
 
                 let dbs_ctx:Vec<_> = (0..t+1).map(|i| {
-                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys.clone(), secret_keys[i].clone())
+                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, i, public_keys.clone(), secret_keys[i].clone())
                 }).collect();
                 let mut pvecs = Vec::with_capacity(t+1); 
                 for i in 0..t+1 {
                     let pvec = 
-                    dbs_ctx[i].generate_shares(&dss_kpair[i], &mut rng);
+                    dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng);
                     pvecs.push(pvec);
                 }
 
@@ -215,7 +215,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.insert(i, dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -223,12 +223,12 @@ macro_rules! bench_curve {
                 let h1 = G1::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng));
 
                 let dbs_ctx:Vec<_> = (0..t+1).map(|i| {
-                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys.clone(), secret_keys[i].clone())
+                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, i, public_keys.clone(), secret_keys[i].clone())
                 }).collect();
                 let mut pvecs = Vec::with_capacity(t+1); 
                 for i in 0..t+1 {
                     let pvec = 
-                    dbs_ctx[i].generate_shares(&dss_kpair[i], &mut rng);
+                    dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng);
                     pvecs.push(pvec);
                 }
 
@@ -264,7 +264,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.insert(i as u16, dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -272,12 +272,12 @@ macro_rules! bench_curve {
                 let h1 = G1::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng));
 
                 let dbs_ctx:Vec<_> = (0..t+1).map(|i| {
-                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys.clone(), secret_keys[i].clone())
+                    DbsContext::<E>::new(&mut rng, h2, h1, n, t, i, public_keys.clone(), secret_keys[i].clone())
                 }).collect();
                 let mut pvecs = Vec::with_capacity(t+1); 
                 for i in 0..t+1 {
                     let pvec = 
-                    dbs_ctx[i].generate_shares(&dss_kpair[i], &mut rng);
+                    dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng);
                     pvecs.push(pvec);
                 }
 
@@ -289,7 +289,7 @@ macro_rules! bench_curve {
                 group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &_t| {
                     b.iter(|| {
                         // Insert code that you want tested here
-                        let _ = dbs_ctx[0].decrypt_share(&agg_pvss.encs[0], &dss_kpair[0], &mut rng);
+                        let _ = dbs_ctx[0].decrypt_share(1, &agg_pvss.encs[0], &dss_kpair[0], &mut rng);
                     });
                 });
             }
@@ -313,7 +313,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.insert(i as u16, dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -326,7 +326,7 @@ macro_rules! bench_curve {
                 let mut pvecs = Vec::with_capacity(t+1); 
                 for i in 0..t+1 {
                     let pvec = 
-                    dbs_ctx[i].generate_shares(&dss_kpair[i], &mut rng);
+                    dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng);
                     pvecs.push(pvec);
                 }
 
@@ -334,7 +334,7 @@ macro_rules! bench_curve {
                 let (agg_pvss, _agg_pi) = dbs_ctx[0].aggregate(&indices, pvecs);
                 let mut decs = Vec::with_capacity(n);
                 for i in 0..n {
-                    let dec = dbs_ctx[i].decrypt_share(&agg_pvss.encs[0], &dss_kpair[0], &mut rng);
+                    let dec = dbs_ctx[i].decrypt_share(1, &agg_pvss.encs[i], &dss_kpair[i], &mut rng);
                     decs.push(dec);
                 }
 
@@ -344,7 +344,7 @@ macro_rules! bench_curve {
                     b.iter(|| {
                         // Insert code that you want tested here
                         for i in 0..t+1 {
-                            dbs_ctx[0].verify_share(i, &agg_pvss.encs[i], &decs[i], &dss_pk[&(i as u16)]);
+                            dbs_ctx[0].verify_share(i, 1, &agg_pvss.encs[i], &decs[i], &dss_pk[&(i as u16)]);
                         }
                     });
                 });
@@ -369,7 +369,7 @@ macro_rules! bench_curve {
                     let kpair = Keypair::<E>::generate_keypair(&mut rng);
                     secret_keys.push(kpair.0);
                     public_keys.push(kpair.1);
-                    let dsskpair = crypto_lib::Keypair::generate_ed25519();
+                    let dsskpair = DSSSecretKey::generate_ed25519();
                     dss_pk.insert(i as u16, dsskpair.public());
                     dss_kpair.push(dsskpair);
                 }
@@ -382,7 +382,7 @@ macro_rules! bench_curve {
                 let mut pvecs = Vec::with_capacity(t+1); 
                 for i in 0..t+1 {
                     let pvec = 
-                    dbs_ctx[i].generate_shares(&dss_kpair[i], &mut rng);
+                    dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng);
                     pvecs.push(pvec);
                 }
 
@@ -391,7 +391,7 @@ macro_rules! bench_curve {
                 let mut decs = Vec::with_capacity(n);
                 let mut dec_pi = Vec::with_capacity(n);
                 for i in 0..n {
-                    let dec = dbs_ctx[i].decrypt_share(&agg_pvss.encs[0], &dss_kpair[0], &mut rng);
+                    let dec = dbs_ctx[i].decrypt_share(1, &agg_pvss.encs[i], &dss_kpair[i], &mut rng);
                     decs.push(Some(dec.dec));
                     dec_pi.push(dec.proof);
                 }
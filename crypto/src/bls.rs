@@ -0,0 +1,113 @@
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, Zero};
+use rand::Rng;
+
+use crate::{DbsError, KeyPop, Keypair, PublicKey, Scalar, SecretValue, Transcript, hash_to_curve};
+
+/// The tag for hashing messages to G1 for BLS signatures
+pub const BLS_SIG_TAG: &[u8] = b"optrand-bls-sig";
+
+/// A BLS signature H(m)^sk
+/// The signatures are in G1 and the public keys g2^sk in G2, like the PVSS keys, so signatures stay small
+/// The product of the signatures of several nodes on the same message is a signature under the sum of their public keys
+pub type BlsSignature<E> = <E as PairingEngine>::G1Projective;
+
+/// A key pair for BLS multi-signatures
+/// Use a separate key pair from the PVSS keys, so that signatures never leak anything about the PVSS secret keys
+pub struct BlsKeypair<E>
+where E: PairingEngine,
+{
+    sk: SecretValue<Scalar<E>>,
+    pub pk: PublicKey<E>,
+}
+
+impl<E> BlsKeypair<E>
+where E: PairingEngine,
+{
+    /// Generates a key pair for the node id along with a proof that the node knows the secret key
+    /// Aggregating public keys is only safe if every key comes with a proof of possession (see `KeyPop::verify_bls`)
+    pub fn generate_with_pop<R>(id: usize, rng: &mut R) -> (Self, KeyPop<E>)
+    where R: Rng + ?Sized,
+    {
+        let kpair = Keypair::<E>::generate_keypair(rng);
+        let pop = KeyPop::prove_in(&KeyPop::<E>::bls_transcript(id), &kpair.0, &kpair.1, rng);
+        (Self::from_secret(kpair.0), pop)
+    }
+
+    pub fn from_secret(sk: Scalar<E>) -> Self {
+        Self {
+            pk: PublicKey::<E>::prime_subgroup_generator().mul(sk.into_repr()),
+            sk: SecretValue::new(sk),
+        }
+    }
+
+    pub fn secret(&self) -> &SecretValue<Scalar<E>> {
+        &self.sk
+    }
+
+    /// Signs the message
+    pub fn sign(&self, msg: &[u8]) -> BlsSignature<E> {
+        bls_hash::<E>(msg).mul(self.sk.expose_secret().into_repr())
+    }
+}
+
+impl<E> KeyPop<E>
+where E: PairingEngine,
+{
+    /// The context for the proof of possession of the BLS key of node id
+    /// It differs from the PVSS context, so that a proof for one key cannot be replayed for the other
+    pub fn bls_transcript(id: usize) -> Transcript {
+        let mut t = Transcript::new(b"bls-key-pop");
+        t.append_u64(b"id", id as u64);
+        t
+    }
+
+    /// Checks that the node id knows the secret key for the BLS public key pk
+    pub fn verify_bls(&self, id: usize, pk: &PublicKey<E>) -> Option<DbsError> {
+        if !self.verify_in(&Self::bls_transcript(id), pk) {
+            return Some(DbsError::InvalidKeyPop(id));
+        }
+        None
+    }
+}
+
+/// Hashes the message to G1 (see `hash_to_curve`)
+pub fn bls_hash<E>(msg: &[u8]) -> E::G1Projective
+where E: PairingEngine,
+{
+    hash_to_curve::<E::G1Affine>(BLS_SIG_TAG, msg)
+}
+
+/// Checks that sig is a signature on msg under pk, i.e., e(sig, g2) = e(H(msg), pk)
+/// For an aggregate signature, pk is the sum of the public keys of the signers
+pub fn bls_verify<E>(msg: &[u8], sig: &BlsSignature<E>, pk: &PublicKey<E>) -> bool
+where E: PairingEngine,
+{
+    if pk.is_zero() {
+        return false;
+    }
+    let g2 = PublicKey::<E>::prime_subgroup_generator();
+    let pairs: [(E::G1Prepared, E::G2Prepared); 2] = [
+        (sig.into_affine().into(), (-g2).into_affine().into()),
+        (bls_hash::<E>(msg).into_affine().into(), pk.into_affine().into()),
+    ];
+    <E as PairingEngine>::final_exponentiation(
+        &<E as PairingEngine>::miller_loop(pairs.iter())
+    ).map_or(false, |res| res.is_one())
+}
+
+/// Combines the signatures of several nodes on the same message
+pub fn bls_aggregate<'a, E, I>(sigs: I) -> BlsSignature<E>
+where E: PairingEngine,
+    I: IntoIterator<Item = &'a BlsSignature<E>>,
+{
+    sigs.into_iter().fold(BlsSignature::<E>::zero(), |acc, s| acc + s)
+}
+
+/// Combines the public keys of several nodes, to check their aggregate signature with `bls_verify`
+pub fn bls_aggregate_keys<'a, E, I>(pks: I) -> PublicKey<E>
+where E: PairingEngine,
+    I: IntoIterator<Item = &'a PublicKey<E>>,
+{
+    pks.into_iter().fold(PublicKey::<E>::zero(), |acc, pk| acc + pk)
+}
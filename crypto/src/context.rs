@@ -1,11 +1,12 @@
 use ark_ec::msm::{FixedBaseMSM, VariableBaseMSM};
+use std::sync::Arc;
 
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::ark_serde::{canonical_deserialize,canonical_serialize};
-use crate::precomputes::Precomputation;
-use crate::{Scalar, PublicKey};
+use crate::precomputes::{PrecomputeConfig, Precomputation};
+use crate::{DbsError, KeyPop, PendingKey, Scalar, PublicKey, ProofMode, SecretValue, serialize_canonical_secret, deserialize_canonical_secret};
 use ark_ec::{AffineCurve, PairingEngine};
 use ark_ff::PrimeField;
 use ark_std::One;
@@ -25,6 +26,11 @@ where E: PairingEngine,
     #[serde(deserialize_with = "canonical_deserialize")]
     pub h1p: E::G1Projective,
 
+    /// The public seed that h1 and h2 were derived from, if any
+    /// Use `check_generators` to check that the generators match the seed
+    #[serde(default)]
+    pub(crate) generator_seed: Option<String>,
+
     /// n: The number of nodes in the system
     /// This value will be used for some useful precomputations
     pub(crate) n: usize,
@@ -38,10 +44,39 @@ where E: PairingEngine,
     #[serde(serialize_with = "canonical_serialize")]
     #[serde(deserialize_with = "canonical_deserialize")]
     pub(crate) public_keys: Vec<PublicKey<E>>,
+    /// Proofs of possession for the PVSS public keys
+    /// key_pops[i] proves that node i knows the secret key for public_keys[i]
+    #[serde(default)]
+    #[serde(bound(serialize = "KeyPop<E>: Serialize"))]
+    #[serde(bound(deserialize = "KeyPop<E>: Deserialize<'de>"))]
+    pub(crate) key_pops: Vec<KeyPop<E>>,
     /// My private secret key
-    #[serde(serialize_with = "canonical_serialize")]
-    #[serde(deserialize_with = "canonical_deserialize")]
-    pub(crate) my_key: Scalar<E>,
+    #[serde(serialize_with = "serialize_canonical_secret")]
+    #[serde(deserialize_with = "deserialize_canonical_secret")]
+    pub(crate) my_key: SecretValue<Scalar<E>>,
+
+    /// The number of key rotations applied to this context
+    /// The sharings record the version of the keys they are encrypted under
+    #[serde(default)]
+    pub(crate) key_version: u64,
+    /// The new secret key that I announced with `announce_rotation`, along with its switch epoch
+    /// It is exported with the context (see `export_secret`), and the node writes its context back to its config file when it announces a rotation, so restarting before the switch keeps the key
+    #[serde(default)]
+    #[serde(bound(serialize = "PendingKey<E>: Serialize"))]
+    #[serde(bound(deserialize = "PendingKey<E>: Deserialize<'de>"))]
+    pub(crate) next_key: Option<PendingKey<E>>,
+    /// The contexts for the previous key versions, along with the epochs at which they were replaced
+    /// We keep them during the transition, so that the sharings encrypted under them can still be verified and decrypted (see `retire_keys`)
+    /// They are exported with the context too, so restarting during the transition keeps the old keys
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_retired")]
+    #[serde(deserialize_with = "deserialize_retired")]
+    pub(crate) retired: Vec<(u64, Arc<DbsContext<E>>)>,
+
+    /// How the DLEQ proofs created and checked with this context are pinned to their creators
+    /// This is set from the node config, so it is not serialized with the context
+    #[serde(skip)]
+    pub(crate) proof_mode: ProofMode,
 
     // Pre-computation optimizations
     // OPTIMIZATIONS: Pre-computed lagarange scalar inverses
@@ -76,36 +111,137 @@ where E:PairingEngine,
         Self{
             h2p: h2,
             h1p: h1,
+            generator_seed: None,
             n,
             t,
             public_keys,
+            key_pops: Vec::new(),
             origin:id,
-            my_key,
+            my_key: SecretValue::new(my_key),
+            key_version: 0,
+            next_key: None,
+            retired: Vec::new(),
+            proof_mode: ProofMode::default(),
             optimizations,
         }
     }
 
+    /// Attaches the proofs of possession for the public keys, and checks them
+    /// pops[i] is the proof for the public key of node i
+    pub fn with_key_pops(mut self, pops: Vec<KeyPop<E>>) -> Result<Self, DbsError> {
+        self.key_pops = pops;
+        match self.check_key_pops() {
+            None => Ok(self),
+            Some(e) => Err(e),
+        }
+    }
+
+    /// Checks that every node proved that it knows the secret key for its public key
+    /// To be called when loading a context from a file
+    pub fn check_key_pops(&self) -> Option<DbsError> {
+        if self.key_pops.len() != self.public_keys.len() {
+            return Some(DbsError::MissingKeyPops);
+        }
+        for i in 0..self.public_keys.len() {
+            if let Some(e) = self.key_pops[i].verify(i, &self.public_keys[i]) {
+                return Some(e);
+            }
+        }
+        None
+    }
+
+    /// Sets the format of the DLEQ proofs
+    /// All the nodes must use the same format
+    pub fn set_proof_mode(&mut self, mode: ProofMode) {
+        self.proof_mode = mode;
+        for (_, ctx) in self.retired.iter_mut() {
+            Arc::make_mut(ctx).set_proof_mode(mode);
+        }
+    }
+
+    /// Returns the format of the DLEQ proofs
+    pub fn proof_mode(&self) -> ProofMode {
+        self.proof_mode
+    }
+
+    /// Creates a context whose generators h1 and h2 are derived from the public seed (e.g., a chain id)
+    /// Nobody knows the discrete logs of these generators, and anyone can check them with `check_generators`
+    pub fn new_from_seed<R>(r: &mut R, 
+        seed: &str,
+        n:usize, 
+        t: usize, 
+        id: usize, 
+        public_keys: Vec<PublicKey<E>>, 
+        my_key: Scalar<E>
+    ) -> Self 
+    where R: Rng+?Sized,
+    {
+        let h1 = crate::derive_h1_generator::<E>(seed.as_bytes());
+        let h2 = crate::derive_h2_generator::<E>(seed.as_bytes());
+        let mut ctx = Self::new(r, h2, h1, n, t, id, public_keys, my_key);
+        ctx.generator_seed = Some(seed.to_string());
+        ctx
+    }
+
+    /// Returns the public seed that the generators were derived from, if any
+    pub fn generator_seed(&self) -> Option<&str> {
+        self.generator_seed.as_deref()
+    }
+
     /// To be called after reading from a file since serde will not serialize those elements properly
     /// Sets up the lagrange inverses, my_key_inverse and g,h, and a random codeword
+    /// The fixed base tables are built on first use
     pub fn init<R>(&mut self, rng: &mut R) 
     where R:Rng + ?Sized,
     {
-       self.optimizations = Precomputation::new(
-           self.n, 
-           self.t, 
-           self.h1p, 
-           self.h2p, 
-           self.my_key, 
-           self.public_keys.clone(),
-           rng
+        self.init_with(&PrecomputeConfig::default(), rng)
+    }
+
+    /// Same as init, but with a memory budget for the tables and a cache file to load them from
+    /// If the cache file is missing or stale, we build all the tables now and write them to the cache file
+    /// The contexts for the retired keys are set up too, without the cache file (which is for the current keys)
+    pub fn init_with<R>(&mut self, config: &PrecomputeConfig, rng: &mut R) 
+    where R:Rng + ?Sized,
+    {
+        let retired_config = PrecomputeConfig {
+            cache_path: None,
+            memory_budget: config.memory_budget,
+        };
+        for (_, ctx) in self.retired.iter_mut() {
+            Arc::make_mut(ctx).init_with(&retired_config, rng);
+        }
+        self.optimizations = Precomputation::new_with_budget(
+            self.n, 
+            self.t, 
+            self.h1p, 
+            self.h2p, 
+            *self.my_key.expose_secret(), 
+            self.public_keys.clone(),
+            config.memory_budget,
+            rng
         );
+        let path = match &config.cache_path {
+            None => return,
+            Some(path) => path,
+        };
+        match self.optimizations.load(path) {
+            Ok(true) => {
+                log::info!("Loaded the precomputation from {}", path);
+                return;
+            }
+            Ok(false) => log::info!("The precomputation in {} is stale, rebuilding it", path),
+            Err(e) => log::info!("Failed to load the precomputation from {}: {}, rebuilding it", path, e),
+        }
+        if let Err(e) = self.optimizations.save(path) {
+            log::warn!("Failed to write the precomputation to {}: {}", path, e);
+        }
     }
 
     /// Computes g1^a1, g1^a2,..., g1^a_x efficiently using pre-computation
     pub fn fixed_base_scalar_mul_g1(&self, scalars: &[Scalar<E>]) -> Vec<E::G1Projective> {
         FixedBaseMSM::multi_scalar_mul(self.optimizations.scalar_bits, 
             self.optimizations.window_size, 
-            &self.optimizations.g1_table,
+            self.optimizations.g1_table(),
             scalars 
         )
     }
@@ -114,7 +250,7 @@ where E:PairingEngine,
     pub fn fixed_base_scalar_mul_g2(&self, scalars: &[Scalar<E>]) -> Vec<E::G2Projective> {
         FixedBaseMSM::multi_scalar_mul(self.optimizations.scalar_bits, 
             self.optimizations.window_size, 
-            &self.optimizations.g2_table,
+            self.optimizations.g2_table(),
             scalars 
         )
     }
@@ -153,4 +289,22 @@ where E:PairingEngine,
         );
         <E as PairingEngine>::final_exponentiation(&(lval*rval)).unwrap() == E::Fqk::one()
     }
-}
\ No newline at end of file
+}
+
+/// Writes the retired contexts without their `Arc`s
+fn serialize_retired<E, S>(retired: &[(u64, Arc<DbsContext<E>>)], s: S) -> Result<S::Ok, S::Error>
+where E: PairingEngine,
+    S: Serializer,
+    DbsContext<E>: Serialize,
+{
+    s.collect_seq(retired.iter().map(|(switch_epoch, ctx)| (switch_epoch, ctx.as_ref())))
+}
+
+fn deserialize_retired<'de, E, D>(d: D) -> Result<Vec<(u64, Arc<DbsContext<E>>)>, D::Error>
+where E: PairingEngine,
+    D: Deserializer<'de>,
+    DbsContext<E>: Deserialize<'de>,
+{
+    let retired: Vec<(u64, DbsContext<E>)> = Vec::deserialize(d)?;
+    Ok(retired.into_iter().map(|(switch_epoch, ctx)| (switch_epoch, Arc::new(ctx))).collect())
+}
@@ -1,19 +1,21 @@
-use crate::{Commitment, DbsContext, DbsError, Dleq, Encryptions, Polynomial, Scalar, Share, SingleDleq, precomputes::Precomputation};
+use crate::{Commitment, DSSSecretKey, DbsContext, DbsError, Dleq, Encryptions, Polynomial, Scalar, Share, SingleDleq, precomputes::Precomputation, std_rng, Transcript};
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
-use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
 use ark_poly::{Polynomial as Poly, UVPolynomial};
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use fnv::FnvHashMap as HashMap;
 use crate::pvss::*;
+use crate::parallel::{num_threads, par_find_first, par_map};
 
 impl<E> DbsContext<E> 
 where E:PairingEngine,
 {
-    /// Creates a PVSS sharing for a random secret s\gets Zq
+    /// Creates a PVSS sharing for a random secret s\gets Zq, dealt in the epoch
     /// Returns (v,c,pi)
     pub fn generate_shares<R>(
         &self,
-        dss_sk: &crypto_lib::Keypair,
+        epoch: u64,
+        dss_sk: &DSSSecretKey,
         rng: &mut R,
     ) -> PVSSVec<E>
     where
@@ -21,14 +23,15 @@ where E:PairingEngine,
     {
         // Generate random co-efficients a0,a1,...,at
         let secret = Scalar::<E>::rand(rng);
-        self.generate_share_for_point(dss_sk, rng, secret)       
+        self.generate_share_for_point(epoch, dss_sk, rng, secret)       
     }
 
-    /// Creates a PVSS sharing for a given secret s\gets Zq
+    /// Creates a PVSS sharing for a given secret s\gets Zq, dealt in the epoch
     /// Returns (v,c,pi)
     pub fn generate_share_for_point<R>(
         &self,
-        dss_sk: &crypto_lib::Keypair,
+        epoch: u64,
+        dss_sk: &DSSSecretKey,
         mut rng: &mut R,
         secret: Scalar<E>,
     ) -> PVSSVec<E>
@@ -42,30 +45,35 @@ where E:PairingEngine,
         polynomial.coeffs[0] = secret;
         
         // s_i = p(i)
-        let evaluations: Vec<_> = (0..n).map(|i| 
+        let evaluations = par_map(n, |i| 
             polynomial.evaluate(&Scalar::<E>::from(i as u64 + 1))
-        ).collect();
+        );
 
         // v_i = g1^s_i
         let commitments = self.fixed_base_scalar_mul_g1(&evaluations);
         
         // TODO(Optimize)
         // c_i = pk_i^{s_i}
-        let encryptions: Vec<_> = (0..n).map(|i| {
+        let encryptions = par_map(n, |i| {
             // self.public_keys[i].mul(evaluations[i].into_repr())
             self.optimizations.encyrpt(i, evaluations[i])
-        }).collect();
+        });
 
+        // Every proof draws its nonce from its own rng, so that the sharing does not depend on the order in which the proofs are made
+        let seeds: Vec<[u8; 32]> = (0..n).map(|_| rng.gen()).collect();
         // dleq.prove(s_i,pki,e_i,g2,c_i)
-        let proof:Vec<_> = (0..self.n).map(|i| {
+        let proof = par_map(n, |i| {
             Dleq::<E::G1Projective, E::G2Projective, E::Fr>::prove( 
+                &Self::sharing_transcript(self.origin, epoch, i),
                 &evaluations[i], 
                 &self.optimizations.g1p, 
                 &commitments[i],
                 &self.public_keys[i], 
                 &encryptions[i], 
-                 dss_sk, rng)
-        }).collect();
+                self.proof_mode,
+                dss_sk, 
+                &mut StdRng::from_seed(seeds[i]))
+        });
 
         // Convert commitments into affine form
         let commitments = commitments
@@ -75,10 +83,14 @@ where E:PairingEngine,
 
         let gs = self.optimizations.g1p.mul(secret.into_repr());
         let sok = SingleDleq::<E::G1Projective, Scalar<E>>::prove(
+            &Self::secret_transcript(self.origin, epoch),
             &secret, 
             &self.optimizations.g1p, 
             &gs, 
+            self.proof_mode,
             dss_sk, 
+            self.my_key.expose_secret(),
+            &self.public_keys[self.origin],
             rng
         );
         
@@ -89,33 +101,72 @@ where E:PairingEngine,
             proofs: proof,
             gs,
             sig_of_knowledge: sok,
+            key_version: self.key_version,
+            epoch,
         }
     }
 
-    /// Verifies whether a given PVSS vector is valid
+    /// Verifies whether a given PVSS vector from the dealer is valid
     /// Returns false if the verification fails
+    /// Vectors encrypted under an older key version are checked against those keys, as long as we still have them
+    /// The proofs are checked for the epoch in the vector, so the caller must check that the epoch is recent, or old (and possibly reconstructed) sharings can be replayed
     pub fn verify_sharing(&self, 
+        dealer: usize,
         pvec: &PVSSVec<E>, 
         dss_pk: &crypto_lib::PublicKey
     ) -> Option<DbsError>
     {
+        if pvec.key_version != self.key_version {
+            return match self.for_key_version(pvec.key_version) {
+                Some(ctx) => ctx.verify_sharing(dealer, pvec, dss_pk),
+                None => Some(DbsError::UnknownKeyVersion(pvec.key_version)),
+            };
+        }
+        if let Some(e) = self.check_vectors(&pvec.comms, &pvec.encs) {
+            return Some(e);
+        }
+        if pvec.proofs.len() != self.n {
+            return Some(DbsError::WrongNumberOfProofs(pvec.proofs.len()));
+        }
+        if pvec.gs.is_zero() {
+            return Some(DbsError::IdentityGs);
+        }
         if !coding_check(&self.optimizations, &pvec.comms) {
             return Some(DbsError::CodingCheckFailed);
         }
 
         // OPTIMIZATION: Proof of knowledge check = Dleq = Pairing check
         // If this passes, we know that the pairing check will pass, so don't do pairings
-        for i in 0..self.n {
+        // OPTIMIZATION: Check all the n proofs in one batch, and only if that fails check them one by one to find the culprit
+        let comms: Vec<_> = pvec.comms.iter().map(|c| c.into_projective()).collect();
+        let transcripts: Vec<_> = (0..self.n)
+            .map(|i| Self::sharing_transcript(dealer, pvec.epoch, i))
+            .collect();
+        if !Dleq::<E::G1Projective, E::G2Projective, E::Fr>::batch_verify(
+            &transcripts,
+            &pvec.proofs, 
+            &self.optimizations.g1p, 
+            &comms, 
+            &self.public_keys, 
+            &pvec.encs, 
+            self.proof_mode,
+            dss_pk, 
+            &mut std_rng()) 
+        {
             // Check if dleq(pi, g2, ci, pk, enc)
-            if let Some(x) = Dleq::<E::G1Projective, E::G2Projective, E::Fr>::verify(
-                &pvec.proofs[i], 
-                &self.optimizations.g1p, 
-                &pvec.comms[i].into_projective(), 
-                &self.public_keys[i], 
-                &pvec.encs[i], 
-                dss_pk) 
-            {
-                log::error!("Got error from dleq checker {:?}", x);
+            let culprit = par_find_first(self.n, |i| {
+                Dleq::<E::G1Projective, E::G2Projective, E::Fr>::verify(
+                    &transcripts[i],
+                    &pvec.proofs[i], 
+                    &self.optimizations.g1p, 
+                    &comms[i], 
+                    &self.public_keys[i], 
+                    &pvec.encs[i], 
+                    self.proof_mode,
+                    dss_pk).is_some()
+            });
+            if let Some(i) = culprit {
+                log::error!("Got an invalid dleq proof for {} from {}", i, dealer);
                 return Some(DbsError::DlogProofCheckFailed(i));
             }
         }
@@ -131,11 +182,18 @@ where E:PairingEngine,
         }
 
         // Check if dleq(pi, g, g^s)
+        let dealer_pk = match self.public_keys.get(dealer) {
+            Some(pk) => pk,
+            None => return Some(DbsError::IndexOutOfRange(dealer)),
+        };
         if let Some(x) = SingleDleq::verify(
+            &Self::secret_transcript(dealer, pvec.epoch),
             &pvec.sig_of_knowledge, 
             &self.optimizations.g1p, 
             &pvec.gs, 
-            dss_pk) 
+            self.proof_mode,
+            dss_pk,
+            dealer_pk) 
         {
             return Some(x)
         }
@@ -143,6 +201,7 @@ where E:PairingEngine,
     }
     
     /// Aggregate homomorphically combines all the PVSS vectors together
+    /// All the vectors must be encrypted under the same key version, and dealt in the same epoch
     /// WARNING: This operation is destructive and destroys the original shares
     ///          Clone the vector before using
     pub fn aggregate(&self,
@@ -151,33 +210,39 @@ where E:PairingEngine,
     ) -> (AggregatePVSS<E>, DecompositionProof<E>) 
     {
         assert_eq!(indices.len(), pvec.len());
+        let key_version = pvec.first().map_or(self.key_version, |v| v.key_version);
+        assert!(pvec.iter().all(|v| v.key_version == key_version), 
+            "Cannot aggregate vectors encrypted under different key versions");
+        let epoch = pvec.first().map_or(0, |v| v.epoch);
+        assert!(pvec.iter().all(|v| v.epoch == epoch), 
+            "Cannot aggregate vectors dealt in different epochs");
 
         // v_i = v1_i * v2_i * ... * vt+1_i
-        let combined_encs = (0..self.n)
-            .map(|i| { 
-                (0..pvec.len())
-                    .fold(E::G2Projective::zero(), 
-                    |acc, j| {
-                        acc + pvec[j].encs[i]
-                    }
-                )
-            })
-            .collect();
+        let combined_encs = par_map(self.n, |i| { 
+            (0..pvec.len())
+                .fold(E::G2Projective::zero(), 
+                |acc, j| {
+                    acc + pvec[j].encs[i]
+                }
+            )
+        });
         
         // c_i = c1_i * c2_i * ... * ct+1_i
-        let combined_comms = (0..self.n).map(
-            |i| {(0..pvec.len()).fold(
+        let combined_comms = par_map(self.n, |i| {
+            (0..pvec.len()).fold(
                 E::G1Projective::zero(), 
                 |acc, j| {
                     acc + pvec[j].comms[i].into_projective()
                 }
             ).into_affine()
-        }).collect();
+        });
         
         // Combined public component
         let agg_pvss = AggregatePVSS{
             encs: combined_encs,
             comms: combined_comms,
+            key_version,
+            epoch,
         };
         // Decomposition proofs
         let mut dleq_proof = Vec::with_capacity(pvec.len());
@@ -199,6 +264,15 @@ where E:PairingEngine,
     /// pverify verifies if the public part of the combined PVSS vector is correct
     pub fn pverify(&self, agg_pvss: &AggregatePVSS<E>) -> Option<DbsError> 
     {
+        if agg_pvss.key_version != self.key_version {
+            return match self.for_key_version(agg_pvss.key_version) {
+                Some(ctx) => ctx.pverify(agg_pvss),
+                None => Some(DbsError::UnknownKeyVersion(agg_pvss.key_version)),
+            };
+        }
+        if let Some(e) = self.check_vectors(&agg_pvss.comms, &agg_pvss.encs) {
+            return Some(e);
+        }
         // Coding check
         if !coding_check(&self.optimizations, 
             &agg_pvss.comms
@@ -207,13 +281,14 @@ where E:PairingEngine,
             return Some(DbsError::CodingCheckFailed);
         }
         // Pairing check
+        // OPTIMIZATION - Fold all the pairing checks into one multi-pairing, and only check them one by one to find the culprit
+        if self.batch_pairing_check(agg_pvss, &mut std_rng()) {
+            return None;
+        }
         // OPTIMIZATION - Save 2 pairings (roughly 3-4ms) by not checking self value; we will already check this using decomposition proof
-        for id in 0..self.n {
-            if id == self.origin as usize {
-                continue;
-            }
+        let culprit = par_find_first(self.n, |id| {
             // e(g1, c_j) =? e(v_j, pk_j)
-            if !DbsContext::<E>::reduced_pairing_check_part(
+            id != self.origin as usize && !DbsContext::<E>::reduced_pairing_check_part(
                 self.optimizations.g1_prepared.clone(), 
                 agg_pvss.encs[id].into().into(),
                 (-agg_pvss.comms[id]).into(),
@@ -221,20 +296,85 @@ where E:PairingEngine,
             )
             // if E::pairing(agg_pvss.encs[id], self.optimizations.g2p)
             // != E::pairing(self.public_keys[id], agg_pvss.comms[id]) 
-            {
-                return Some(DbsError::PairingCheckFailed(id));
-            }
+        });
+        culprit.map(DbsError::PairingCheckFailed)
+    }
+    
+    /// Checks e(g1, c_j) = e(v_j, pk_j) for all j (except for ourselves) with a single multi-pairing
+    ///
+    /// OPTIMIZATION: Pick random weights rho_j and check
+    /// e(g1, prod c_j^rho_j) * prod e(v_j^-rho_j, pk_j) = 1
+    /// This needs n+1 miller loops and one final exponentiation instead of 2n miller loops and n final exponentiations
+    /// Returns false if any of the n checks fails
+    pub(crate) fn batch_pairing_check<R>(&self, agg_pvss: &AggregatePVSS<E>, rng: &mut R) -> bool
+    where R: Rng + ?Sized,
+    {
+        let ids: Vec<_> = (0..self.n)
+            .filter(|&id| id != self.origin as usize)
+            .collect();
+        if ids.is_empty() {
+            return true;
         }
-        None
+        // 128-bit weights are sufficient for soundness
+        let rhos: Vec<_> = ids.iter()
+            .map(|_| Scalar::<E>::from(rng.gen::<u128>()).into_repr())
+            .collect();
+
+        // prod c_j^rho_j
+        let encs = E::G2Projective::batch_normalization_into_affine(
+            &ids.iter().map(|&id| agg_pvss.encs[id]).collect::<Vec<_>>()
+        );
+        let combined_enc = DbsContext::<E>::var_base_scalar_mul(&encs, &rhos);
+
+        // v_j^-rho_j
+        let weighted_comms = E::G1Projective::batch_normalization_into_affine(
+            &par_map(ids.len(), |i| {
+                (-agg_pvss.comms[ids[i]]).mul(rhos[i])
+            })
+        );
+
+        let mut pairs = Vec::with_capacity(ids.len() + 1);
+        pairs.push((
+            self.optimizations.g1_prepared.clone(), 
+            combined_enc.into_affine().into(),
+        ));
+        for (i, &id) in ids.iter().enumerate() {
+            pairs.push((
+                weighted_comms[i].into(), 
+                self.optimizations.pub_keys_p[id].clone(),
+            ));
+        }
+        // OPTIMIZATION: The miller loops are independent, so we split them among the threads and multiply the results
+        let chunks: Vec<_> = pairs.chunks((pairs.len() + num_threads() - 1) / num_threads()).collect();
+        let loops = par_map(chunks.len(), |i| {
+            <E as PairingEngine>::miller_loop(chunks[i].iter())
+        });
+        let product = loops.iter().fold(E::Fqk::one(), |acc, x| acc * x);
+        <E as PairingEngine>::final_exponentiation(&product)
+            .map_or(false, |res| res == E::Fqk::one())
     }
     
     /// decomp_verify checks if the decomposition proof received is correct
+    /// Like `verify_sharing`, the proofs are checked for the epoch in the aggregate, which the caller must check
     pub fn decomp_verify(&self, 
         agg_pvss:&AggregatePVSS<E>, 
         agg_pi:&DecompositionProof<E>, 
         pk_map: &HashMap<usize, crypto_lib::PublicKey>
     ) -> Option<DbsError> 
     {
+        // The proofs for the secrets are bound to the PVSS keys of the dealers when the aggregate was made
+        if agg_pvss.key_version != self.key_version {
+            return match self.for_key_version(agg_pvss.key_version) {
+                Some(ctx) => ctx.decomp_verify(agg_pvss, agg_pi, pk_map),
+                None => Some(DbsError::UnknownKeyVersion(agg_pvss.key_version)),
+            };
+        }
+        if agg_pvss.comms.len() != self.n {
+            return Some(DbsError::WrongNumberOfCommitments(agg_pvss.comms.len()));
+        }
+        if let Some(e) = self.check_decomposition(agg_pi, pk_map) {
+            return Some(e);
+        }
         let into_repr: Vec<_> = (0..self.t+1).map(|i| {
             self.optimizations.fixed_lagranges[i].into()
         }).collect();
@@ -252,10 +392,13 @@ where E:PairingEngine,
         for i in 0..agg_pi.dleq_proof.len() {
             // Check if  dleq(pi, g1, g1^s)
             if let Some(x) = SingleDleq::verify(
+                &Self::secret_transcript(agg_pi.indices[i], agg_pvss.epoch),
                 &agg_pi.dleq_proof[i], 
                 &self.optimizations.g1p, 
                 &agg_pi.gs_vec[i], 
-                &pk_map[&agg_pi.indices[i]]
+                self.proof_mode,
+                &pk_map[&agg_pi.indices[i]],
+                &self.public_keys[agg_pi.indices[i]],
             ) {
                 return Some(x);
             }
@@ -264,20 +407,28 @@ where E:PairingEngine,
         None
     }
     
-    /// Decrypt an encryption meant for me
+    /// Decrypt an encryption meant for me in the epoch
+    /// The encryption must be under the keys of this context; use `for_key_version` for older aggregates
     /// OPTIMIZATION - Pairing is expensive, NIZKs are cheap
     /// Send a NIZK proof to avoid pairing checks
-    pub fn decrypt_share<R>(&self, e: &Encryptions<E>, dss_sk: &crypto_lib::Keypair, rng:&mut R) -> Decryption<E>
+    pub fn decrypt_share<R>(&self, 
+        epoch: u64, 
+        e: &Encryptions<E>, 
+        dss_sk: &DSSSecretKey, 
+        rng:&mut R
+    ) -> Decryption<E>
     where R: Rng+?Sized,
     {
         // OPTIMIZATION - Precompute my_key.inverse
-        let d = e.mul(self.optimizations.my_key_inv.into_repr());
+        let d = e.mul(self.optimizations.my_key_inv.expose_secret().into_repr());
         let pi = Dleq::<E::G2Projective, E::G2Projective, E::Fr>::prove(
-            &self.my_key, 
+            &Self::decryption_transcript(self.origin, epoch),
+            self.my_key.expose_secret(), 
             &self.optimizations.g2p, 
             &self.public_keys[self.origin as usize], 
             &d, 
             &e, 
+            self.proof_mode,
             dss_sk, 
             rng);
         Decryption{
@@ -286,103 +437,246 @@ where E:PairingEngine,
         }
     }
     
-    /// Verify the received share and check the NIZK proof to see if it was decrypted correctly
+    /// Verify the received share for the epoch and check the NIZK proof to see if it was decrypted correctly
+    /// The encryption must be under the keys of this context; use `for_key_version` for older aggregates
     pub fn verify_share(&self, 
         origin: usize,
+        epoch: u64,
         e: &Encryptions<E>, 
         dec: &Decryption<E>,
         dss_pk: &crypto_lib::PublicKey
     ) -> Option<DbsError> 
     {
+        if origin >= self.n {
+            return Some(DbsError::IndexOutOfRange(origin));
+        }
         // Verify dleq(pi, g2p, pk, dec, enc)
         Dleq::verify(
+            &Self::decryption_transcript(origin, epoch),
             &dec.proof, 
             &self.optimizations.g2p, 
             &self.public_keys[origin], 
             &dec.dec, 
             e, 
+            self.proof_mode,
             &dss_pk
         )
     }
     
+    /// Checks that there are n commitments and n encryptions, and that none of them is the identity
+    /// Call this before looking into the vectors from other nodes, so that malformed vectors cannot make us panic
+    fn check_vectors(&self, comms: &[Commitment<E>], encs: &[Encryptions<E>]) -> Option<DbsError> {
+        if comms.len() != self.n {
+            return Some(DbsError::WrongNumberOfCommitments(comms.len()));
+        }
+        if encs.len() != self.n {
+            return Some(DbsError::WrongNumberOfEncryptions(encs.len()));
+        }
+        if let Some(i) = comms.iter().position(|c| c.is_zero()) {
+            return Some(DbsError::IdentityCommitment(i));
+        }
+        if let Some(i) = encs.iter().position(|e| e.is_zero()) {
+            return Some(DbsError::IdentityEncryption(i));
+        }
+        None
+    }
+
+    /// Checks that the decomposition proof has one proof and one g^s (not the identity) for every index, and that the indices are distinct nodes that we know
+    fn check_decomposition(&self, 
+        agg_pi: &DecompositionProof<E>, 
+        pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> Option<DbsError>
+    {
+        let k = agg_pi.indices.len();
+        if k == 0 || agg_pi.dleq_proof.len() != k || agg_pi.gs_vec.len() != k {
+            return Some(DbsError::DecompositionLengthMismatch);
+        }
+        let mut seen = vec![false; self.n];
+        for &i in &agg_pi.indices {
+            if i >= self.n {
+                return Some(DbsError::IndexOutOfRange(i));
+            }
+            if seen[i] {
+                return Some(DbsError::DuplicateIndex(i));
+            }
+            seen[i] = true;
+            if !pk_map.contains_key(&i) {
+                return Some(DbsError::UnknownNode(i));
+            }
+        }
+        if agg_pi.gs_vec.iter().any(|gs| gs.is_zero()) {
+            return Some(DbsError::IdentityGs);
+        }
+        None
+    }
+
+    /// The context for the proof that the dealer encrypted the i-th share correctly in a sharing dealt in the epoch
+    pub fn sharing_transcript(dealer: usize, epoch: u64, i: usize) -> Transcript {
+        let mut t = Transcript::new(b"pvss-sharing");
+        t.append_u64(b"dealer", dealer as u64);
+        t.append_u64(b"epoch", epoch);
+        t.append_u64(b"index", i as u64);
+        t
+    }
+
+    /// The context for the proof that the dealer knows the secret s in g1^s, for a sharing dealt in the epoch
+    pub fn secret_transcript(dealer: usize, epoch: u64) -> Transcript {
+        let mut t = Transcript::new(b"pvss-secret");
+        t.append_u64(b"dealer", dealer as u64);
+        t.append_u64(b"epoch", epoch);
+        t
+    }
+
+    /// The context for the proof that the node decrypted its share correctly in the epoch
+    pub fn decryption_transcript(decrypter: usize, epoch: u64) -> Transcript {
+        let mut t = Transcript::new(b"pvss-decryption");
+        t.append_u64(b"decrypter", decrypter as u64);
+        t.append_u64(b"epoch", epoch);
+        t
+    }
+
     /// Reconstruct after obtaining t+1 valid decryptions
     /// Returns (B,S=e(B,h'))
     pub fn reconstruct(&self,
         decrypted_shares: &[Option<Share<E>>]
     ) -> Beacon<E>
     {
-        let valid_share_indices: Vec<_> = (0..self.n)
-        .filter(|&i| decrypted_shares[i].is_some())
-        .map(|i| (i, Scalar::<E>::from(i as u64 + 1)))
-        .collect();
-        // OPTIMIZATIONS - Precompute (i-j)^-1 before hand
-        // This is (n-1)^2 pre-computations, can be reduced to 2n pre-computations
-        let secret = valid_share_indices
-        .iter()
-        .map(|&(i, _scalar_i)| {
-            decrypted_shares[i].unwrap().mul(
-                valid_share_indices
-                    .iter()
-                    .map(|&(j, scalar_j)| {
-                        if j == i {
-                            Scalar::<E>::one()
-                        } else {
-                            scalar_j * self.optimizations.lagrange_inverses[&(j,i)]
-                        }
-                    })
-                    .fold(Scalar::<E>::one(), |lambda, x| lambda * x).into_repr(),
-            )
-        })
-        .fold(E::G2Projective::zero(), |acc, x| acc + x);
+        let indices: Vec<_> = (0..std::cmp::min(decrypted_shares.len(), self.n))
+            .filter(|&i| decrypted_shares[i].is_some())
+            .collect();
+        // OPTIMIZATIONS - The lagrange co-efficients for this set of shares from the cache, and a multi-exponentiation to combine the shares
+        let lagranges: Vec<_> = self.optimizations.lagranges.get(&indices)
+            .iter()
+            .map(|l| l.into_repr())
+            .collect();
+        let shares = E::G2Projective::batch_normalization_into_affine(
+            &indices.iter().map(|&i| decrypted_shares[i].unwrap()).collect::<Vec<_>>()
+        );
+        let secret = DbsContext::<E>::var_base_scalar_mul(&shares, &lagranges);
         Beacon{
             beacon: E::pairing(self.h1p, secret), 
             value: secret,
         }
     }
 
-    /// Check beacon checks if this is the correct beacon
+    /// Reconstructs the beacon of the epoch from unverified decryptions, and identifies the bad ones if there are any
+    /// decs[i] is the decryption sent by node i (if any) for the aggregated PVSS vector pvss
+    /// Returns the beacon (None if there are not enough valid decryptions) and the nodes that sent invalid decryptions
+    ///
+    /// OPTIMIZATION: We optimistically reconstruct from the first t+1 decryptions without verifying them, and check the result against the commitments.
+    /// This costs a multi-exponentiation and two pairings instead of t+1 DLEQ verifications
+    /// Only if the check fails, we verify every decryption, blame the nodes with invalid ones, and reconstruct from the valid ones
+    pub fn reconstruct_optimistic(&self,
+        epoch: u64,
+        pvss: &AggregatePVSS<E>,
+        decs: &[Option<Decryption<E>>],
+        pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> (Option<Beacon<E>>, Vec<usize>)
+    {
+        if pvss.key_version != self.key_version {
+            return match self.for_key_version(pvss.key_version) {
+                Some(ctx) => ctx.reconstruct_optimistic(epoch, pvss, decs, pk_map),
+                None => (None, Vec::new()),
+            };
+        }
+        if pvss.encs.len() != self.n || pvss.comms.len() != self.n {
+            return (None, Vec::new());
+        }
+        let indices: Vec<_> = (0..std::cmp::min(decs.len(), self.n))
+            .filter(|&i| decs[i].is_some())
+            .collect();
+        if indices.len() < self.t+1 {
+            return (None, Vec::new());
+        }
+
+        // Optimistic path
+        let mut shares = vec![None; self.n];
+        for &i in &indices[0..self.t+1] {
+            shares[i] = decs[i].as_ref().map(|d| d.dec);
+        }
+        let beacon = self.reconstruct(&shares);
+        if self.check_beacon(&beacon, &pvss.comms) {
+            return (Some(beacon), Vec::new());
+        }
+
+        // Pessimistic path: Find the culprits
+        let mut blamed = Vec::new();
+        let mut shares = vec![None; self.n];
+        let mut num_valid = 0;
+        for &i in &indices {
+            let dec = decs[i].as_ref().unwrap();
+            let valid = match pk_map.get(&i) {
+                None => false,
+                Some(pk) => self.verify_share(i, epoch, &pvss.encs[i], dec, pk).is_none(),
+            };
+            if !valid {
+                blamed.push(i);
+                continue;
+            }
+            shares[i] = Some(dec.dec);
+            num_valid += 1;
+        }
+        if num_valid < self.t+1 {
+            return (None, blamed);
+        }
+        (Some(self.reconstruct(&shares)), blamed)
+    }
+
+    /// Check beacon checks if this is the correct beacon using the commitments of the first t+1 nodes
     pub fn check_beacon(&self, b: &Beacon<E>, cvec: &[Commitment<E>]) -> bool {
-        if E::pairing(self.h1p, b.value) != b.beacon {
+        let indices: Vec<_> = (0..self.t+1).collect();
+        self.check_beacon_for(b, cvec, &indices)
+    }
+
+    /// Checks if this is the correct beacon using the commitments of the nodes in indices
+    /// cvec[i] is the commitment of node i, and indices must contain at least t+1 distinct nodes (only the first t+1 are used)
+    ///
+    /// The beacon is correct if e(h1,g2^s) = beacon and e(g1,g2^s) = e(g1^s,g2), where g1^s is interpolated from the commitments
+    /// OPTIMIZATION: We check both with one multi-pairing: For a challenge r derived from the inputs, check
+    /// e(g1 + r*h1, g2^s) * e(-g1^s, g2) = beacon^r
+    /// OPTIMIZATION: The lagrange co-efficients for the first t+1 nodes are pre-computed
+    pub fn check_beacon_for(&self, 
+        b: &Beacon<E>, 
+        cvec: &[Commitment<E>], 
+        indices: &[usize],
+    ) -> bool 
+    {
+        if indices.len() < self.t+1 {
             return false;
         }
-        let mut counter = 0;
-        let mut com_vec = Vec::with_capacity(self.t+1);
-        for v in cvec {
-            if counter > self.t +1 {
-                break;
-            }
-            com_vec.push(v);
-            counter += 1;
-        }
-        let valid_share_indices: Vec<_> = (0..self.t+1)
-        .map(|i| (i, Scalar::<E>::from(i as u64 + 1)))
-        .collect();
-        let hs = valid_share_indices
-        .iter()
-        .map(|&(i, _scalar_i)| {
-            com_vec[i].mul(
-                valid_share_indices
-                    .iter()
-                    .map(|&(j, scalar_j)| {
-                        if j == i {
-                            Scalar::<E>::one()
-                        } else {
-                            scalar_j * self.optimizations.lagrange_inverses[&(j,i)]
-                        }
-                    })
-                    .fold(Scalar::<E>::one(), |lambda, x| lambda * x).into_repr(),
-            )
-        })
-        .fold(E::G1Projective::zero(), |acc, x| acc + x);
-        // Check e(g'1,g2^s) = e(g'1^s,g2)
-        DbsContext::<E>::reduced_pairing_check_part(
-            self.optimizations.h1_prepared.clone(), 
-            b.value.into().into(), 
-            (-hs.into()).into(),
-            self.optimizations.g2_prepared.clone(), 
-        )
-        // E::pairing(b.value, self.h2p) == E::pairing(self.optimizations.g1p, hs)
-    } 
+        let indices = &indices[0..self.t+1];
+        let mut sorted = indices.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if sorted.len() != indices.len() || sorted[sorted.len()-1] >= cvec.len() {
+            return false;
+        }
+
+        // g1^s
+        let into_repr: Vec<_> = if indices.iter().enumerate().all(|(i, &j)| i == j) {
+            self.optimizations.fixed_lagranges.iter().map(|l| l.into_repr()).collect()
+        } else {
+            self.optimizations.lagranges.get(indices).iter().map(|l| l.into_repr()).collect()
+        };
+        let bases: Vec<_> = indices.iter().map(|&i| cvec[i]).collect();
+        let gs = DbsContext::<E>::var_base_scalar_mul(&bases, &into_repr).into_affine();
+
+        let mut t = Transcript::new(b"beacon-check");
+        t.append_point(b"value", &b.value.into_affine());
+        t.append_point(b"beacon", &b.beacon);
+        t.append_point(b"commitment", &gs);
+        let (_, r) = t.challenge::<Scalar<E>>();
+        let r = r.into_repr();
+
+        let lhs = self.optimizations.g1p + self.h1p.mul(r);
+        let pairs: [(E::G1Prepared, E::G2Prepared); 2] = [
+            (lhs.into_affine().into(), b.value.into_affine().into()),
+            ((-gs).into(), self.optimizations.g2_prepared.clone()),
+        ];
+        <E as PairingEngine>::final_exponentiation(
+            &<E as PairingEngine>::miller_loop(pairs.iter())
+        ).map_or(false, |res| res == b.beacon.pow(r))
+    }
 }
 
 fn coding_check<E>(ctx: &Precomputation<E>, comms: &[Commitment<E>]) -> bool 
@@ -2,14 +2,14 @@ use crate::{DbsError, ark_serde::{
     canonical_deserialize, 
     canonical_serialize, 
 }};
-use crate::hash::ser_and_hash;
-use ark_ec::ProjectiveCurve;
-use ark_ff::PrimeField;
+use crate::hash::Hash;
+use crate::{DSSSecretKey, Transcript, batch_verify_sigs};
+use ark_ec::{ProjectiveCurve, msm::VariableBaseMSM};
+use ark_ff::{PrimeField, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
-use ark_ff::to_bytes;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct DleqProof<G1, G2, S> 
@@ -37,14 +37,36 @@ where
     /// Signatures
     /// These are used to pin the proof to the node creating the sharing
     /// This is necessary for the decomposition checks to go through
+    /// Empty in the SignatureOfKnowledge mode, where the challenge pins the proof instead
     pub sig: Vec<u8>, 
 }
 
+/// A Schnorr proof for log_k(pk), where pk is the PVSS public key of the prover and k the generator of its group
+/// It shares its challenge with the single discrete log proof it is attached to
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-pub struct SingleDleqProof<G, S> 
+pub struct KeyProof<K, S> 
 where 
-    G: CanonicalSerialize + CanonicalDeserialize,
+    K: CanonicalSerialize + CanonicalDeserialize,
     S: CanonicalSerialize + CanonicalDeserialize
+{
+    /// The commitment k^w
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub a: K,
+    /// The response w - c*sk
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub r: S,
+}
+
+/// A proof for log_g(x)
+/// K is the group of the PVSS public key of the prover, which is proven along with it in the SignatureOfKnowledge mode
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SingleDleqProof<G, S, K = G> 
+where 
+    G: CanonicalSerialize + CanonicalDeserialize,
+    S: CanonicalSerialize + CanonicalDeserialize,
+    K: CanonicalSerialize + CanonicalDeserialize,
 {
     #[serde(serialize_with = "canonical_serialize")]
     #[serde(deserialize_with = "canonical_deserialize")]
@@ -62,7 +84,12 @@ where
     /// Signatures
     /// These are used to pin the proof to the node creating the sharing
     /// This is necessary for the decomposition checks to go through
+    /// Empty in the SignatureOfKnowledge mode, where the key proof pins the proof instead
     pub sig: Vec<u8>, 
+    /// The proof of knowledge of the PVSS secret key of the prover in the SignatureOfKnowledge mode, and None otherwise
+    #[serde(bound(serialize = "KeyProof<K, S>: Serialize"))]
+    #[serde(bound(deserialize = "KeyProof<K, S>: Deserialize<'de>"))]
+    pub key_proof: Option<KeyProof<K, S>>,
 }
 
 pub struct Dleq<G1, G2, S>
@@ -77,6 +104,57 @@ pub struct SingleDleq<G, S>
     _x: PhantomData<G>,
     _y: PhantomData<S>,
 }
+/// The domain tag for DLEQ proofs
+pub const DLEQ_DOMAIN: &[u8] = b"dleq";
+/// The domain tag for single discrete log proofs
+pub const SINGLE_DLEQ_DOMAIN: &[u8] = b"single-dleq";
+
+/// How a DLEQ proof is pinned to the node creating it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofMode {
+    /// The prover signs the challenge hash with its DSS key, and the signature is sent along with the proof
+    Signed,
+    /// The prover's DSS public key is absorbed into the challenge, and the proof for the secret of a sharing also proves knowledge of the prover's PVSS secret key under the same challenge
+    /// Anyone can make the DLEQ proofs for the shares of a sharing whose evaluations it knows, so a sharing (or a decomposition) is only pinned to its dealer by the proof for its secret
+    /// OPTIMIZATION: Saves n signatures per sharing for a Schnorr proof, and n signature verifications for two exponentiations
+    SignatureOfKnowledge,
+}
+
+impl Default for ProofMode {
+    fn default() -> Self {
+        ProofMode::Signed
+    }
+}
+
+impl ProofMode {
+    /// Returns the transcript to derive the challenge from for a proof by the holder of dss_pk
+    fn bind(&self, transcript: &Transcript, dss_pk: &crypto_lib::PublicKey) -> Transcript {
+        let mut t = transcript.clone();
+        if let ProofMode::SignatureOfKnowledge = self {
+            t.append_message(b"signer", &dss_pk.clone().into_protobuf_encoding());
+        }
+        t
+    }
+
+    /// Returns the signature to attach to a proof whose challenge hash is hash
+    fn sign(&self, hash: &Hash, my_key: &DSSSecretKey) -> Vec<u8> {
+        match self {
+            ProofMode::Signed => my_key
+                .sign(hash)
+                .expect("Failed to sign the DLEQ Proof"),
+            ProofMode::SignatureOfKnowledge => Vec::new(),
+        }
+    }
+
+    /// Checks the signature attached to a proof whose challenge hash is hash
+    fn verify(&self, hash: &Hash, sig: &[u8], dss_pk: &crypto_lib::PublicKey) -> bool {
+        match self {
+            ProofMode::Signed => dss_pk.verify(hash, sig),
+            // Do not accept any junk in place of the signature
+            ProofMode::SignatureOfKnowledge => sig.is_empty(),
+        }
+    }
+}
 
 impl<G1, G2, S> Dleq<G1, G2, S> 
 where 
@@ -87,12 +165,38 @@ where
         + Into<G1::ScalarField>
         + Into<G2::ScalarField>,
 {
-    pub fn prove<R>(knowledge: &S,
+    /// Computes the challenge for the statement log_g(x) = log_h(y) with commitments a1 and a2
+    /// The transcript contains the context of the proof, i.e., who is proving it and why
+    pub(crate) fn challenge(
+        transcript: &Transcript,
+        g: &G1::Affine,
+        x: &G1::Affine,
+        h: &G2::Affine,
+        y: &G2::Affine,
+        a1: &G1::Affine,
+        a2: &G2::Affine,
+    ) -> (Hash, S)
+    {
+        let mut t = transcript.clone();
+        t.append_message(b"proof", DLEQ_DOMAIN);
+        t.append_point(b"g", g);
+        t.append_point(b"x", x);
+        t.append_point(b"h", h);
+        t.append_point(b"y", y);
+        t.append_point(b"a1", a1);
+        t.append_point(b"a2", a2);
+        t.challenge()
+    }
+
+    pub fn prove<R>(
+        transcript: &Transcript,
+        knowledge: &S,
         g: &G1,
         x: &G1,
         h: &G2,
         y: &G2,
-        my_key: &crypto_lib::Keypair,
+        mode: ProofMode,
+        my_key: &DSSSecretKey,
         rng: &mut R
     ) -> DleqProof<G1, G2, S>
     where
@@ -102,20 +206,17 @@ where
         let a1: G1 = g.mul(w.into_repr());
         let a2: G2 = h.mul(w.into_repr());
 
-        let mut buf = Vec::new();
-        buf.append(&mut to_bytes!(a1.into_affine()).unwrap()); // a1
-        buf.append(&mut to_bytes!(a2.into_affine()).unwrap()); // a2
-        buf.append(&mut to_bytes!(x.into_affine()).unwrap()); // x
-        buf.append(&mut to_bytes!(y.into_affine()).unwrap()); // y
-        let hash = ser_and_hash(&buf);
-
-        let mut rngs: StdRng = SeedableRng::from_seed(hash);
-        let c = S::rand(&mut rngs);
+        let (hash, c) = Self::challenge(&mode.bind(transcript, &my_key.public()), 
+            &g.into_affine(), 
+            &x.into_affine(), 
+            &h.into_affine(), 
+            &y.into_affine(), 
+            &a1.into_affine(), 
+            &a2.into_affine(),
+        );
         let r = w - c * knowledge;
 
-        let sig = my_key
-            .sign(&hash)
-            .expect("Failed to sign the DLEQ Proof");
+        let sig = mode.sign(&hash, my_key);
         DleqProof::<G1, G2, S> {
             a1, 
             a2, 
@@ -126,30 +227,31 @@ where
     }
 
     pub fn verify(
+        transcript: &Transcript,
         pi: &DleqProof<G1, G2, S>,
         g: &G1,
         x: &G1,
         h: &G2,
         y: &G2,
+        mode: ProofMode,
         dss_pk: &crypto_lib::PublicKey,
     ) -> Option<DbsError>
     {
         // Check if the challenge is correct
-        let mut buf = Vec::new();
-        buf.append(&mut to_bytes!(pi.a1.into_affine()).unwrap());
-        buf.append(&mut to_bytes!(pi.a2.into_affine()).unwrap());
-        buf.append(&mut to_bytes!(x.into_affine()).unwrap());
-        buf.append(&mut to_bytes!(y.into_affine()).unwrap());
-        let hash = ser_and_hash(&buf);
-        
-        let mut rngs: StdRng = SeedableRng::from_seed(hash);
-        let c = S::rand(&mut rngs);
+        let (hash, c) = Self::challenge(&mode.bind(transcript, dss_pk), 
+            &g.into_affine(), 
+            &x.into_affine(), 
+            &h.into_affine(), 
+            &y.into_affine(), 
+            &pi.a1.into_affine(), 
+            &pi.a2.into_affine(),
+        );
         if c != pi.c {
             return Some(DbsError::InvalidChallenge);
         }
 
-        // Check if the response is correct
-        if !dss_pk.verify(&hash, &pi.sig) {
+        // Check if the proof is pinned to the prover
+        if !mode.verify(&hash, &pi.sig, dss_pk) {
             return Some(DbsError::InvalidSignature);
         }
         
@@ -161,6 +263,107 @@ where
         }
         None
     }
+
+    /// Verifies several DLEQ proofs dleq(pi_i, g, x_i, h_i, y_i) by the same dealer at once
+    /// transcripts[i] is the context for the i-th proof
+    ///
+    /// OPTIMIZATION: Instead of checking a1_i = g^r_i x_i^c_i and a2_i = h_i^r_i y_i^c_i for every i, 
+    /// we pick random weights rho_i and check 
+    /// 1. prod (g^r_i x_i^c_i a1_i^-1)^rho_i = 1 with a single multi-exponentiation in G1
+    /// 2. prod (h_i^r_i y_i^c_i a2_i^-1)^rho_i = 1 with a single multi-exponentiation in G2
+    /// The signatures (if any) are also checked in a batch
+    /// 
+    /// Returns false if any of the proofs is invalid; use `verify` on the individual proofs to find the culprit
+    pub fn batch_verify<R>(
+        transcripts: &[Transcript],
+        pis: &[DleqProof<G1, G2, S>],
+        g: &G1,
+        xs: &[G1],
+        hs: &[G2],
+        ys: &[G2],
+        mode: ProofMode,
+        dss_pk: &crypto_lib::PublicKey,
+        rng: &mut R,
+    ) -> bool
+    where
+        R: Rng + ?Sized,
+    {
+        let n = pis.len();
+        if transcripts.len() != n || xs.len() != n || hs.len() != n || ys.len() != n {
+            return false;
+        }
+        if n == 0 {
+            return true;
+        }
+        
+        // OPTIMIZATION: Normalize all the points with a single inversion per vector
+        let g_affine = g.into_affine();
+        let a1s = G1::batch_normalization_into_affine(&pis.iter().map(|pi| pi.a1).collect::<Vec<_>>());
+        let a2s = G2::batch_normalization_into_affine(&pis.iter().map(|pi| pi.a2).collect::<Vec<_>>());
+        let xs = G1::batch_normalization_into_affine(xs);
+        let hs = G2::batch_normalization_into_affine(hs);
+        let ys = G2::batch_normalization_into_affine(ys);
+
+        // Check if the challenges are correct
+        let mut hashes: Vec<Hash> = Vec::with_capacity(n);
+        for i in 0..n {
+            let (hash, c) = Self::challenge(&mode.bind(&transcripts[i], dss_pk), 
+                &g_affine, 
+                &xs[i], 
+                &hs[i], 
+                &ys[i], 
+                &a1s[i], 
+                &a2s[i],
+            );
+            if c != pis[i].c {
+                return false;
+            }
+            hashes.push(hash);
+        }
+
+        match mode {
+            ProofMode::Signed => {
+                // Check all the signatures at once
+                let msgs: Vec<&[u8]> = hashes.iter().map(|h| &h[..]).collect();
+                let sigs: Vec<&[u8]> = pis.iter().map(|pi| &pi.sig[..]).collect();
+                let pks = vec![dss_pk; n];
+                if !batch_verify_sigs(&msgs, &sigs, &pks) {
+                    return false;
+                }
+            }
+            ProofMode::SignatureOfKnowledge => {
+                if pis.iter().any(|pi| !pi.sig.is_empty()) {
+                    return false;
+                }
+            }
+        }
+
+        // 128-bit weights are sufficient for soundness and halve the cost of the exponentiations
+        let rhos: Vec<S> = (0..n).map(|_| S::from(rng.gen::<u128>())).collect();
+        let sum_r = (0..n).fold(S::zero(), |acc, i| acc + rhos[i] * pis[i].r);
+
+        // sum_i (-rho_i) a1_i + (rho_i c_i) x_i + (sum_i rho_i r_i) g =? 0
+        let mut bases1 = a1s;
+        bases1.extend_from_slice(&xs);
+        bases1.push(g_affine);
+        let mut scalars1: Vec<<G1::ScalarField as PrimeField>::BigInt> = Vec::with_capacity(2*n+1);
+        scalars1.extend(rhos.iter().map(|&rho| Into::<G1::ScalarField>::into(-rho).into_repr()));
+        scalars1.extend((0..n).map(|i| Into::<G1::ScalarField>::into(rhos[i] * pis[i].c).into_repr()));
+        scalars1.push(Into::<G1::ScalarField>::into(sum_r).into_repr());
+        if !VariableBaseMSM::multi_scalar_mul(&bases1, &scalars1).is_zero() {
+            return false;
+        }
+
+        // sum_i (-rho_i) a2_i + (rho_i c_i) y_i + (rho_i r_i) h_i =? 0
+        let mut bases2 = a2s;
+        bases2.extend_from_slice(&ys);
+        bases2.extend_from_slice(&hs);
+        let mut scalars2: Vec<<G2::ScalarField as PrimeField>::BigInt> = Vec::with_capacity(3*n);
+        scalars2.extend(rhos.iter().map(|&rho| Into::<G2::ScalarField>::into(-rho).into_repr()));
+        scalars2.extend((0..n).map(|i| Into::<G2::ScalarField>::into(rhos[i] * pis[i].c).into_repr()));
+        scalars2.extend((0..n).map(|i| Into::<G2::ScalarField>::into(rhos[i] * pis[i].r).into_repr()));
+        VariableBaseMSM::multi_scalar_mul(&bases2, &scalars2).is_zero()
+    }
 }
 
 impl<G, S> SingleDleq<G, S> 
@@ -169,65 +372,132 @@ where
     S: PrimeField 
         + Into<G::ScalarField>
 {
-    pub fn prove<R>(knowledge: &S,
+    /// Computes the challenge for the statement "I know log_g(x)" with commitment a
+    /// The transcript contains the context of the proof, i.e., who is proving it and why
+    pub(crate) fn challenge(
+        transcript: &Transcript,
+        g: &G::Affine,
+        x: &G::Affine,
+        a: &G::Affine,
+    ) -> (Hash, S)
+    {
+        let mut t = transcript.clone();
+        t.append_message(b"proof", SINGLE_DLEQ_DOMAIN);
+        t.append_point(b"g", g);
+        t.append_point(b"x", x);
+        t.append_point(b"a", a);
+        t.challenge()
+    }
+
+    /// Appends the PVSS public key of the prover and the commitment of its key proof to the transcript
+    fn bind_key<K>(transcript: &Transcript, signer_pk: &K, a: &K) -> Transcript
+    where K: ProjectiveCurve,
+    {
+        let mut t = transcript.clone();
+        t.append_point(b"signer-key", &signer_pk.into_affine());
+        t.append_point(b"signer-commitment", &a.into_affine());
+        t
+    }
+
+    /// Proves knowledge of log_g(x)
+    /// In the SignatureOfKnowledge mode, this is AND-composed with a proof of knowledge of signer_sk = log_k(signer_pk) for the generator k of K, 
+    /// so that only the holder of the PVSS secret key can create a proof for the dealer
+    pub fn prove<K, R>(
+        transcript: &Transcript,
+        knowledge: &S,
         g: &G,
         x: &G,
-        my_key: &crypto_lib::Keypair,
+        mode: ProofMode,
+        my_key: &DSSSecretKey,
+        signer_sk: &S,
+        signer_pk: &K,
         rng: &mut R
-    ) -> SingleDleqProof<G, S>
+    ) -> SingleDleqProof<G, S, K>
     where
+        K: ProjectiveCurve<ScalarField = S>,
         R: Rng + ?Sized,
     {
         let w = S::rand(rng);
         let a: G = g.mul(w.into_repr());
 
-        let mut buf = Vec::new();
-        buf.append(&mut to_bytes!(a.into_affine()).unwrap()); // a
-        buf.append(&mut to_bytes!(x.into_affine()).unwrap()); // x
-        let hash = ser_and_hash(&buf);
-
-        let mut rngs: StdRng = SeedableRng::from_seed(hash);
-        let c = S::rand(&mut rngs);
+        let mut t = mode.bind(transcript, &my_key.public());
+        let key_nonce = match mode {
+            ProofMode::Signed => None,
+            ProofMode::SignatureOfKnowledge => {
+                let w_k = S::rand(rng);
+                let a_k = K::prime_subgroup_generator().mul(w_k.into_repr());
+                t = Self::bind_key(&t, signer_pk, &a_k);
+                Some((w_k, a_k))
+            }
+        };
+        let (hash, c) = Self::challenge(&t, 
+            &g.into_affine(), 
+            &x.into_affine(), 
+            &a.into_affine(),
+        );
         let r = w - c * knowledge;
+        let key_proof = key_nonce.map(|(w_k, a_k)| KeyProof {
+            a: a_k,
+            r: w_k - c * signer_sk,
+        });
 
-        let sig = my_key
-            .sign(&hash)
-            .expect("Failed to sign the DLEQ Proof");
-        SingleDleqProof::<G, S> {
+        let sig = mode.sign(&hash, my_key);
+        SingleDleqProof::<G, S, K> {
             a, 
             r, 
             c, 
             sig,
+            key_proof,
         }
     }
 
-    pub fn verify(
-        pi: &SingleDleqProof<G, S>,
+    /// Checks a proof of knowledge of log_g(x) by the node with the DSS key dss_pk and the PVSS key signer_pk
+    pub fn verify<K>(
+        transcript: &Transcript,
+        pi: &SingleDleqProof<G, S, K>,
         g: &G,
         x: &G,
+        mode: ProofMode,
         dss_pk: &crypto_lib::PublicKey,
+        signer_pk: &K,
     ) -> Option<DbsError>
+    where
+        K: ProjectiveCurve<ScalarField = S>,
     {
+        let mut t = mode.bind(transcript, dss_pk);
+        match (mode, &pi.key_proof) {
+            (ProofMode::Signed, None) => (),
+            (ProofMode::SignatureOfKnowledge, Some(key_proof)) if !signer_pk.is_zero() => {
+                t = Self::bind_key(&t, signer_pk, &key_proof.a);
+            }
+            _ => return Some(DbsError::InvalidKeyProof),
+        }
+
         // Check if the challenge is correct
-        let mut buf = Vec::new();
-        buf.append(&mut to_bytes!(pi.a.into_affine()).unwrap());
-        buf.append(&mut to_bytes!(x.into_affine()).unwrap());
-        let hash = ser_and_hash(&buf);
-        
-        let mut rngs: StdRng = SeedableRng::from_seed(hash);
-        let c = S::rand(&mut rngs);
+        let (hash, c) = Self::challenge(&t, 
+            &g.into_affine(), 
+            &x.into_affine(), 
+            &pi.a.into_affine(),
+        );
         if c != pi.c {
             return Some(DbsError::InvalidChallenge);
         }
 
-        // Check if the response is correct
-        if !dss_pk.verify(&hash, &pi.sig) {
+        // Check if the proof is pinned to the prover
+        if !mode.verify(&hash, &pi.sig, dss_pk) {
             return Some(DbsError::InvalidSignature);
         }
         
         if pi.a != g.mul(pi.r.into_repr()) + x.mul(c.into_repr()) {
             return Some(DbsError::LeftCheckFailed);
         }
+
+        // Check if the prover knows the secret key for signer_pk
+        if let Some(key_proof) = &pi.key_proof {
+            if key_proof.a != K::prime_subgroup_generator().mul(key_proof.r.into_repr()) + signer_pk.mul(c.into_repr()) {
+                return Some(DbsError::InvalidKeyProof);
+            }
+        }
         None
     }
-}
\ No newline at end of file
+}
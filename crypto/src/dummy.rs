@@ -2,7 +2,7 @@ use ark_bls12_381::Bls12_381;
 use ark_ec::{PairingEngine, AffineCurve};
 use ark_ff::PrimeField;
 use ark_std::UniformRand;
-use crate::{AggregatePVSS, Beacon, DbsContext, DecompositionProof, Decryption, DleqProof, Keypair, PVSSVec, Scalar, std_rng};
+use crate::{AggregatePVSS, Beacon, DSSSecretKey, DbsContext, DecompositionProof, Decryption, DleqProof, Keypair, PVSSVec, Scalar, std_rng};
 
 // fn dummy_pvec<E>(n:usize) -> PVSSVec 
 // where E: PairingEngine,
@@ -56,9 +56,9 @@ pub fn test_messages(n:usize) -> (PVSSVec<Bls12_381>, AggregatePVSS<Bls12_381>,
         keypairs[i].1
     }).collect();
     let ctx = DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys, keypairs[0].0);
-    let sk = crypto_lib::Keypair::generate_ed25519();
-    let pvec = ctx.generate_shares(&sk,&mut rng);
-    let pvec2 = ctx.generate_shares(&sk,&mut rng);
+    let sk = DSSSecretKey::generate_ed25519();
+    let pvec = ctx.generate_shares(1, &sk,&mut rng);
+    let pvec2 = ctx.generate_shares(1, &sk,&mut rng);
     let indices = [1, 2];
     let (agg, decomp) = ctx.aggregate(&indices, [pvec.clone(),pvec2].to_vec());
     (pvec, agg, decomp)
@@ -19,4 +19,43 @@ pub enum DbsError {
     // Decomposition verification checks
     CommitmentNotDecomposing,
     EncryptionNotDecomposing,
+
+    // Structural checks for messages from other nodes
+    /// The vector has the wrong number of entries (we got the given number)
+    WrongNumberOfCommitments(usize),
+    WrongNumberOfEncryptions(usize),
+    WrongNumberOfProofs(usize),
+    /// The indices, proofs and gs_vec of a decomposition proof have different lengths
+    DecompositionLengthMismatch,
+    /// A node index is not in 0..n
+    IndexOutOfRange(usize),
+    /// A node index appears twice
+    DuplicateIndex(usize),
+    /// We do not have the DSS public key for this node
+    UnknownNode(usize),
+    /// The i-th commitment or encryption is the identity
+    IdentityCommitment(usize),
+    IdentityEncryption(usize),
+    /// Some g^s is the identity
+    IdentityGs,
+    /// The sharings in an aggregate were dealt in different epochs
+    EpochMismatch,
+    /// The proof of knowledge of the dealer's PVSS secret key is missing or invalid
+    InvalidKeyProof,
+
+    // Setup checks
+    MissingGeneratorSeed,
+    GeneratorMismatch,
+    MissingKeyPops,
+    InvalidKeyPop(usize),
+
+    // Key rotation checks
+    /// The vector is encrypted under a key version that we do not have (anymore)
+    UnknownKeyVersion(u64),
+    /// The rotation of the node is not endorsed by its current key
+    InvalidRotation(usize),
+    /// The rotations applied together have different switch epochs
+    SwitchEpochMismatch,
+    /// My key was rotated, but I do not have the new secret key
+    MissingRotationKey,
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use rand::RngCore;
+use crate::{BeaconStream, DbsContext, DbsError, Transcript};
+
+/// The tag for deriving h1 from the seed
+pub const H1_TAG: &[u8] = b"optrand-h1";
+/// The tag for deriving h2 from the seed
+pub const H2_TAG: &[u8] = b"optrand-h2";
+
+/// The number of bytes we feed to `from_random_bytes` in every attempt
+/// This is enough for an x co-ordinate in G2 of all the supported curves
+const H2C_BYTES: usize = 256;
+
+/// Hashes the seed to a point in the prime order subgroup of G, so that nobody knows its discrete log
+///
+/// We use try-and-increment: for counter = 0, 1, ..., 
+/// 1. Expand the hash of the transcript with domain "hash-to-curve" and messages ("tag", tag), ("seed", seed), ("counter", le64(counter)) into H2C_BYTES bytes (see `BeaconStream`)
+/// 2. Interpret the bytes as an x co-ordinate and a sign for y using `AffineCurve::from_random_bytes`
+/// 3. Multiply the point by the cofactor, and return it if it is not the identity
+///
+/// This is not constant time, but the seed is public
+pub fn hash_to_curve<G>(tag: &[u8], seed: &[u8]) -> G::Projective
+where G: AffineCurve,
+{
+    let mut bytes = vec![0u8; H2C_BYTES];
+    for counter in 0u64.. {
+        let mut t = Transcript::new(b"hash-to-curve");
+        t.append_message(b"tag", tag);
+        t.append_message(b"seed", seed);
+        t.append_u64(b"counter", counter);
+        BeaconStream::new(t.hash()).fill_bytes(&mut bytes);
+        if let Some(p) = G::from_random_bytes(&bytes) {
+            let p = p.mul_by_cofactor_to_projective();
+            if !p.is_zero() {
+                return p;
+            }
+        }
+    }
+    unreachable!("Roughly half of the attempts succeed")
+}
+
+/// Derives the generator h1 from a public seed
+pub fn derive_h1_generator<E>(seed: &[u8]) -> E::G1Projective 
+where E: PairingEngine,
+{
+    hash_to_curve::<E::G1Affine>(H1_TAG, seed)
+}
+
+/// Derives the generator h2 from a public seed
+pub fn derive_h2_generator<E>(seed: &[u8]) -> E::G2Projective 
+where E: PairingEngine,
+{
+    hash_to_curve::<E::G2Affine>(H2_TAG, seed)
+}
+
+/// Checks that h1 and h2 were derived from the seed
+pub fn check_generators<E>(seed: &[u8], h1: &E::G1Projective, h2: &E::G2Projective) -> bool 
+where E: PairingEngine,
+{
+    derive_h1_generator::<E>(seed) == *h1 && 
+        derive_h2_generator::<E>(seed) == *h2
+}
+
+impl<E> DbsContext<E> 
+where E: PairingEngine,
+{
+    /// Checks that the generators h1 and h2 in this context were derived from the seed in this context
+    /// Contexts with random generators have no seed and fail this check, since someone may know the discrete logs of h1 and h2
+    pub fn check_generators(&self) -> Option<DbsError> {
+        match &self.generator_seed {
+            None => Some(DbsError::MissingGeneratorSeed),
+            Some(seed) if !check_generators::<E>(seed.as_bytes(), &self.h1p, &self.h2p) => 
+                Some(DbsError::GeneratorMismatch),
+            Some(_) => None,
+        }
+    }
+}
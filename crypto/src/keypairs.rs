@@ -1,13 +1,25 @@
 use rand::Rng;
 use ark_ec::{PairingEngine, AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, Zero};
 use ark_std::UniformRand;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
-use crate::{Scalar, PublicKey, SecretKey};
+use crate::{DbsError, Scalar, PublicKey, SecretKey, SingleDleq, Transcript, ark_serde::{
+    canonical_deserialize, 
+    canonical_serialize,
+}};
 
 
 pub struct Keypair<E: PairingEngine> (pub SecretKey<E>, 
     pub PublicKey<E>);
 
+impl<E: PairingEngine> Drop for Keypair<E> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl<E> Keypair<E> 
 where E: PairingEngine,
 {
@@ -19,4 +31,90 @@ where E: PairingEngine,
             PublicKey::<E>::prime_subgroup_generator().into().mul(secret),
         )
     }
-}
\ No newline at end of file
+
+    /// Generates a key pair for the node id along with a proof that the node knows the secret key
+    pub fn generate_keypair_with_pop<R>(id: usize, rng: &mut R) -> (Self, KeyPop<E>)
+    where R: Rng + ?Sized,
+    {
+        let kpair = Self::generate_keypair(rng);
+        let pop = kpair.prove_possession(id, rng);
+        (kpair, pop)
+    }
+
+    /// Proves that the node id knows the secret key for the public key
+    /// A Schnorr proof for log_g2(pk), bound to the node id
+    pub fn prove_possession<R>(&self, id: usize, rng: &mut R) -> KeyPop<E>
+    where R: Rng + ?Sized,
+    {
+        KeyPop::prove_in(&KeyPop::<E>::transcript(id), &self.0, &self.1, rng)
+    }
+}
+
+/// A proof of possession for a PVSS public key
+/// Without this, a node can pick its public key as a function of the other public keys (rogue key attacks)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyPop<E> 
+where E: PairingEngine,
+{
+    /// The commitment g2^w
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub a: PublicKey<E>,
+    /// The response w - c*sk
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub r: Scalar<E>,
+}
+
+impl<E> KeyPop<E> 
+where E: PairingEngine,
+{
+    /// The context for the proof of possession of the node id
+    pub fn transcript(id: usize) -> Transcript {
+        let mut t = Transcript::new(b"pvss-key-pop");
+        t.append_u64(b"id", id as u64);
+        t
+    }
+
+    /// A Schnorr proof for log_g2(pk) in the context t
+    /// With the context of a message, this is a signature on the message by sk
+    pub fn prove_in<R>(t: &Transcript, sk: &SecretKey<E>, pk: &PublicKey<E>, rng: &mut R) -> Self
+    where R: Rng + ?Sized,
+    {
+        let g2 = PublicKey::<E>::prime_subgroup_generator();
+        let w = Scalar::<E>::rand(rng);
+        let a = g2.mul(w.into_repr());
+        let c = Self::challenge(t, pk, &a);
+        KeyPop {
+            a,
+            r: w - c * sk,
+        }
+    }
+
+    /// Checks a proof created by `prove_in` with the same context
+    pub fn verify_in(&self, t: &Transcript, pk: &PublicKey<E>) -> bool {
+        if pk.is_zero() {
+            return false;
+        }
+        let c = Self::challenge(t, pk, &self.a);
+        let g2 = PublicKey::<E>::prime_subgroup_generator();
+        self.a == g2.mul(self.r.into_repr()) + pk.mul(c.into_repr())
+    }
+
+    fn challenge(t: &Transcript, pk: &PublicKey<E>, a: &PublicKey<E>) -> Scalar<E> {
+        SingleDleq::<PublicKey<E>, Scalar<E>>::challenge(
+            t,
+            &PublicKey::<E>::prime_subgroup_generator().into_affine(),
+            &pk.into_affine(),
+            &a.into_affine(),
+        ).1
+    }
+
+    /// Checks that the node id knows the secret key for pk
+    pub fn verify(&self, id: usize, pk: &PublicKey<E>) -> Option<DbsError> {
+        if !self.verify_in(&Self::transcript(id), pk) {
+            return Some(DbsError::InvalidKeyPop(id));
+        }
+        None
+    }
+}
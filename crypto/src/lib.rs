@@ -3,12 +3,26 @@ pub use ark_serde::*;
 
 pub mod hash;
 
+mod transcript;
+pub use transcript::*;
+
 mod dleq;
 pub use dleq::*;
 
+mod batch;
+pub use batch::*;
+
+mod parallel;
+
 mod dbs;
 pub use dbs::*;
 
+mod scheme;
+pub use scheme::*;
+
+mod scrape;
+pub use scrape::*;
+
 pub use rand;
 
 mod context;
@@ -17,12 +31,21 @@ pub use context::*;
 mod keypairs;
 pub use keypairs::*;
 
+mod rotation;
+pub use rotation::*;
+
+mod bls;
+pub use bls::*;
+
 mod dummy;
 pub use dummy::*;
 
 mod errors;
 pub use errors::*;
 
+mod secret;
+pub use secret::*;
+
 use ark_ec::{PairingEngine, AffineCurve};
 use ark_std::UniformRand;
 use ark_poly::univariate::DensePolynomial;
@@ -56,6 +79,27 @@ pub fn std_rng() -> StdRng {
     rand::rngs::StdRng::from_entropy()
 }
 
+/// TEST ONLY: Returns a deterministic rng for the node id and the purpose label, derived from the seed
+/// Every (seed, id, label) gives an independent stream, so that the nodes and their threads do not share randomness
+/// Anyone who knows the seed knows every secret drawn from these rngs, so never use this in deployments
+pub fn seeded_rng(seed: u64, id: usize, label: &[u8]) -> StdRng {
+    let mut t = Transcript::new(b"test-rng");
+    t.append_u64(b"seed", seed);
+    t.append_u64(b"id", id as u64);
+    t.append_message(b"label", label);
+    StdRng::from_seed(t.hash())
+}
+
+/// Returns `seeded_rng` if there is a test seed, and `std_rng` otherwise
+pub fn rng_for(test_seed: Option<u64>, id: usize, label: &[u8]) -> StdRng {
+    match test_seed {
+        Some(seed) => seeded_rng(seed, id, label),
+        None => std_rng(),
+    }
+}
+
+/// WARNING: Whoever picks the random exponent knows the discrete log of h2
+/// Use `derive_h2_generator` for deployments
 pub fn rand_h2_generator<R, E>(rng: &mut R) 
     -> E::G2Projective
     where R:Rng+?Sized, 
@@ -65,6 +109,8 @@ pub fn rand_h2_generator<R, E>(rng: &mut R)
         .mul(E::Fr::rand(rng))
 }
 
+/// WARNING: Whoever picks the random exponent knows the discrete log of h1
+/// Use `derive_h1_generator` for deployments
 pub fn rand_h1_generator<R, E>(rng: &mut R) 
     -> E::G1Projective
     where R:Rng+?Sized, 
@@ -75,16 +121,25 @@ pub fn rand_h1_generator<R, E>(rng: &mut R)
 }
 
 mod precomputes;
+pub use precomputes::{PrecomputeConfig, lagrange_coefficients, lagrange_coefficients_for};
 mod pvss;
 pub use pvss::*;
 
+mod beacon;
+pub use beacon::*;
+
+mod generators;
+pub use generators::*;
+
+pub mod randomness;
+
 mod test;
 
 #[macro_use]
 extern crate derive_builder;
 
 pub type DSSPublicKey = crypto_lib::PublicKey;
-pub type DSSSecretKey = crypto_lib::Keypair;
+pub type DSSSecretKey = SigningKey;
 
 #[macro_use]
 pub mod bench;
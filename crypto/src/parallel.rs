@@ -0,0 +1,46 @@
+//! The per-node loops of the PVSS
+//! With the `parallel` feature they run on the rayon thread pool (see `RAYON_NUM_THREADS` or `rayon::ThreadPool::install`), otherwise on the calling thread
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Returns [f(0), f(1), ..., f(n-1)]
+pub(crate) fn par_map<T, F>(n: usize, f: F) -> Vec<T>
+where T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        (0..n).into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..n).map(f).collect()
+    }
+}
+
+/// Returns the first i in 0..n for which f(i) is true, if any
+pub(crate) fn par_find_first<F>(n: usize, f: F) -> Option<usize>
+where F: Fn(usize) -> bool + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        (0..n).into_par_iter().find_first(|&i| f(i))
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..n).find(|&i| f(i))
+    }
+}
+
+/// The number of threads that the loops are split among
+pub(crate) fn num_threads() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::current_num_threads()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
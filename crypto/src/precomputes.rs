@@ -1,38 +1,71 @@
 use ark_ec::{PairingEngine, ProjectiveCurve, msm::FixedBaseMSM};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
 use fnv::FnvHashMap as HashMap;
-use crate::{Keypair, PublicKey, Scalar};
+use crate::{Keypair, PublicKey, Scalar, SecretValue, Transcript, std_rng};
+use crate::hash::{Hash, do_hash};
 use ark_ec::AffineCurve;
-use ark_ff::{Field, One, PrimeField};
+use ark_ff::{Field, One, PrimeField, batch_inversion};
 use rand::{Rng};
 use ark_poly::{UVPolynomial, Polynomial as PolyT};
-use crate::Polynomial;
+use ark_poly::univariate::DensePolynomial;
 use ark_std::UniformRand;
+use std::io::{Error as IOError, ErrorKind, Read, Write};
+use std::path::Path;
 
 type Table1<E> = Vec<Vec<<E as PairingEngine>::G1Affine>>;
 type Table2<E> = Vec<Vec<<E as PairingEngine>::G2Affine>>;
 
+/// The number of random entries of every table that `load` checks against the base of the table
+const CACHE_SAMPLES: usize = 16;
+
+/// The number of index sets that a `LagrangeCache` keeps
+const LAGRANGE_CACHE_SIZE: usize = 64;
+
+/// Options for building the precomputation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PrecomputeConfig {
+    /// A file to load the tables from
+    /// If the file is missing or was built for different parameters, we build all the tables and write them here
+    #[serde(default)]
+    pub cache_path: Option<String>,
+    /// The maximum number of bytes to spend on the tables for the public keys
+    /// Encryptions for the public keys beyond this budget use variable base exponentiations
+    #[serde(default)]
+    pub memory_budget: Option<usize>,
+}
+
 #[derive(Debug, Clone, Builder)]
 pub struct Precomputation<E: PairingEngine> {
     pub(crate) g1p: E::G1Projective,
     pub(crate) g2p: E::G2Projective,
     pub(crate) g1_prepared: E::G1Prepared,
     pub(crate) g2_prepared: E::G2Prepared,
-    pub(crate) h1_prepared: E::G1Prepared,
+    pub(crate) _h1_prepared: E::G1Prepared,
     pub(crate) _h2_prepared: E::G2Prepared,
-    pub(crate) my_key_inv: Scalar<E>,
+    pub(crate) my_key_inv: SecretValue<Scalar<E>>,
     pub(crate) scalar_bits: usize,
     pub(crate) window_size: usize,
-    pub(crate) g1_table: Table1<E>,
-    pub(crate) g2_table: Table2<E>,
-    /// OPTIMIZATIONS: Pre-computations for (n,t) degree check
-    pub(crate) lagrange_inverses: HashMap<(usize, usize),Scalar<E>>,
+    /// The hash of (n, t, h1, h2, public keys) identifying the tables in a cache file
+    pub(crate) cache_key: Hash,
+    /// OPTIMIZATIONS: The tables are built on first use
+    /// They are shared by the clones, e.g., with the contexts for the next key versions
+    pub(crate) g1_table: Arc<OnceCell<Table1<E>>>,
+    pub(crate) g2_table: Arc<OnceCell<Table2<E>>>,
     /// OPTIMIZATIONS: Pre-compute sk^-1 for decryptions
     pub(crate) codewords: Vec<Scalar<E>>,
     /// OPTIMIZATIONS: Pre-compute tables for public keys
-    pub(crate) pk_tables: Vec<Table2<E>>,
+    pub(crate) pub_keys: Vec<PublicKey<E>>,
+    pub(crate) pk_tables: Vec<Arc<OnceCell<Table2<E>>>>,
+    /// Only the first pk_table_limit public keys get a table (see PrecomputeConfig::memory_budget)
+    pub(crate) pk_table_limit: usize,
     pub(crate) pub_keys_p: Vec<E::G2Prepared>,
     /// OPTIMIZATIONS: Pre-compute lagranges for the gs check
     pub(crate) fixed_lagranges: Vec<Scalar<E>>,
+    /// OPTIMIZATIONS: The lagranges for the other sets of shares, shared by the clones
+    pub(crate) lagranges: Arc<LagrangeCache<Scalar<E>>>,
 }
 
 impl<E: PairingEngine> Precomputation<E> {
@@ -44,100 +77,330 @@ impl<E: PairingEngine> Precomputation<E> {
         pub_keys: Vec<PublicKey<E>>,
         rng: &mut R) -> Self 
     where R: Rng + ?Sized,
+    {
+        Self::new_with_budget(n, t, h1, h2, my_key, pub_keys, None, rng)
+    }
+
+    /// Sets up the precomputation
+    /// The expensive tables are built on first use, or loaded from a cache file with `load`
+    pub fn new_with_budget<R>(n: usize, 
+        t: usize, 
+        h1: E::G1Projective, 
+        h2: E::G2Projective, 
+        my_key: Scalar<E>,
+        pub_keys: Vec<PublicKey<E>>,
+        memory_budget: Option<usize>,
+        rng: &mut R) -> Self 
+    where R: Rng + ?Sized,
     {
         let mut opt = PrecomputationBuilder::default();
         let scalar_bits = E::Fr::size_in_bits();
         let window_size = FixedBaseMSM::get_mul_window_size(t + 1);
         let g1p = E::G1Affine::prime_subgroup_generator().into_projective();
         let g2p = E::G2Affine::prime_subgroup_generator().into_projective();
-        let pk_tables = (0..pub_keys.len()).map(|i| {
-            FixedBaseMSM::get_window_table(scalar_bits, window_size, pub_keys[i])
-        }).collect();
+        let pk_table_limit = match memory_budget {
+            None => pub_keys.len(),
+            Some(budget) => std::cmp::min(pub_keys.len(), 
+                budget / table_size::<E::G2Affine>(scalar_bits, window_size)),
+        };
         let pub_keys_p : Vec<_>= (0..pub_keys.len()).map(|i| {
             pub_keys[i].into_affine().into()
         }).collect();
-        let lagrange_inverses = compute_inv_map::<E>(n);
 
-        let indices:Vec<_> = (0..t+1).map(|i| (i, Scalar::<E>::from(i as u64 + 1))).collect();
-        let lagranges: Vec<_> = indices
-        .iter()
-        .map(|&(i, _scalar_i)| {
-                indices
-                    .iter()
-                    .map(|&(j, scalar_j)| {
-                        if j == i {
-                            Scalar::<E>::one()
-                        } else {
-                            scalar_j * lagrange_inverses[&(j,i)]
-                        }
-                    })
-                    .fold(Scalar::<E>::one(), |lambda, x| lambda * x)
-        }).collect();
+        // The lagrange co-efficients at 0 for the points 1, 2, ..., t+1
+        let lagranges = lagrange_coefficients_for::<E>(&(0..t+1).collect::<Vec<_>>());
 
         opt
             .g1p(g1p)
             .g2p(g2p)
-            .my_key_inv(my_key.inverse()
-                .expect("Failed to compute the inverse of my secret key"))
-            .lagrange_inverses(lagrange_inverses)
-            .codewords(random_codewords::<R, E>(n, t, rng))
+            .my_key_inv(SecretValue::new(my_key.inverse()
+                .expect("Failed to compute the inverse of my secret key")))
+            .codewords(random_codewords::<R, Scalar<E>>(n, t, rng))
             .scalar_bits(scalar_bits)
             .window_size(window_size)
-            .g1_table(FixedBaseMSM::get_window_table(scalar_bits, window_size, g1p))
-            .g2_table(FixedBaseMSM::get_window_table(scalar_bits, window_size, g2p))
-            .pk_tables(pk_tables)
+            .cache_key(cache_key::<E>(n, t, &h1, &h2, &pub_keys))
+            .g1_table(Arc::new(OnceCell::new()))
+            .g2_table(Arc::new(OnceCell::new()))
+            .pk_tables((0..pub_keys.len()).map(|_| Arc::new(OnceCell::new())).collect())
+            .pk_table_limit(pk_table_limit)
+            .pub_keys(pub_keys)
             .pub_keys_p(pub_keys_p)
             .g1_prepared(g1p.into().into())
             .g2_prepared(g2p.into().into())
             ._h2_prepared(h2.into().into())
-            .h1_prepared(h1.into().into())
+            ._h1_prepared(h1.into().into())
             .fixed_lagranges(lagranges)
+            .lagranges(Arc::new(LagrangeCache::new()))
                 ;
         opt.build().expect("Failed to build the precomputation module")
     }
 
+    /// Returns the precomputation for the keys after a rotation
+    /// The generator tables, the codewords and the tables for the public keys that did not change are shared with this one
+    pub(crate) fn rotate(&self,
+        n: usize,
+        t: usize,
+        h1: &E::G1Projective,
+        h2: &E::G2Projective,
+        my_key: &Scalar<E>,
+        pub_keys: Vec<PublicKey<E>>,
+    ) -> Self
+    {
+        let mut opt = self.clone();
+        for i in 0..pub_keys.len() {
+            if pub_keys[i] == self.pub_keys[i] {
+                continue;
+            }
+            opt.pk_tables[i] = Arc::new(OnceCell::new());
+            opt.pub_keys_p[i] = pub_keys[i].into_affine().into();
+        }
+        opt.my_key_inv = SecretValue::new(my_key.inverse()
+            .expect("Failed to compute the inverse of my secret key"));
+        opt.cache_key = cache_key::<E>(n, t, h1, h2, &pub_keys);
+        opt.pub_keys = pub_keys;
+        opt
+    }
+
+    /// Returns the fixed base table for g1
+    pub(crate) fn g1_table(&self) -> &Table1<E> {
+        self.g1_table.get_or_init(|| {
+            FixedBaseMSM::get_window_table(self.scalar_bits, self.window_size, self.g1p)
+        })
+    }
+
+    /// Returns the fixed base table for g2
+    pub(crate) fn g2_table(&self) -> &Table2<E> {
+        self.g2_table.get_or_init(|| {
+            FixedBaseMSM::get_window_table(self.scalar_bits, self.window_size, self.g2p)
+        })
+    }
+
+    /// Returns the fixed base table for the public key of node id, if it is within the memory budget
+    fn pk_table(&self, id: usize) -> Option<&Table2<E>> {
+        if id >= self.pk_table_limit {
+            return None;
+        }
+        Some(self.pk_tables[id].get_or_init(|| {
+            FixedBaseMSM::get_window_table(self.scalar_bits, self.window_size, self.pub_keys[id])
+        }))
+    }
+
     pub fn encyrpt(&self, id: usize, val: E::Fr) -> PublicKey<E> {
-        let res = FixedBaseMSM::multi_scalar_mul(self.scalar_bits, 
-            self.window_size, 
-            &self.pk_tables[id],
-            &[val] 
-        );
-        res[0]
+        match self.pk_table(id) {
+            Some(table) => {
+                let res = FixedBaseMSM::multi_scalar_mul(self.scalar_bits, 
+                    self.window_size, 
+                    table,
+                    &[val] 
+                );
+                res[0]
+            }
+            None => self.pub_keys[id].mul(val.into_repr()),
+        }
+    }
+
+    /// Builds all the tables (within the memory budget) and writes them to the file
+    ///
+    /// The file contains the cache key, the hash of the payload, and the payload
+    /// The payload is the uncompressed g1 table, g2 table, the number of public key tables and the public key tables
+    pub fn save<P>(&self, path: P) -> std::io::Result<()>
+    where P: AsRef<Path>,
+    {
+        let mut payload = Vec::new();
+        self.g1_table().serialize_uncompressed(&mut payload).map_err(ser_err)?;
+        self.g2_table().serialize_uncompressed(&mut payload).map_err(ser_err)?;
+        (self.pk_table_limit as u64).serialize(&mut payload).map_err(ser_err)?;
+        for id in 0..self.pk_table_limit {
+            self.pk_table(id)
+                .expect("Tables within the limit always exist")
+                .serialize_uncompressed(&mut payload).map_err(ser_err)?;
+        }
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(&self.cache_key)?;
+        file.write_all(&do_hash(&payload))?;
+        file.write_all(&payload)?;
+        file.flush()
+    }
+
+    /// Loads the tables from a file written by `save`
+    /// Returns false if the file was built for different parameters
+    ///
+    /// The hash covers the whole payload, so any corruption of an entry is caught, and the points are checked to be in the prime order subgroups
+    /// Anyone who can write the file can also compute the cache key and the hash, so the file must be protected like the config
+    /// As a sanity check, a random sample of the entries of every table is also checked against its base (see `check_table`)
+    /// On any mismatch we return an error, and the caller rebuilds the tables
+    pub fn load<P>(&mut self, path: P) -> std::io::Result<bool>
+    where P: AsRef<Path>,
+    {
+        let mut bytes = Vec::new();
+        std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+        if bytes.len() < 64 || bytes[0..32] != self.cache_key {
+            return Ok(false);
+        }
+        let payload = &bytes[64..];
+        if bytes[32..64] != do_hash(payload) {
+            return Err(IOError::new(ErrorKind::InvalidData, "The precomputation cache is corrupted"));
+        }
+        let mut reader = payload;
+        let g1_table = Table1::<E>::deserialize_uncompressed(&mut reader).map_err(ser_err)?;
+        let g2_table = Table2::<E>::deserialize_uncompressed(&mut reader).map_err(ser_err)?;
+        let num_tables = u64::deserialize(&mut reader).map_err(ser_err)? as usize;
+        if num_tables > self.pk_tables.len() {
+            return Err(IOError::new(ErrorKind::InvalidData, "The precomputation cache has too many tables"));
+        }
+        let mut pk_tables = Vec::with_capacity(num_tables);
+        for _ in 0..num_tables {
+            pk_tables.push(Table2::<E>::deserialize_uncompressed(&mut reader).map_err(ser_err)?);
+        }
+        if !reader.is_empty() {
+            return Err(IOError::new(ErrorKind::InvalidData, "The precomputation cache has trailing bytes"));
+        }
+
+        let mut rng = std_rng();
+        let valid = self.check_table(&g1_table, self.g1p, &mut rng) &&
+            self.check_table(&g2_table, self.g2p, &mut rng) &&
+            pk_tables.iter().enumerate().all(|(id, table)| self.check_table(table, self.pub_keys[id], &mut rng));
+        if !valid {
+            return Err(IOError::new(ErrorKind::InvalidData, "The precomputation cache does not match the generators"));
+        }
+
+        self.g1_table = Arc::new(OnceCell::from(g1_table));
+        self.g2_table = Arc::new(OnceCell::from(g2_table));
+        for (id, table) in pk_tables.into_iter().enumerate().take(self.pk_table_limit) {
+            self.pk_tables[id] = Arc::new(OnceCell::from(table));
+        }
+        Ok(true)
+    }
+
+    /// Checks that the table has the shape of a fixed base table for base, and that CACHE_SAMPLES random entries are right
+    /// Entry j of window w is (j * 2^(w*window_size)) * base (see `FixedBaseMSM::get_window_table`)
+    fn check_table<G, R>(&self, table: &[Vec<G::Affine>], base: G, rng: &mut R) -> bool
+    where G: ProjectiveCurve<ScalarField = Scalar<E>>,
+          R: Rng + ?Sized,
+    {
+        let in_window = 1 << self.window_size;
+        let outerc = (self.scalar_bits + self.window_size - 1) / self.window_size;
+        if table.len() != outerc || table.iter().any(|row| row.len() != in_window) {
+            return false;
+        }
+        // The last window only covers the remaining bits, and the rest of it is zero
+        let last_in_window = 1 << (self.scalar_bits - (outerc - 1) * self.window_size);
+        let shift = Scalar::<E>::from(in_window as u64);
+        (0..CACHE_SAMPLES).all(|_| {
+            let w = rng.gen_range(0..outerc);
+            let len = if w == outerc - 1 { last_in_window } else { in_window };
+            let j = rng.gen_range(0..len);
+            let k = Scalar::<E>::from(j as u64) * shift.pow([w as u64]);
+            table[w][j] == base.mul(k.into_repr()).into_affine()
+        })
     }
 }
 
-/// Compute a map containing the inverses
-/// TODO(Optimize)
-fn compute_inv_map<E:PairingEngine>(n:usize) -> HashMap<(usize, usize), Scalar<E>> 
+/// The key for the tables for (n, t, h1, h2, public keys)
+fn cache_key<E: PairingEngine>(n: usize, 
+    t: usize, 
+    h1: &E::G1Projective, 
+    h2: &E::G2Projective, 
+    pub_keys: &[PublicKey<E>]) -> Hash 
 {
-    // let n_int = n as i128;
-    let mut inv_map = HashMap::default();
-    for i in 0..n {
-        for j in 0..n {
-            if i == j {
-                continue;
-            }
-            let scalar_i = Scalar::<E>::from((i+1) as u64);
-            let scalar_j = Scalar::<E>::from((j+1) as u64);
-            let inv = (scalar_i - scalar_j).inverse().unwrap();
-            inv_map.insert((i,j), inv);
+    let mut tr = Transcript::new(b"precomputation-cache");
+    tr.append_u64(b"n", n as u64);
+    tr.append_u64(b"t", t as u64);
+    tr.append_point(b"h1", &h1.into_affine());
+    tr.append_point(b"h2", &h2.into_affine());
+    for pk in pub_keys {
+        tr.append_point(b"pk", &pk.into_affine());
+    }
+    tr.hash()
+}
+
+/// The number of bytes in a fixed base table
+fn table_size<G: AffineCurve>(scalar_bits: usize, window_size: usize) -> usize {
+    let num_windows = (scalar_bits + window_size - 1) / window_size;
+    num_windows * (1 << window_size) * std::mem::size_of::<G>()
+}
+
+fn ser_err(e: SerializationError) -> IOError {
+    IOError::new(ErrorKind::InvalidData, e)
+}
+
+/// Returns the lagrange co-efficients at 0 for the points x_1, x_2, ..., x_k, i.e., 
+/// lambda_i = prod_{j != i} x_j/(x_j - x_i), so that p(0) = sum_i lambda_i p(x_i) for every polynomial p of degree < k
+///
+/// OPTIMIZATION: With N = prod_j x_j and d_i = x_i prod_{j != i} (x_j - x_i), lambda_i = N/d_i
+/// This needs O(k^2) multiplications, a single batch inversion for all the d_i, and O(k) memory
+pub fn lagrange_coefficients<F: Field>(points: &[F]) -> Vec<F> {
+    let numerator = points.iter().fold(F::one(), |acc, x| acc * x);
+    let mut denominators: Vec<_> = points.iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            points.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(*x_i, |acc, (_, x_j)| acc * (*x_j - x_i))
+        })
+        .collect();
+    batch_inversion(&mut denominators);
+    denominators.iter().map(|d| numerator * d).collect()
+}
+
+/// The lagrange co-efficients at 0 for the sets of shares seen so far (see `lagrange_coefficients_for`)
+///
+/// OPTIMIZATION: The beacons are often reconstructed from the shares of the same nodes (e.g., the fastest t+1), 
+/// so the O(k^2) computation is done once per set, and the other reconstructions only need a lookup
+/// The cache is cleared when it has LAGRANGE_CACHE_SIZE sets, so it uses O(k) memory per set
+#[derive(Debug)]
+pub struct LagrangeCache<F> {
+    sets: Mutex<HashMap<Vec<usize>, Arc<Vec<F>>>>,
+}
+
+impl<F: Field> LagrangeCache<F> {
+    pub(crate) fn new() -> Self {
+        Self {
+            sets: Mutex::new(HashMap::default()),
         }
     }
-    inv_map
+
+    /// Returns the lagrange co-efficients at 0 for the shares of the nodes in indices, in the same order
+    /// Node i holds the evaluation at i+1
+    pub(crate) fn get(&self, indices: &[usize]) -> Arc<Vec<F>> {
+        if let Some(lagranges) = self.sets.lock().unwrap().get(indices) {
+            return lagranges.clone();
+        }
+        let points: Vec<_> = indices.iter()
+            .map(|&i| F::from(i as u64 + 1))
+            .collect();
+        let lagranges = Arc::new(lagrange_coefficients(&points));
+        let mut sets = self.sets.lock().unwrap();
+        if sets.len() >= LAGRANGE_CACHE_SIZE {
+            sets.clear();
+        }
+        sets.insert(indices.to_vec(), lagranges.clone());
+        lagranges
+    }
+}
+
+/// Returns the lagrange co-efficients at 0 for the shares of the nodes in indices
+/// Node i holds the evaluation at i+1
+pub fn lagrange_coefficients_for<E: PairingEngine>(indices: &[usize]) -> Vec<Scalar<E>> {
+    let points: Vec<_> = indices.iter()
+        .map(|&i| Scalar::<E>::from(i as u64 + 1))
+        .collect();
+    lagrange_coefficients(&points)
 }
 
 /// Generate random codewords for the pairing check
-fn random_codewords<R, E:PairingEngine>(n:usize, t:usize, rng:&mut R) -> Vec<Scalar<E>>
+pub(crate) fn random_codewords<R, F:PrimeField>(n:usize, t:usize, rng:&mut R) -> Vec<F>
 where R: Rng + ?Sized,
 {
     let vec: Vec<_> = (0..n - t - 1)
-        .map(|_| Scalar::<E>::rand(rng))
+        .map(|_| F::rand(rng))
         .collect();
     
     let polynomial = 
-        Polynomial::<E>::from_coefficients_vec(vec);
+        DensePolynomial::<F>::from_coefficients_vec(vec);
     let indices: Vec<_> = (0..n)
-        .map(|i| (i, Scalar::<E>::from(i as u64 + 1)))
+        .map(|i| (i, F::from(i as u64 + 1)))
         .collect();
 
     let codewords: Vec<_> = indices
@@ -147,12 +410,12 @@ where R: Rng + ?Sized,
             .iter()
             .map(|&(j, scalar_j)| {
                 if j == i {
-                    Scalar::<E>::one()
+                    F::one()
                 } else {
                     (scalar_i - scalar_j).inverse().unwrap()
                 }
             })
-            .fold(Scalar::<E>::one(), |v, x| v * x)
+            .fold(F::one(), |v, x| v * x)
         * polynomial.evaluate(&scalar_i)
     })
     .collect();
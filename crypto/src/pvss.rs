@@ -25,12 +25,20 @@ where E: PairingEngine,
     #[serde(deserialize_with = "canonical_deserialize")]
     pub gs: <Commitment<E> as AffineCurve>::Projective,
 
-    #[serde(bound(serialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>>: Serialize"))]
-    #[serde(bound(deserialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>>: Deserialize<'de>"))]
+    #[serde(bound(serialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>, PublicKey<E>>: Serialize"))]
+    #[serde(bound(deserialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>, PublicKey<E>>: Deserialize<'de>"))]
     pub sig_of_knowledge: SingleDleqProof<
         <Commitment<E> as AffineCurve>::Projective, 
-        Scalar<E>
-    >
+        Scalar<E>,
+        PublicKey<E>,
+    >,
+
+    /// The version of the PVSS public keys the shares are encrypted under (see `DbsContext::apply_rotations`)
+    #[serde(default)]
+    pub key_version: u64,
+    /// The epoch the sharing was dealt in, which the proofs are bound to
+    #[serde(default)]
+    pub epoch: u64,
 }
 
 
@@ -47,6 +55,13 @@ where E: PairingEngine,
     #[serde(serialize_with = "canonical_serialize")]
     #[serde(deserialize_with = "canonical_deserialize")]
     pub comms: Vec<Commitment<E>>,
+
+    /// The version of the PVSS public keys the combined shares are encrypted under
+    #[serde(default)]
+    pub key_version: u64,
+    /// The epoch the combined sharings were dealt in
+    #[serde(default)]
+    pub epoch: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -56,9 +71,9 @@ where E: PairingEngine,
     /// indices of the nodes whose shares we have combined
     pub indices: Vec<usize>,
     /// A vector of dleq proofs for all constituent vi and ci for [n]
-    #[serde(bound(serialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>>: Serialize"))]
-    #[serde(bound(deserialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>>: Deserialize<'de>"))]
-    pub dleq_proof: Vec<SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>>>,
+    #[serde(bound(serialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>, PublicKey<E>>: Serialize"))]
+    #[serde(bound(deserialize = "SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>, PublicKey<E>>: Deserialize<'de>"))]
+    pub dleq_proof: Vec<SingleDleqProof<<Commitment<E> as AffineCurve>::Projective, Scalar<E>, PublicKey<E>>>,
     #[serde(serialize_with = "canonical_serialize")]
     #[serde(deserialize_with = "canonical_deserialize")]
     pub gs_vec: Vec<<Commitment<E> as AffineCurve>::Projective>,
@@ -0,0 +1,125 @@
+//! Randomness for applications derived from the beacon of an epoch
+//!
+//! Every application picks its own label, so that two applications using the same beacon get independent randomness
+//! All the results only depend on the beacon, the epoch, and the label, and are the same on every platform
+
+use ark_ec::PairingEngine;
+use rand::RngCore;
+use crate::hash::Hash;
+use crate::{Beacon, BeaconStream, Transcript};
+
+/// The domain for deriving application randomness from a beacon
+/// Bump the version whenever any of the algorithms below change
+pub const RANDOMNESS_DOMAIN: &[u8] = b"randomness-v1";
+
+/// Returns the seed for the application label in the epoch
+/// The seed is the hash of the transcript with domain RANDOMNESS_DOMAIN and the messages ("beacon", beacon seed), ("epoch", le64(epoch)), ("label", label)
+pub fn derive_seed(beacon_seed: &Hash, epoch: u64, label: &[u8]) -> Hash {
+    let mut t = Transcript::new(RANDOMNESS_DOMAIN);
+    t.append_message(b"beacon", beacon_seed);
+    t.append_u64(b"epoch", epoch);
+    t.append_message(b"label", label);
+    t.hash()
+}
+
+/// A deterministic source of randomness for an application
+///
+/// All the integers are drawn as little-endian u64s from the output stream of the seed (see `Beacon`)
+#[derive(Debug, Clone)]
+pub struct Randomness {
+    stream: BeaconStream,
+}
+
+impl Randomness {
+    /// Randomness for the application label from the beacon of the epoch
+    pub fn new<E>(beacon: &Beacon<E>, epoch: u64, label: &[u8]) -> Self 
+    where E: PairingEngine,
+    {
+        Self::from_seed(derive_seed(&beacon.seed(), epoch, label))
+    }
+
+    /// Randomness from a seed derived with `derive_seed`
+    pub fn from_seed(seed: Hash) -> Self {
+        Self {
+            stream: BeaconStream::new(seed),
+        }
+    }
+
+    /// Returns the next 8 bytes of the stream as a little-endian integer
+    pub fn next_u64(&mut self) -> u64 {
+        self.stream.next_u64()
+    }
+
+    /// Fills dest with the next bytes of the stream
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.stream.fill_bytes(dest)
+    }
+
+    /// Returns a uniformly random integer in [0, n)
+    ///
+    /// We use rejection sampling to avoid the modulo bias: 
+    /// The draws x < 2^64 mod n are discarded, so that the remaining draws cover every residue equally often
+    pub fn uniform(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "Cannot sample from an empty range");
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return x % n;
+            }
+        }
+    }
+
+    /// Returns a uniformly random integer in [lo, hi)
+    pub fn range(&mut self, lo: u64, hi: u64) -> u64 {
+        assert!(lo < hi, "Cannot sample from an empty range [{}, {})", lo, hi);
+        lo + self.uniform(hi - lo)
+    }
+
+    /// Shuffles the items using the Fisher-Yates shuffle
+    /// For i = len-1 down to 1, swap items[i] with items[uniform(i+1)]
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.uniform(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// Returns a uniformly random permutation of 0..n
+    pub fn permutation(&mut self, n: usize) -> Vec<usize> {
+        let mut items: Vec<_> = (0..n).collect();
+        self.shuffle(&mut items);
+        items
+    }
+
+    /// Samples a committee of k distinct members from 0..n
+    /// The members are returned in the order they were sampled
+    ///
+    /// Uses a partial Fisher-Yates shuffle: For i = 0 to k-1, swap items[i] with items[i + uniform(n-i)]
+    pub fn committee(&mut self, n: usize, k: usize) -> Vec<usize> {
+        assert!(k <= n, "Cannot sample {} members from {}", k, n);
+        let mut items: Vec<_> = (0..n).collect();
+        for i in 0..k {
+            let j = i + self.uniform((n - i) as u64) as usize;
+            items.swap(i, j);
+        }
+        items.truncate(k);
+        items
+    }
+
+    /// Returns i with probability weights[i]/sum(weights)
+    pub fn weighted(&mut self, weights: &[u64]) -> usize {
+        let total = weights.iter()
+            .try_fold(0u64, |acc, &w| acc.checked_add(w))
+            .expect("The sum of the weights must fit in a u64");
+        assert!(total > 0, "The sum of the weights must be positive");
+        let mut x = self.uniform(total);
+        for (i, &w) in weights.iter().enumerate() {
+            if x < w {
+                return i;
+            }
+            x -= w;
+        }
+        unreachable!("x is always smaller than the sum of the weights")
+    }
+}
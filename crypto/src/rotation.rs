@@ -0,0 +1,216 @@
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::PrimeField;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{DbsContext, DbsError, KeyPop, Keypair, PublicKey, Scalar, SecretValue, Transcript, ark_serde::{
+    canonical_deserialize,
+    canonical_serialize,
+}, deserialize_canonical_secret, serialize_canonical_secret};
+
+/// An announcement that node `id` replaces its PVSS key pair from the epoch `switch_epoch` on
+///
+/// The new key comes with a proof of possession, and the announcement is endorsed by the current key of the node,
+/// so only the holder of the current key can rotate it
+/// All the nodes must agree on the rotations and their switch epochs (e.g., by including them in the chain) before applying them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyRotation<E>
+where E: PairingEngine,
+{
+    pub id: usize,
+    pub switch_epoch: u64,
+    /// The new PVSS public key
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub new_pk: PublicKey<E>,
+    /// The proof of possession for the new key
+    #[serde(bound(serialize = "KeyPop<E>: Serialize"))]
+    #[serde(bound(deserialize = "KeyPop<E>: Deserialize<'de>"))]
+    pub pop: KeyPop<E>,
+    /// The signature on (id, switch_epoch, new_pk) by the current key
+    #[serde(bound(serialize = "KeyPop<E>: Serialize"))]
+    #[serde(bound(deserialize = "KeyPop<E>: Deserialize<'de>"))]
+    pub endorsement: KeyPop<E>,
+}
+
+/// The new secret key of a rotation that I announced, waiting for its switch epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingKey<E>
+where E: PairingEngine,
+{
+    pub(crate) switch_epoch: u64,
+    #[serde(serialize_with = "serialize_canonical_secret")]
+    #[serde(deserialize_with = "deserialize_canonical_secret")]
+    pub(crate) key: SecretValue<Scalar<E>>,
+}
+
+impl<E> KeyRotation<E>
+where E: PairingEngine,
+{
+    /// The context for the endorsement of the new key by the current key of node id
+    pub fn transcript(id: usize, switch_epoch: u64, new_pk: &PublicKey<E>) -> Transcript {
+        let mut t = Transcript::new(b"pvss-key-rotation");
+        t.append_u64(b"id", id as u64);
+        t.append_u64(b"switch-epoch", switch_epoch);
+        t.append_point(b"new-pk", &new_pk.into_affine());
+        t
+    }
+}
+
+impl<E> DbsContext<E>
+where E: PairingEngine,
+{
+    /// Returns the version of the current keys, i.e., the number of rotations applied so far
+    pub fn key_version(&self) -> u64 {
+        self.key_version
+    }
+
+    /// Returns the context for the keys of the given version, if we still have them
+    /// Use it to decrypt and verify the shares of an aggregate made before a rotation
+    pub fn for_key_version(&self, version: u64) -> Option<&Self> {
+        if version == self.key_version {
+            return Some(self);
+        }
+        self.retired
+            .iter()
+            .map(|(_, ctx)| ctx.as_ref())
+            .find(|ctx| ctx.key_version == version)
+    }
+
+    /// Creates a new key pair for me, to be used from the switch epoch on
+    /// The new secret key is kept until the rotation is applied with `apply_rotations`
+    /// Announcing again replaces the pending key, so the earlier announcement must not be applied
+    pub fn announce_rotation<R>(&mut self, switch_epoch: u64, rng: &mut R) -> KeyRotation<E>
+    where R: Rng + ?Sized,
+    {
+        let (kpair, pop) = Keypair::<E>::generate_keypair_with_pop(self.origin, rng);
+        let endorsement = KeyPop::prove_in(
+            &KeyRotation::<E>::transcript(self.origin, switch_epoch, &kpair.1),
+            self.my_key.expose_secret(),
+            &self.public_keys[self.origin],
+            rng,
+        );
+        self.next_key = Some(PendingKey {
+            switch_epoch,
+            key: SecretValue::new(kpair.0),
+        });
+        KeyRotation {
+            id: self.origin,
+            switch_epoch,
+            new_pk: kpair.1,
+            pop,
+            endorsement,
+        }
+    }
+
+    /// Returns the switch epoch of the rotation that I announced and that is not applied yet, if any
+    pub fn pending_rotation(&self) -> Option<u64> {
+        self.next_key.as_ref().map(|next| next.switch_epoch)
+    }
+
+    /// The earliest switch epoch for a rotation proposed in the epoch
+    /// A block is committed by every node at most t+1 epochs after it was proposed, so the rotations in it are known to everyone before they switch
+    pub fn min_switch_epoch(&self, epoch: u64) -> u64 {
+        epoch + self.t as u64 + 2
+    }
+
+    /// Checks that the rotation is endorsed by the current key of the node, and that the node knows the new secret key
+    pub fn verify_rotation(&self, rot: &KeyRotation<E>) -> Option<DbsError> {
+        if rot.id >= self.n {
+            return Some(DbsError::IndexOutOfRange(rot.id));
+        }
+        let t = KeyRotation::<E>::transcript(rot.id, rot.switch_epoch, &rot.new_pk);
+        if !rot.endorsement.verify_in(&t, &self.public_keys[rot.id]) {
+            return Some(DbsError::InvalidRotation(rot.id));
+        }
+        rot.pop.verify(rot.id, &rot.new_pk)
+    }
+
+    /// Switches to the new keys of the rotations, which must have the same switch epoch
+    /// This increments the key version, and keeps the current keys for the sharings encrypted under them (see `for_key_version`)
+    ///
+    /// Nothing is changed if a rotation is invalid
+    /// If my key is rotated but I do not have the new secret key (e.g., I restarted from a config exported before announcing it), we still switch to the new public keys, so that we agree with the other nodes, and return `MissingRotationKey`
+    pub fn apply_rotations(&mut self, rots: &[KeyRotation<E>]) -> Option<DbsError> {
+        let switch_epoch = match rots.first() {
+            None => return None,
+            Some(rot) => rot.switch_epoch,
+        };
+        let mut rotated = vec![false; self.n];
+        for rot in rots {
+            if rot.switch_epoch != switch_epoch {
+                return Some(DbsError::SwitchEpochMismatch);
+            }
+            if let Some(e) = self.verify_rotation(rot) {
+                return Some(e);
+            }
+            if rotated[rot.id] {
+                return Some(DbsError::DuplicateIndex(rot.id));
+            }
+            rotated[rot.id] = true;
+        }
+
+        let mut public_keys = self.public_keys.clone();
+        let mut key_pops = self.key_pops.clone();
+        for rot in rots {
+            public_keys[rot.id] = rot.new_pk;
+            if key_pops.len() == self.n {
+                key_pops[rot.id] = rot.pop.clone();
+            }
+        }
+        let mut err = None;
+        let my_key = match self.next_key.take() {
+            Some(next) if rotated[self.origin] && next.switch_epoch == switch_epoch => next.key,
+            next_key => {
+                self.next_key = next_key;
+                if rotated[self.origin] {
+                    err = Some(DbsError::MissingRotationKey);
+                }
+                self.my_key.clone()
+            }
+        };
+        if err.is_none() && PublicKey::<E>::prime_subgroup_generator()
+            .mul(my_key.expose_secret().into_repr()) != public_keys[self.origin]
+        {
+            err = Some(DbsError::MissingRotationKey);
+        }
+
+        let optimizations = self.optimizations.rotate(self.n,
+            self.t,
+            &self.h1p,
+            &self.h2p,
+            my_key.expose_secret(),
+            public_keys.clone(),
+        );
+        let next = DbsContext {
+            h2p: self.h2p,
+            h1p: self.h1p,
+            generator_seed: self.generator_seed.clone(),
+            n: self.n,
+            t: self.t,
+            origin: self.origin,
+            public_keys,
+            key_pops,
+            my_key,
+            key_version: self.key_version + 1,
+            next_key: self.next_key.take(),
+            retired: std::mem::take(&mut self.retired),
+            proof_mode: self.proof_mode,
+            optimizations,
+        };
+        let prev = std::mem::replace(self, next);
+        self.retired.push((switch_epoch, Arc::new(prev)));
+        err
+    }
+
+    /// Forgets the keys of the previous versions that we no longer need, which wipes my old secret keys
+    /// oldest_in_use is the oldest key version of the aggregates that we still have to decrypt
+    /// We also keep the keys for t epochs after the switch, so that the blocks proposed before the switch are committed (or abandoned) first
+    pub fn retire_keys(&mut self, epoch: u64, oldest_in_use: u64) {
+        let t = self.t as u64;
+        self.retired.retain(|(switch_epoch, ctx)| {
+            ctx.key_version >= oldest_in_use || epoch <= switch_epoch + t
+        });
+    }
+}
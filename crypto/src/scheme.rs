@@ -0,0 +1,211 @@
+use ark_ec::PairingEngine;
+use fnv::FnvHashMap as HashMap;
+use rand::Rng;
+
+use crate::{AggregatePVSS, Beacon, DSSSecretKey, DbsContext, DbsError, DecompositionProof, Decryption, Encryptions, PVSSVec, Share};
+
+/// The life cycle of a PVSS scheme as used by the protocol
+/// 1. Every node deals a sharing with `generate_shares`, and the leader checks it with `verify_sharing`
+/// 2. The leader combines t+1 sharings with `aggregate`, and the others check the result with `pverify` and `decomp_verify`
+/// 3. In the epoch, every node decrypts its share with `decrypt_share`, and the others check it with `verify_share`
+/// 4. Any t+1 valid decryptions give the beacon with `reconstruct` (or `reconstruct_optimistic`), which anyone can check with `check_beacon`
+///
+/// `DbsContext` is the pairing based implementation, and `ScrapeContext` the DDH based one
+/// In consensus, the generator and leader threads and the beacon reconstruction are generic over this trait
+/// The protocol messages, the blocks and the key rotations still carry the `DbsContext` types, so the state machines run with `DbsContext`
+pub trait PvssScheme {
+    /// The sharing dealt by a single node
+    type Sharing;
+    /// The homomorphic combination of several sharings
+    type Aggregate;
+    /// The proof that an aggregate is made of sharings by the claimed dealers
+    type DecompProof;
+    /// The encrypted share of a single node
+    type Encryption;
+    /// A decrypted share along with the proof of correct decryption
+    type Decryption;
+    /// A decrypted share
+    type Share;
+    /// The reconstructed secret
+    type Beacon;
+
+    /// Creates a sharing for a random secret, dealt in the epoch
+    fn generate_shares<R>(&self,
+        epoch: u64,
+        dss_sk: &DSSSecretKey,
+        rng: &mut R,
+    ) -> Self::Sharing
+    where R: Rng + ?Sized;
+
+    /// Verifies the sharing from the dealer
+    /// The sharing is checked for the epoch it claims, so the caller must check that the epoch is recent
+    fn verify_sharing(&self,
+        dealer: usize,
+        pvec: &Self::Sharing,
+        dss_pk: &crypto_lib::PublicKey,
+    ) -> Option<DbsError>;
+
+    /// The epoch of the sharing, and the version of the keys it is encrypted under
+    /// Only sharings with the same tag can be combined
+    fn sharing_tag(&self, pvec: &Self::Sharing) -> (u64, u64);
+
+    /// Combines the sharings from the nodes in indices
+    fn aggregate(&self,
+        indices: &[usize],
+        pvec: Vec<Self::Sharing>,
+    ) -> (Self::Aggregate, Self::DecompProof);
+
+    /// Verifies the public part of an aggregate
+    fn pverify(&self, agg: &Self::Aggregate) -> Option<DbsError>;
+
+    /// Verifies that the aggregate is made of sharings by the dealers in the proof
+    fn decomp_verify(&self,
+        agg: &Self::Aggregate,
+        proof: &Self::DecompProof,
+        pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> Option<DbsError>;
+
+    /// Returns the encryption of the share of node i in the aggregate, if the aggregate has one
+    fn encryption<'a>(&self, agg: &'a Self::Aggregate, i: usize) -> Option<&'a Self::Encryption>;
+
+    /// Decrypts my share for the epoch
+    fn decrypt_share<R>(&self,
+        epoch: u64,
+        e: &Self::Encryption,
+        dss_sk: &DSSSecretKey,
+        rng: &mut R,
+    ) -> Self::Decryption
+    where R: Rng + ?Sized;
+
+    /// Verifies the decryption of the share of node origin for the epoch
+    fn verify_share(&self,
+        origin: usize,
+        epoch: u64,
+        e: &Self::Encryption,
+        dec: &Self::Decryption,
+        dss_pk: &crypto_lib::PublicKey,
+    ) -> Option<DbsError>;
+
+    /// The share in a decryption, to pass to `reconstruct`
+    fn share_of(&self, dec: &Self::Decryption) -> Self::Share;
+
+    /// Reconstructs the beacon from t+1 valid shares
+    /// shares[i] is the share of node i (if any)
+    fn reconstruct(&self, shares: &[Option<Self::Share>]) -> Self::Beacon;
+
+    /// Reconstructs the beacon from unverified decryptions
+    /// Returns the beacon (if there are enough valid decryptions) and the nodes that sent invalid decryptions
+    fn reconstruct_optimistic(&self,
+        epoch: u64,
+        agg: &Self::Aggregate,
+        decs: &[Option<Self::Decryption>],
+        pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> (Option<Self::Beacon>, Vec<usize>);
+
+    /// Checks whether this is the beacon for the aggregate
+    fn check_beacon(&self, b: &Self::Beacon, agg: &Self::Aggregate) -> bool;
+}
+
+impl<E> PvssScheme for DbsContext<E>
+where E: PairingEngine,
+{
+    type Sharing = PVSSVec<E>;
+    type Aggregate = AggregatePVSS<E>;
+    type DecompProof = DecompositionProof<E>;
+    type Encryption = Encryptions<E>;
+    type Decryption = Decryption<E>;
+    type Share = Share<E>;
+    type Beacon = Beacon<E>;
+
+    fn generate_shares<R>(&self,
+        epoch: u64,
+        dss_sk: &DSSSecretKey,
+        rng: &mut R,
+    ) -> Self::Sharing
+    where R: Rng + ?Sized,
+    {
+        DbsContext::generate_shares(self, epoch, dss_sk, rng)
+    }
+
+    fn verify_sharing(&self,
+        dealer: usize,
+        pvec: &Self::Sharing,
+        dss_pk: &crypto_lib::PublicKey,
+    ) -> Option<DbsError>
+    {
+        DbsContext::verify_sharing(self, dealer, pvec, dss_pk)
+    }
+
+    fn sharing_tag(&self, pvec: &Self::Sharing) -> (u64, u64) {
+        (pvec.epoch, pvec.key_version)
+    }
+
+    fn aggregate(&self,
+        indices: &[usize],
+        pvec: Vec<Self::Sharing>,
+    ) -> (Self::Aggregate, Self::DecompProof)
+    {
+        DbsContext::aggregate(self, indices, pvec)
+    }
+
+    fn pverify(&self, agg: &Self::Aggregate) -> Option<DbsError> {
+        DbsContext::pverify(self, agg)
+    }
+
+    fn decomp_verify(&self,
+        agg: &Self::Aggregate,
+        proof: &Self::DecompProof,
+        pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> Option<DbsError>
+    {
+        DbsContext::decomp_verify(self, agg, proof, pk_map)
+    }
+
+    fn encryption<'a>(&self, agg: &'a Self::Aggregate, i: usize) -> Option<&'a Self::Encryption> {
+        agg.encs.get(i)
+    }
+
+    fn decrypt_share<R>(&self,
+        epoch: u64,
+        e: &Self::Encryption,
+        dss_sk: &DSSSecretKey,
+        rng: &mut R,
+    ) -> Self::Decryption
+    where R: Rng + ?Sized,
+    {
+        DbsContext::decrypt_share(self, epoch, e, dss_sk, rng)
+    }
+
+    fn verify_share(&self,
+        origin: usize,
+        epoch: u64,
+        e: &Self::Encryption,
+        dec: &Self::Decryption,
+        dss_pk: &crypto_lib::PublicKey,
+    ) -> Option<DbsError>
+    {
+        DbsContext::verify_share(self, origin, epoch, e, dec, dss_pk)
+    }
+
+    fn share_of(&self, dec: &Self::Decryption) -> Self::Share {
+        dec.dec
+    }
+
+    fn reconstruct(&self, shares: &[Option<Self::Share>]) -> Self::Beacon {
+        DbsContext::reconstruct(self, shares)
+    }
+
+    fn reconstruct_optimistic(&self,
+        epoch: u64,
+        agg: &Self::Aggregate,
+        decs: &[Option<Self::Decryption>],
+        pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> (Option<Self::Beacon>, Vec<usize>)
+    {
+        DbsContext::reconstruct_optimistic(self, epoch, agg, decs, pk_map)
+    }
+
+    fn check_beacon(&self, b: &Self::Beacon, agg: &Self::Aggregate) -> bool {
+        DbsContext::check_beacon(self, b, &agg.comms)
+    }
+}
@@ -0,0 +1,701 @@
+use ark_ec::{ProjectiveCurve, msm::VariableBaseMSM};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_poly::{Polynomial as Poly, UVPolynomial, univariate::DensePolynomial};
+use fnv::FnvHashMap as HashMap;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::ark_serde::{canonical_deserialize, canonical_serialize};
+use crate::hash::Hash;
+use crate::precomputes::{LagrangeCache, lagrange_coefficients, random_codewords};
+use crate::{BeaconStream, DSSSecretKey, DbsError, SecretValue, Dleq, DleqProof, ProofMode, PvssScheme, SingleDleq, SingleDleqProof, Transcript, hash_to_curve, std_rng};
+
+/// The tag for deriving h from the seed
+pub const SCRAPE_H_TAG: &[u8] = b"optrand-scrape-h";
+/// The domain for deriving bytes from a SCRAPE beacon
+/// Bump the version whenever the derivation changes
+pub const SCRAPE_BEACON_OUTPUT_DOMAIN: &[u8] = b"scrape-beacon-output-v1";
+
+/// The scalar field of the group G
+pub type ScrapeScalar<G> = <G as ProjectiveCurve>::ScalarField;
+
+/// A PVSS context for the SCRAPE (DDH) scheme over a prime order group G without pairings, e.g., `ark_ed_on_bls12_381::EdwardsProjective`
+///
+/// With g the generator of G, h a generator derived from a public seed, and pk_i = g^sk_i, a dealer with the polynomial p shares s = p(0) as
+/// - commitments v_i = h^p(i) (and h^s), which must pass the SCRAPE coding check
+/// - encryptions c_i = pk_i^p(i), with DLEQ proofs for log_h(v_i) = log_pk_i(c_i)
+///
+/// Node i decrypts g^p(i) = c_i^(1/sk_i), and any t+1 decryptions give the secret g^s
+///
+/// Without pairings, nobody can check the combined encryptions against the combined commitments
+/// So an aggregate carries the sharings it is made of, and `decomp_verify` checks all of their DLEQ proofs
+#[derive(Debug, Clone)]
+pub struct ScrapeContext<G>
+where G: ProjectiveCurve,
+{
+    /// The generator h for the commitments, derived from the seed
+    pub h: G,
+    /// The public seed that h was derived from
+    pub(crate) generator_seed: String,
+
+    /// n: The number of nodes in the system
+    pub(crate) n: usize,
+    /// t: The number of faults tolerated by the system
+    pub(crate) t: usize,
+    /// The ID of the node using this context
+    pub(crate) origin: usize,
+
+    /// PVSS Public keys of all the participants
+    pub(crate) public_keys: Vec<G>,
+    /// My private secret key
+    pub(crate) my_key: SecretValue<ScrapeScalar<G>>,
+    /// OPTIMIZATIONS: Pre-compute sk^-1 for decryptions
+    pub(crate) my_key_inv: SecretValue<ScrapeScalar<G>>,
+
+    /// How the sharing proofs created and checked with this context are pinned to their creators
+    pub(crate) proof_mode: ProofMode,
+
+    /// OPTIMIZATIONS: Pre-compute the codewords for the coding check
+    pub(crate) codewords: Vec<ScrapeScalar<G>>,
+    /// OPTIMIZATIONS: Pre-compute lagranges for the h^s check
+    pub(crate) fixed_lagranges: Vec<ScrapeScalar<G>>,
+    /// OPTIMIZATIONS: The lagranges for the sets of shares in the reconstructions, shared by the clones
+    pub(crate) lagranges: Arc<LagrangeCache<ScrapeScalar<G>>>,
+}
+
+/// The sharing dealt by a single node
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScrapeSharing<G>
+where G: ProjectiveCurve,
+{
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub comms: Vec<G>,
+
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub encs: Vec<G>,
+
+    #[serde(bound(serialize = "DleqProof<G, G, ScrapeScalar<G>>: Serialize"))]
+    #[serde(bound(deserialize = "DleqProof<G, G, ScrapeScalar<G>>: Deserialize<'de>"))]
+    pub proofs: Vec<DleqProof<G, G, ScrapeScalar<G>>>,
+
+    /// h^s
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub hs: G,
+
+    #[serde(bound(serialize = "SingleDleqProof<G, ScrapeScalar<G>>: Serialize"))]
+    #[serde(bound(deserialize = "SingleDleqProof<G, ScrapeScalar<G>>: Deserialize<'de>"))]
+    pub sig_of_knowledge: SingleDleqProof<G, ScrapeScalar<G>>,
+
+    /// The epoch the sharing was dealt in, which the proofs are bound to
+    pub epoch: u64,
+}
+
+/// The combination of several sharings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScrapeAggregate<G>
+where G: ProjectiveCurve,
+{
+    /// The combined commitments
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub comms: Vec<G>,
+
+    /// The combined encryptions
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub encs: Vec<G>,
+
+    /// The sharings that we have combined
+    #[serde(bound(serialize = "ScrapeSharing<G>: Serialize"))]
+    #[serde(bound(deserialize = "ScrapeSharing<G>: Deserialize<'de>"))]
+    pub parts: Vec<ScrapeSharing<G>>,
+
+    /// The epoch all the parts were dealt in
+    pub epoch: u64,
+}
+
+/// A decrypted share g^p(i) and the proof that it was decrypted correctly
+///
+/// The proof is not pinned to the DSS key of the decrypter: Its statement already contains the PVSS public key of the decrypter, so a copied proof proves the same share
+/// This lets anyone check a beacon using the decryptions in it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScrapeDecryption<G>
+where G: ProjectiveCurve,
+{
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub dec: G,
+
+    #[serde(bound(serialize = "DleqProof<G, G, ScrapeScalar<G>>: Serialize"))]
+    #[serde(bound(deserialize = "DleqProof<G, G, ScrapeScalar<G>>: Deserialize<'de>"))]
+    pub proof: DleqProof<G, G, ScrapeScalar<G>>,
+}
+
+/// The secret g^s along with the t+1 decryptions it was reconstructed from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScrapeBeacon<G>
+where G: ProjectiveCurve,
+{
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    pub value: G,
+
+    /// (i, decryption of node i)
+    #[serde(bound(serialize = "ScrapeDecryption<G>: Serialize"))]
+    #[serde(bound(deserialize = "ScrapeDecryption<G>: Deserialize<'de>"))]
+    pub shares: Vec<(usize, ScrapeDecryption<G>)>,
+}
+
+impl<G> ScrapeBeacon<G>
+where G: ProjectiveCurve,
+{
+    /// Returns the seed for the output stream
+    /// The hash of the transcript with domain SCRAPE_BEACON_OUTPUT_DOMAIN and the message ("value", g^s)
+    pub fn seed(&self) -> Hash {
+        let mut t = Transcript::new(SCRAPE_BEACON_OUTPUT_DOMAIN);
+        t.append_point(b"value", &self.value.into_affine());
+        t.hash()
+    }
+
+    /// Returns the first 32 bytes of the output stream
+    pub fn to_bytes32(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        self.stream().fill_bytes(&mut out);
+        out
+    }
+
+    /// Returns the output stream of this beacon (see `Beacon::stream`)
+    pub fn stream(&self) -> BeaconStream {
+        BeaconStream::new(self.seed())
+    }
+}
+
+impl<G> ScrapeContext<G>
+where G: ProjectiveCurve,
+{
+    /// Generates a PVSS key pair (sk, g^sk)
+    pub fn generate_keypair<R>(rng: &mut R) -> (ScrapeScalar<G>, G)
+    where R: Rng + ?Sized,
+    {
+        let sk = ScrapeScalar::<G>::rand(rng);
+        (sk, G::prime_subgroup_generator().mul(sk.into_repr()))
+    }
+
+    /// Creates a context whose generator h is derived from the public seed (e.g., a chain id)
+    pub fn new_from_seed<R>(r: &mut R,
+        seed: &str,
+        n: usize,
+        t: usize,
+        id: usize,
+        public_keys: Vec<G>,
+        my_key: ScrapeScalar<G>
+    ) -> Self
+    where R: Rng + ?Sized,
+    {
+        assert!(n>2*t, "n={} must be more than 2*t={}", n, t);
+        let points: Vec<_> = (0..t+1)
+            .map(|i| ScrapeScalar::<G>::from(i as u64 + 1))
+            .collect();
+        Self {
+            h: derive_scrape_generator::<G>(seed.as_bytes()),
+            generator_seed: seed.to_string(),
+            n,
+            t,
+            origin: id,
+            public_keys,
+            my_key: SecretValue::new(my_key),
+            my_key_inv: SecretValue::new(my_key.inverse()
+                .expect("Failed to compute the inverse of my secret key")),
+            proof_mode: ProofMode::default(),
+            codewords: random_codewords::<R, ScrapeScalar<G>>(n, t, r),
+            fixed_lagranges: lagrange_coefficients(&points),
+            lagranges: Arc::new(LagrangeCache::new()),
+        }
+    }
+
+    /// Sets the format of the sharing proofs
+    /// All the nodes must use the same format
+    pub fn set_proof_mode(&mut self, mode: ProofMode) {
+        self.proof_mode = mode;
+    }
+
+    /// Returns the public seed that h was derived from
+    pub fn generator_seed(&self) -> &str {
+        &self.generator_seed
+    }
+
+    /// Creates a sharing for a given secret s, dealt in the epoch
+    pub fn generate_share_for_point<R>(&self,
+        epoch: u64,
+        dss_sk: &DSSSecretKey,
+        mut rng: &mut R,
+        secret: ScrapeScalar<G>,
+    ) -> ScrapeSharing<G>
+    where R: Rng + ?Sized,
+    {
+        let mut polynomial = DensePolynomial::<ScrapeScalar<G>>::rand(self.t, &mut rng);
+        polynomial.coeffs[0] = secret;
+
+        // s_i = p(i)
+        let evaluations: Vec<_> = (0..self.n).map(|i|
+            polynomial.evaluate(&ScrapeScalar::<G>::from(i as u64 + 1))
+        ).collect();
+
+        // v_i = h^s_i, c_i = pk_i^s_i
+        let comms: Vec<_> = evaluations.iter()
+            .map(|s| self.h.mul(s.into_repr()))
+            .collect();
+        let encs: Vec<_> = (0..self.n)
+            .map(|i| self.public_keys[i].mul(evaluations[i].into_repr()))
+            .collect();
+
+        // dleq.prove(s_i, h, v_i, pk_i, c_i)
+        let proofs = (0..self.n).map(|i| {
+            Dleq::<G, G, ScrapeScalar<G>>::prove(
+                &Self::sharing_transcript(self.origin, epoch, i),
+                &evaluations[i],
+                &self.h,
+                &comms[i],
+                &self.public_keys[i],
+                &encs[i],
+                self.proof_mode,
+                dss_sk, rng)
+        }).collect();
+
+        let hs = self.h.mul(secret.into_repr());
+        let sig_of_knowledge = SingleDleq::<G, ScrapeScalar<G>>::prove(
+            &Self::secret_transcript(self.origin, epoch),
+            &secret,
+            &self.h,
+            &hs,
+            self.proof_mode,
+            dss_sk,
+            self.my_key.expose_secret(),
+            &self.public_keys[self.origin],
+            rng
+        );
+
+        ScrapeSharing {
+            comms,
+            encs,
+            proofs,
+            hs,
+            sig_of_knowledge,
+            epoch,
+        }
+    }
+
+    /// Checks that there are n commitments and n encryptions, and that none of them is the identity
+    fn check_vectors(&self, comms: &[G], encs: &[G]) -> Option<DbsError> {
+        if comms.len() != self.n {
+            return Some(DbsError::WrongNumberOfCommitments(comms.len()));
+        }
+        if encs.len() != self.n {
+            return Some(DbsError::WrongNumberOfEncryptions(encs.len()));
+        }
+        if let Some(i) = comms.iter().position(|c| c.is_zero()) {
+            return Some(DbsError::IdentityCommitment(i));
+        }
+        if let Some(i) = encs.iter().position(|e| e.is_zero()) {
+            return Some(DbsError::IdentityEncryption(i));
+        }
+        None
+    }
+
+    /// Returns sum_i codewords_i * v_i == 0
+    fn coding_check(&self, comms: &[G]) -> bool {
+        let bases = G::batch_normalization_into_affine(comms);
+        let codes: Vec<_> = self.codewords.iter().map(|c| c.into_repr()).collect();
+        VariableBaseMSM::multi_scalar_mul(&bases, &codes).is_zero()
+    }
+
+    /// Returns the interpolation of the first t+1 commitments at 0
+    fn interpolate_commitments(&self, comms: &[G]) -> G {
+        let bases = G::batch_normalization_into_affine(&comms[0..self.t+1]);
+        let lagranges: Vec<_> = self.fixed_lagranges.iter().map(|l| l.into_repr()).collect();
+        VariableBaseMSM::multi_scalar_mul(&bases, &lagranges)
+    }
+
+    /// Creates the proof that dec = e^(1/sk), i.e., log_g(pk) = log_dec(e)
+    fn prove_decryption<R>(&self, e: &G, dec: &G, rng: &mut R) -> DleqProof<G, G, ScrapeScalar<G>>
+    where R: Rng + ?Sized,
+    {
+        let g = G::prime_subgroup_generator();
+        let pk = &self.public_keys[self.origin];
+        let w = ScrapeScalar::<G>::rand(rng);
+        let a1 = g.mul(w.into_repr());
+        let a2 = dec.mul(w.into_repr());
+        let (_, c) = Dleq::<G, G, ScrapeScalar<G>>::challenge(
+            &Self::decryption_transcript(self.origin),
+            &g.into_affine(),
+            &pk.into_affine(),
+            &dec.into_affine(),
+            &e.into_affine(),
+            &a1.into_affine(),
+            &a2.into_affine(),
+        );
+        DleqProof {
+            a1,
+            a2,
+            c,
+            r: w - c * self.my_key.expose_secret(),
+            sig: Vec::new(),
+        }
+    }
+
+    /// Checks the decryption of node origin for the encryption e
+    fn verify_decryption(&self, origin: usize, e: &G, dec: &ScrapeDecryption<G>) -> Option<DbsError> {
+        if origin >= self.public_keys.len() {
+            return Some(DbsError::IndexOutOfRange(origin));
+        }
+        let g = G::prime_subgroup_generator();
+        let pk = &self.public_keys[origin];
+        let pi = &dec.proof;
+        let (_, c) = Dleq::<G, G, ScrapeScalar<G>>::challenge(
+            &Self::decryption_transcript(origin),
+            &g.into_affine(),
+            &pk.into_affine(),
+            &dec.dec.into_affine(),
+            &e.into_affine(),
+            &pi.a1.into_affine(),
+            &pi.a2.into_affine(),
+        );
+        if c != pi.c {
+            return Some(DbsError::InvalidChallenge);
+        }
+        if !pi.sig.is_empty() {
+            return Some(DbsError::InvalidSignature);
+        }
+        if pi.a1 != g.mul(pi.r.into_repr()) + pk.mul(c.into_repr()) {
+            return Some(DbsError::LeftCheckFailed);
+        }
+        if pi.a2 != dec.dec.mul(pi.r.into_repr()) + e.mul(c.into_repr()) {
+            return Some(DbsError::RightCheckFailed);
+        }
+        None
+    }
+
+    /// The context for the proof that the dealer encrypted the i-th share correctly in a sharing dealt in the epoch
+    pub fn sharing_transcript(dealer: usize, epoch: u64, i: usize) -> Transcript {
+        let mut t = Transcript::new(b"scrape-sharing");
+        t.append_u64(b"dealer", dealer as u64);
+        t.append_u64(b"epoch", epoch);
+        t.append_u64(b"index", i as u64);
+        t
+    }
+
+    /// The context for the proof that the dealer knows the secret s in h^s, for a sharing dealt in the epoch
+    pub fn secret_transcript(dealer: usize, epoch: u64) -> Transcript {
+        let mut t = Transcript::new(b"scrape-secret");
+        t.append_u64(b"dealer", dealer as u64);
+        t.append_u64(b"epoch", epoch);
+        t
+    }
+
+    /// The context for the proof that the node decrypted its share correctly
+    /// The encryption is part of the statement, so we do not need the epoch here
+    pub fn decryption_transcript(decrypter: usize) -> Transcript {
+        let mut t = Transcript::new(b"scrape-decryption");
+        t.append_u64(b"decrypter", decrypter as u64);
+        t
+    }
+}
+
+/// Derives the generator h for the SCRAPE commitments from a public seed
+pub fn derive_scrape_generator<G>(seed: &[u8]) -> G
+where G: ProjectiveCurve,
+{
+    hash_to_curve::<G::Affine>(SCRAPE_H_TAG, seed)
+}
+
+impl<G> PvssScheme for ScrapeContext<G>
+where G: ProjectiveCurve,
+{
+    type Sharing = ScrapeSharing<G>;
+    type Aggregate = ScrapeAggregate<G>;
+    /// The dealers of the sharings in the aggregate
+    type DecompProof = Vec<usize>;
+    type Encryption = G;
+    type Decryption = ScrapeDecryption<G>;
+    /// The decryptions carry their proofs, so that the beacon can be checked by anyone
+    type Share = ScrapeDecryption<G>;
+    type Beacon = ScrapeBeacon<G>;
+
+    fn generate_shares<R>(&self,
+        epoch: u64,
+        dss_sk: &DSSSecretKey,
+        rng: &mut R,
+    ) -> Self::Sharing
+    where R: Rng + ?Sized,
+    {
+        let secret = ScrapeScalar::<G>::rand(rng);
+        self.generate_share_for_point(epoch, dss_sk, rng, secret)
+    }
+
+    fn verify_sharing(&self,
+        dealer: usize,
+        pvec: &Self::Sharing,
+        dss_pk: &crypto_lib::PublicKey,
+    ) -> Option<DbsError>
+    {
+        if let Some(e) = self.check_vectors(&pvec.comms, &pvec.encs) {
+            return Some(e);
+        }
+        if pvec.proofs.len() != self.n {
+            return Some(DbsError::WrongNumberOfProofs(pvec.proofs.len()));
+        }
+        if pvec.hs.is_zero() {
+            return Some(DbsError::IdentityGs);
+        }
+        if !self.coding_check(&pvec.comms) {
+            return Some(DbsError::CodingCheckFailed);
+        }
+
+        // OPTIMIZATION: Check all the n proofs in one batch, and only if that fails check them one by one to find the culprit
+        let transcripts: Vec<_> = (0..self.n)
+            .map(|i| Self::sharing_transcript(dealer, pvec.epoch, i))
+            .collect();
+        if !Dleq::<G, G, ScrapeScalar<G>>::batch_verify(
+            &transcripts,
+            &pvec.proofs,
+            &self.h,
+            &pvec.comms,
+            &self.public_keys,
+            &pvec.encs,
+            self.proof_mode,
+            dss_pk,
+            &mut std_rng())
+        {
+            for i in 0..self.n {
+                if let Some(x) = Dleq::<G, G, ScrapeScalar<G>>::verify(
+                    &transcripts[i],
+                    &pvec.proofs[i],
+                    &self.h,
+                    &pvec.comms[i],
+                    &self.public_keys[i],
+                    &pvec.encs[i],
+                    self.proof_mode,
+                    dss_pk)
+                {
+                    log::error!("Got error from dleq checker {:?}", x);
+                    return Some(DbsError::DlogProofCheckFailed(i));
+                }
+            }
+        }
+
+        if self.interpolate_commitments(&pvec.comms) != pvec.hs {
+            return Some(DbsError::InvalidGs);
+        }
+
+        // Check if dleq(pi, h, h^s)
+        let dealer_pk = match self.public_keys.get(dealer) {
+            Some(pk) => pk,
+            None => return Some(DbsError::IndexOutOfRange(dealer)),
+        };
+        SingleDleq::verify(
+            &Self::secret_transcript(dealer, pvec.epoch),
+            &pvec.sig_of_knowledge,
+            &self.h,
+            &pvec.hs,
+            self.proof_mode,
+            dss_pk,
+            dealer_pk)
+    }
+
+    /// SCRAPE keys are never rotated, so every sharing is under version 0
+    fn sharing_tag(&self, pvec: &Self::Sharing) -> (u64, u64) {
+        (pvec.epoch, 0)
+    }
+
+    fn aggregate(&self,
+        indices: &[usize],
+        pvec: Vec<Self::Sharing>,
+    ) -> (Self::Aggregate, Self::DecompProof)
+    {
+        assert_eq!(indices.len(), pvec.len());
+        let epoch = pvec.first().map_or(0, |p| p.epoch);
+        assert!(pvec.iter().all(|p| p.epoch == epoch),
+            "Cannot aggregate sharings dealt in different epochs");
+        let comms = (0..self.n)
+            .map(|i| pvec.iter().fold(G::zero(), |acc, p| acc + p.comms[i]))
+            .collect();
+        let encs = (0..self.n)
+            .map(|i| pvec.iter().fold(G::zero(), |acc, p| acc + p.encs[i]))
+            .collect();
+        let agg = ScrapeAggregate {
+            comms,
+            encs,
+            parts: pvec,
+            epoch,
+        };
+        (agg, indices.to_vec())
+    }
+
+    /// Checks that the combined vectors are the sums of the parts, and pass the coding check
+    /// The parts themselves are checked by `decomp_verify`
+    fn pverify(&self, agg: &Self::Aggregate) -> Option<DbsError> {
+        if let Some(e) = self.check_vectors(&agg.comms, &agg.encs) {
+            return Some(e);
+        }
+        for p in &agg.parts {
+            if let Some(e) = self.check_vectors(&p.comms, &p.encs) {
+                return Some(e);
+            }
+            if p.epoch != agg.epoch {
+                return Some(DbsError::EpochMismatch);
+            }
+        }
+        if !self.coding_check(&agg.comms) {
+            return Some(DbsError::CodingCheckFailed);
+        }
+        for i in 0..self.n {
+            if agg.parts.iter().fold(G::zero(), |acc, p| acc + p.comms[i]) != agg.comms[i] {
+                return Some(DbsError::CommitmentNotDecomposing);
+            }
+            if agg.parts.iter().fold(G::zero(), |acc, p| acc + p.encs[i]) != agg.encs[i] {
+                return Some(DbsError::EncryptionNotDecomposing);
+            }
+        }
+        None
+    }
+
+    /// Verifies every sharing in the aggregate
+    fn decomp_verify(&self,
+        agg: &Self::Aggregate,
+        proof: &Self::DecompProof,
+        pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> Option<DbsError>
+    {
+        if proof.is_empty() || proof.len() != agg.parts.len() {
+            return Some(DbsError::DecompositionLengthMismatch);
+        }
+        let mut seen = vec![false; self.n];
+        for (dealer, part) in proof.iter().zip(agg.parts.iter()) {
+            if *dealer >= self.n {
+                return Some(DbsError::IndexOutOfRange(*dealer));
+            }
+            if seen[*dealer] {
+                return Some(DbsError::DuplicateIndex(*dealer));
+            }
+            seen[*dealer] = true;
+            let pk = match pk_map.get(dealer) {
+                None => return Some(DbsError::UnknownNode(*dealer)),
+                Some(pk) => pk,
+            };
+            if let Some(x) = self.verify_sharing(*dealer, part, pk) {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    fn encryption<'a>(&self, agg: &'a Self::Aggregate, i: usize) -> Option<&'a Self::Encryption> {
+        agg.encs.get(i)
+    }
+
+    fn decrypt_share<R>(&self,
+        _epoch: u64,
+        e: &Self::Encryption,
+        _dss_sk: &DSSSecretKey,
+        rng: &mut R,
+    ) -> Self::Decryption
+    where R: Rng + ?Sized,
+    {
+        let dec = e.mul(self.my_key_inv.expose_secret().into_repr());
+        let proof = self.prove_decryption(e, &dec, rng);
+        ScrapeDecryption {
+            dec,
+            proof,
+        }
+    }
+
+    fn verify_share(&self,
+        origin: usize,
+        _epoch: u64,
+        e: &Self::Encryption,
+        dec: &Self::Decryption,
+        _dss_pk: &crypto_lib::PublicKey,
+    ) -> Option<DbsError>
+    {
+        self.verify_decryption(origin, e, dec)
+    }
+
+    /// Reconstructs g^s from the first t+1 shares
+    /// The shares must have been verified with `verify_share`
+    fn share_of(&self, dec: &Self::Decryption) -> Self::Share {
+        dec.clone()
+    }
+
+    fn reconstruct(&self, shares: &[Option<Self::Share>]) -> Self::Beacon {
+        let shares: Vec<_> = shares.iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|s| (i, s.clone())))
+            .take(self.t+1)
+            .collect();
+        let indices: Vec<_> = shares.iter().map(|(i, _)| *i).collect();
+        let lagranges: Vec<_> = self.lagranges.get(&indices)
+            .iter()
+            .map(|l| l.into_repr())
+            .collect();
+        let bases = G::batch_normalization_into_affine(
+            &shares.iter().map(|(_, s)| s.dec).collect::<Vec<_>>()
+        );
+        ScrapeBeacon {
+            value: VariableBaseMSM::multi_scalar_mul(&bases, &lagranges),
+            shares,
+        }
+    }
+
+    /// Without pairings there is no cheap check for the result, so we verify every decryption
+    fn reconstruct_optimistic(&self,
+        _epoch: u64,
+        agg: &Self::Aggregate,
+        decs: &[Option<Self::Decryption>],
+        _pk_map: &HashMap<usize, crypto_lib::PublicKey>,
+    ) -> (Option<Self::Beacon>, Vec<usize>)
+    {
+        let mut blamed = Vec::new();
+        let mut shares = vec![None; decs.len()];
+        let mut num_valid = 0;
+        for (i, dec) in decs.iter().enumerate() {
+            let dec = match dec {
+                None => continue,
+                Some(dec) => dec,
+            };
+            if i >= agg.encs.len() || self.verify_decryption(i, &agg.encs[i], dec).is_some() {
+                blamed.push(i);
+                continue;
+            }
+            shares[i] = Some(dec.clone());
+            num_valid += 1;
+        }
+        if num_valid < self.t+1 {
+            return (None, blamed);
+        }
+        (Some(self.reconstruct(&shares)), blamed)
+    }
+
+    /// Checks the t+1 decryptions in the beacon, and that the beacon is their interpolation
+    fn check_beacon(&self, b: &Self::Beacon, agg: &Self::Aggregate) -> bool {
+        if b.shares.len() != self.t+1 {
+            return false;
+        }
+        let mut indices: Vec<_> = b.shares.iter().map(|(i, _)| *i).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.len() != b.shares.len() || indices[indices.len()-1] >= agg.encs.len() {
+            return false;
+        }
+        if b.shares.iter().any(|(i, dec)| self.verify_decryption(*i, &agg.encs[*i], dec).is_some()) {
+            return false;
+        }
+        let shares: Vec<_> = (0..agg.encs.len())
+            .map(|i| b.shares.iter().find(|(j, _)| *j == i).map(|(_, s)| s.clone()))
+            .collect();
+        self.reconstruct(&shares).value == b.value
+    }
+}
@@ -0,0 +1,167 @@
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, ser::Error};
+use std::{cell::Cell, fmt};
+use zeroize::Zeroize;
+
+use crate::ark_serde::{canonical_deserialize, canonical_serialize};
+
+/// A secret (a key, or something derived from a key) that is wiped from memory when dropped, and never shows up in logs
+///
+/// The value is read with `expose_secret`
+/// It does not implement Serialize, so a struct holding it has to name how the secret is written, i.e.,
+/// - `#[serde(serialize_with = "serialize_secret", deserialize_with = "deserialize_secret")]` for serde types (e.g., bytes)
+/// - `#[serde(serialize_with = "serialize_canonical_secret", deserialize_with = "deserialize_canonical_secret")]` for arkworks types (e.g., scalars)
+///
+/// Even then, serializing the struct fails unless it happens inside `export_secret`
+///
+/// WARNING: Scalars are Copy, so only the copy inside this wrapper is wiped
+#[derive(Clone, Default)]
+pub struct SecretValue<T: Zeroize>(T);
+
+impl<T: Zeroize> SecretValue<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns the secret
+    /// Do not log or store the result
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns the secret mutably, e.g., to decode a key that wipes its input
+    pub fn expose_secret_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for SecretValue<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Zeroize> Drop for SecretValue<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for SecretValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretValue(<redacted>)")
+    }
+}
+
+thread_local! {
+    /// Whether this thread is inside `export_secret`
+    static EXPORTING: Cell<bool> = Cell::new(false);
+}
+
+/// Runs f with the secrets exported, e.g., to write the config of a node to its file
+/// Outside of it, serializing a struct that holds a secret fails instead of writing the secret
+pub fn export_secret<F, R>(f: F) -> R
+where F: FnOnce() -> R,
+{
+    /// Restores the previous state, even if f panics
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            EXPORTING.with(|e| e.set(self.0));
+        }
+    }
+    let _restore = Restore(EXPORTING.with(|e| e.replace(true)));
+    f()
+}
+
+fn check_exporting<E: Error>() -> Result<(), E> {
+    if EXPORTING.with(|e| e.get()) {
+        Ok(())
+    } else {
+        Err(E::custom("refusing to serialize a secret outside of export_secret"))
+    }
+}
+
+/// Writes the secret with serde, if called inside `export_secret`
+pub fn serialize_secret<S, T>(secret: &SecretValue<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Zeroize + Serialize,
+{
+    check_exporting()?;
+    secret.expose_secret().serialize(serializer)
+}
+
+/// Reads a secret written by `serialize_secret`
+pub fn deserialize_secret<'de, D, T>(deserializer: D) -> Result<SecretValue<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Zeroize + Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(SecretValue::new)
+}
+
+/// Writes the secret using its canonical (arkworks) serialization, if called inside `export_secret`
+pub fn serialize_canonical_secret<S, T>(secret: &SecretValue<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Zeroize + CanonicalSerialize,
+{
+    check_exporting()?;
+    canonical_serialize(secret.expose_secret(), serializer)
+}
+
+/// Reads a secret written by `serialize_canonical_secret`
+pub fn deserialize_canonical_secret<'de, D, T>(deserializer: D) -> Result<SecretValue<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Zeroize + CanonicalDeserialize,
+{
+    canonical_deserialize(deserializer).map(SecretValue::new)
+}
+
+/// The signing key of a node
+///
+/// It never shows up in logs and is not serializable; the ed25519 secret inside is wiped by crypto_lib when dropped
+#[derive(Clone)]
+pub struct SigningKey(crypto_lib::Keypair);
+
+impl SigningKey {
+    pub fn new(kpair: crypto_lib::Keypair) -> Self {
+        Self(kpair)
+    }
+
+    pub fn generate_ed25519() -> Self {
+        Self(crypto_lib::Keypair::generate_ed25519())
+    }
+
+    pub fn generate_secp256k1() -> Self {
+        Self(crypto_lib::Keypair::generate_secp256k1())
+    }
+
+    /// Returns the key pair
+    /// Do not log or store the result
+    pub fn expose_secret(&self) -> &crypto_lib::Keypair {
+        &self.0
+    }
+
+    pub fn public(&self) -> crypto_lib::PublicKey {
+        self.0.public()
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, crypto_lib::error::SigningError> {
+        self.0.sign(msg)
+    }
+}
+
+impl From<crypto_lib::Keypair> for SigningKey {
+    fn from(kpair: crypto_lib::Keypair) -> Self {
+        Self::new(kpair)
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SigningKey(<redacted>)")
+    }
+}
@@ -1,13 +1,124 @@
+/// The setup shared by the tests of the scheme
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::{DSSSecretKey, DbsContext, Keypair};
+    use ark_bls12_381::Bls12_381;
+    use fnv::FnvHashMap as HashMap;
+    use rand::Rng;
+
+    type E = Bls12_381;
+
+    /// Creates the contexts of n nodes with the threshold t, along with their signing keys and the map of their public signing keys
+    /// Everything is drawn from rng, so a seeded rng gives the same nodes every time
+    pub(crate) fn dbs_contexts<R>(n: usize, t: usize, rng: &mut R) -> (Vec<DbsContext<E>>, Vec<DSSSecretKey>, HashMap<usize, crypto_lib::PublicKey>)
+    where R: Rng + ?Sized,
+    {
+        let keypairs: Vec<_> = (0..n).map(|_| Keypair::<E>::generate_keypair(&mut *rng)).collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let ctx = (0..n).map(|i| {
+            DbsContext::<E>::new_from_seed(&mut *rng, "chain-1", n, t, i, public_keys.clone(), keypairs[i].0)
+        }).collect();
+        let dss: Vec<_> = (0..n).map(|_| {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            let sk = crypto_lib::ed25519::SecretKey::from_bytes(&mut bytes).unwrap();
+            DSSSecretKey::new(crypto_lib::Keypair::Ed25519(sk.into()))
+        }).collect();
+        let pk_map = (0..n).map(|i| (i, dss[i].public())).collect();
+        (ctx, dss, pk_map)
+    }
+}
+
 #[cfg(test)]
 mod keypair_tests {
     use ark_bls12_381::Bls12_381;
-    use crate::{std_rng, Keypair};
+    use ark_ff::{Field, Zero};
+    use crate::{DSSSecretKey, DbsContext, DbsError, KeyPop, PublicKey, Scalar, export_secret, std_rng, Keypair};
+
+    type E = Bls12_381;
 
     #[test]
     fn test_gen() {
         let mut rng = std_rng();
         let _ = Keypair::<Bls12_381>::generate_keypair(&mut rng);
     }
+
+    #[test]
+    fn test_pop() {
+        let mut rng = std_rng();
+        let (kpair, pop) = Keypair::<E>::generate_keypair_with_pop(3, &mut rng);
+        assert_eq!(None, pop.verify(3, &kpair.1));
+        // The proof is bound to the node id
+        assert_eq!(Some(DbsError::InvalidKeyPop(4)), pop.verify(4, &kpair.1));
+        // A rogue key pk' = pk2 - pk1 cannot reuse the proof for pk2
+        let other = Keypair::<E>::generate_keypair(&mut rng);
+        let rogue = kpair.1 - other.1;
+        assert_eq!(Some(DbsError::InvalidKeyPop(3)), pop.verify(3, &rogue));
+
+        let pop2: KeyPop<E> = bincode::deserialize(&bincode::serialize(&pop).unwrap()).unwrap();
+        assert_eq!(pop, pop2);
+    }
+
+    #[test]
+    fn test_context_pops() {
+        let mut rng = std_rng();
+        let n = 4;
+        let mut public_keys = Vec::new();
+        let mut secret_keys = Vec::new();
+        let mut pops = Vec::new();
+        for i in 0..n {
+            let (kpair, pop) = Keypair::<E>::generate_keypair_with_pop(i, &mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+            pops.push(pop);
+        }
+        let ctx = DbsContext::<E>::new_from_seed(&mut rng, "chain-1", n, 1, 0, public_keys.clone(), secret_keys[0]);
+        assert_eq!(Some(DbsError::MissingKeyPops), ctx.check_key_pops());
+
+        let ctx = ctx.with_key_pops(pops.clone()).unwrap();
+        let bytes = export_secret(|| bincode::serialize(&ctx)).unwrap();
+        let ctx2: DbsContext<E> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(None, ctx2.check_key_pops());
+
+        // Swapping the keys of two nodes breaks the binding to the node ids
+        pops.swap(1, 2);
+        let mut swapped_keys = public_keys.clone();
+        swapped_keys.swap(1, 2);
+        let ctx3 = DbsContext::<E>::new_from_seed(&mut rng, "chain-1", n, 1, 0, swapped_keys, secret_keys[0]);
+        assert_eq!(Some(DbsError::InvalidKeyPop(1)), ctx3.with_key_pops(pops).err());
+
+        // The identity is not a valid public key
+        let zero = Keypair::<E>(Scalar::<E>::zero(), PublicKey::<E>::zero());
+        let bad = zero.prove_possession(2, &mut rng);
+        assert_eq!(Some(DbsError::InvalidKeyPop(2)), bad.verify(2, &zero.1));
+    }
+
+    #[test]
+    fn test_secret_hygiene() {
+        let mut rng = std_rng();
+        let n = 4;
+        let keypairs: Vec<_> = (0..n).map(|_| Keypair::<E>::generate_keypair(&mut rng)).collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let sk = keypairs[2].0;
+        let ctx = DbsContext::<E>::new_from_seed(&mut rng, "chain-1", n, 1, 2, public_keys, sk);
+
+        // Neither the key nor its inverse shows up in the logs
+        let logged = format!("{:?}", ctx);
+        assert!(!logged.contains(&format!("{:?}", sk)));
+        assert!(!logged.contains(&format!("{:?}", sk.inverse().unwrap())));
+        assert!(logged.contains("<redacted>"));
+
+        // The key is only written when the context is exported explicitly
+        assert!(bincode::serialize(&ctx).is_err());
+        let bytes = export_secret(|| bincode::serialize(&ctx)).unwrap();
+        let ctx2: DbsContext<E> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(&sk, ctx2.my_key.expose_secret());
+        assert!(bincode::serialize(&ctx2).is_err());
+
+        // Neither is the signing key
+        let dss = DSSSecretKey::generate_ed25519();
+        assert_eq!("SigningKey(<redacted>)", format!("{:?}", dss));
+    }
 }
 
 
@@ -119,7 +230,7 @@ mod ctx_tests {
 
 #[cfg(test)]
 mod dleq_tests {
-    use crate::{DbsContext, Keypair, Scalar, std_rng};
+    use crate::{DbsContext, Keypair, ProofMode, Scalar, std_rng};
     use ark_bls12_381::Bls12_381;
     use ark_ff::PrimeField;
     use ark_std::UniformRand;
@@ -134,7 +245,7 @@ mod dleq_tests {
         let t = 50;
         let mut public_keys: Vec<_> = Vec::new();
         let mut secret_keys: Vec<_> = Vec::new();
-        let dss_kpair = crypto_lib::Keypair::generate_secp256k1();
+        let dss_kpair = crate::DSSSecretKey::generate_secp256k1();
         let dss_pk = dss_kpair.public();
         for _i in 0..n {
             let kpair = Keypair::<E>::generate_keypair(&mut rng);
@@ -147,26 +258,87 @@ mod dleq_tests {
 
         let dbs_ctx = DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys, secret_keys[0]);
         let pvec = 
-            dbs_ctx.generate_shares( &dss_kpair, &mut rng);
+            dbs_ctx.generate_shares(1, &dss_kpair, &mut rng);
         for i in 0..n {
             assert_eq!(None, 
                 crate::Dleq::<<E as PairingEngine>::G1Projective, 
                     <E as PairingEngine>::G2Projective, 
                     <E as PairingEngine>::Fr>::verify(
+                        &DbsContext::<E>::sharing_transcript(0, 1, i),
                         &pvec.proofs[i], 
                         &dbs_ctx.optimizations.g1p, 
                         &pvec.comms[i].into_projective(), 
                         &dbs_ctx.public_keys[i], 
                         &pvec.encs[i], 
+                        ProofMode::Signed,
                         &dss_pk)
                 );
         }
     }
+
+    #[test]
+    fn test_batch_dleq() {
+        let mut rng = std_rng();
+        let n = 21;
+        let t = 10;
+        let mut public_keys: Vec<_> = Vec::new();
+        let mut secret_keys: Vec<_> = Vec::new();
+        let dss_kpair = crate::DSSSecretKey::generate_ed25519();
+        let dss_pk = dss_kpair.public();
+        for _i in 0..n {
+            let kpair = Keypair::<E>::generate_keypair(&mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+        }
+        let h2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator()
+            .mul(Scalar::<E>::rand(&mut rng));
+        let h1 = <Bls12_381 as PairingEngine>::G1Affine::prime_subgroup_generator().mul(Scalar::<Bls12_381>::rand(&mut rng).into_repr());
+
+        let dbs_ctx = DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys, secret_keys[0]);
+        let mut pvec = 
+            dbs_ctx.generate_shares(1, &dss_kpair, &mut rng);
+        let comms: Vec<_> = pvec.comms.iter().map(|c| c.into_projective()).collect();
+        let transcripts: Vec<_> = (0..n).map(|i| DbsContext::<E>::sharing_transcript(0, 1, i)).collect();
+        assert_eq!(true, 
+            crate::Dleq::<<E as PairingEngine>::G1Projective, 
+                <E as PairingEngine>::G2Projective, 
+                <E as PairingEngine>::Fr>::batch_verify(
+                    &transcripts,
+                    &pvec.proofs, 
+                    &dbs_ctx.optimizations.g1p, 
+                    &comms, 
+                    &dbs_ctx.public_keys, 
+                    &pvec.encs, 
+                    ProofMode::Signed,
+                    &dss_pk,
+                    &mut rng)
+            );
+        
+        // Tamper with one of the responses
+        pvec.proofs[7].r = Scalar::<E>::rand(&mut rng);
+        assert_eq!(false, 
+            crate::Dleq::<<E as PairingEngine>::G1Projective, 
+                <E as PairingEngine>::G2Projective, 
+                <E as PairingEngine>::Fr>::batch_verify(
+                    &transcripts,
+                    &pvec.proofs, 
+                    &dbs_ctx.optimizations.g1p, 
+                    &comms, 
+                    &dbs_ctx.public_keys, 
+                    &pvec.encs, 
+                    ProofMode::Signed,
+                    &dss_pk,
+                    &mut rng)
+            );
+        assert_eq!(Some(crate::DbsError::DlogProofCheckFailed(7)), 
+            dbs_ctx.verify_sharing(0, &pvec, &dss_pk)
+        );
+    }
 }
 
 #[cfg(test)]
 mod dbs_tests {
-    use crate::{DbsContext, Keypair, PVSSVec, PublicKey, Scalar, SecretKey, std_rng};
+    use crate::{DbsContext, DbsError, Keypair, PVSSVec, ProofMode, PublicKey, Scalar, SecretKey, std_rng};
     use ark_bls12_381::Bls12_381;
     use ark_std::{UniformRand, Zero};
     use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
@@ -189,7 +361,7 @@ mod dbs_tests {
             let kpair = Keypair::<E>::generate_keypair(&mut rng);
             secret_keys.push(kpair.0);
             public_keys.push(kpair.1);
-            let dsskpair = crypto_lib::Keypair::generate_secp256k1();
+            let dsskpair = crate::DSSSecretKey::generate_secp256k1();
             dss_pk.push(dsskpair.public());
             dss_kpair.push(dsskpair);
         }
@@ -199,7 +371,7 @@ mod dbs_tests {
         let dbs_ctx = DbsContext::<E>::new(&mut rng, h2,h1, n, t, 0, public_keys, secret_keys[0]);
         let idx = 0;
         let pvec = 
-            dbs_ctx.generate_shares(&dss_kpair[idx], &mut rng);
+            dbs_ctx.generate_shares(1, &dss_kpair[idx], &mut rng);
         
         type G1P = <E as PairingEngine>::G1Projective;
         let coding_check = (0..n).map(|i| {
@@ -208,7 +380,7 @@ mod dbs_tests {
                 acc + c
             }) == G1P::zero(); 
         assert_eq!(coding_check, true);
-        assert_eq!(None, dbs_ctx.verify_sharing(&pvec, &dss_pk[idx]));
+        assert_eq!(None, dbs_ctx.verify_sharing(idx, &pvec, &dss_pk[idx]));
 
         let into_repr: Vec<_> = (0..dbs_ctx.t+1).map(|i| {
             dbs_ctx.optimizations.fixed_lagranges[i].into()
@@ -246,7 +418,7 @@ mod dbs_tests {
             let kpair = Keypair::<E>::generate_keypair(&mut rng);
             secret_keys.push(kpair.0);
             public_keys.push(kpair.1);
-            let dsskpair = crypto_lib::Keypair::generate_secp256k1();
+            let dsskpair = crate::DSSSecretKey::generate_secp256k1();
             dss_pk.push(dsskpair.public());
             dss_kpair.push(dsskpair);
         }
@@ -256,10 +428,10 @@ mod dbs_tests {
         let dbs_ctx = DbsContext::<E>::new(&mut rng, h2,h1, n, t, 0, public_keys, secret_keys[0]);
         let idx = 0;
         let pvec = 
-            dbs_ctx.generate_shares(&dss_kpair[idx], &mut rng);
+            dbs_ctx.generate_shares(1, &dss_kpair[idx], &mut rng);
         
-        assert_eq!(None, dbs_ctx.verify_sharing(&pvec, &dss_pk[idx]));
-        assert_eq!(None, dbs_ctx.clone().verify_sharing(&pvec, &dss_pk[idx]));
+        assert_eq!(None, dbs_ctx.verify_sharing(idx, &pvec, &dss_pk[idx]));
+        assert_eq!(None, dbs_ctx.clone().verify_sharing(idx, &pvec, &dss_pk[idx]));
 
         let bytes = bincode::serialize(&pvec);
         assert_eq!(true, bytes.is_ok());
@@ -286,7 +458,7 @@ mod dbs_tests {
             let kpair = Keypair::<E>::generate_keypair(&mut rng);
             secret_keys.push(kpair.0);
             public_keys.push(kpair.1);
-            let dsskpair = crypto_lib::Keypair::generate_secp256k1();
+            let dsskpair = crate::DSSSecretKey::generate_secp256k1();
             dss_pk.insert(i,dsskpair.public() );
             dss_kpair.insert(i, dsskpair);
         }
@@ -299,10 +471,14 @@ mod dbs_tests {
         let indices:Vec<_> = (0..t+1).map(|i| i).collect();
         for i in 0..t+1 {
             let pvec = 
-            dbs_ctx[i].generate_shares(&dss_kpair[&i], &mut rng);
-            assert_eq!(None, dbs_ctx[i].verify_sharing(&pvec, &dss_pk[&i]));
+            dbs_ctx[i].generate_shares(1, &dss_kpair[&i], &mut rng);
+            assert_eq!(None, dbs_ctx[i].verify_sharing(i, &pvec, &dss_pk[&i]));
             pvecs.push(pvec);
         }
+        // A sharing dealt for an epoch cannot be replayed in a later one
+        let mut replayed = pvecs[0].clone();
+        replayed.epoch = 2;
+        assert_eq!(Some(DbsError::DlogProofCheckFailed(0)), dbs_ctx[0].verify_sharing(0, &replayed, &dss_pk[&0]));
         let (agg_pvss, agg_pi) = dbs_ctx[0].aggregate(&indices, pvecs);
         assert_eq!(None, dbs_ctx[0].pverify(&agg_pvss));
         for i in 0..n {
@@ -312,6 +488,128 @@ mod dbs_tests {
         }
     }
 
+    #[test]
+    fn sok_mode_test() {
+        let mut rng = std_rng();
+        let n:usize = 21;
+        let t = 10;
+        
+        let mut public_keys: Vec<PublicKey<E>> = Vec::new();
+        let mut secret_keys: Vec<SecretKey<E>> = Vec::new();
+        let mut dss_kpair:HashMap<_,_> = HashMap::default();
+        let mut dss_pk:HashMap<_,_> = HashMap::default();
+        let mut pvecs = Vec::new();
+        for i in 0..n {
+            let kpair = Keypair::<E>::generate_keypair(&mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+            let dsskpair = crate::DSSSecretKey::generate_ed25519();
+            dss_pk.insert(i,dsskpair.public() );
+            dss_kpair.insert(i, dsskpair);
+        }
+        let h2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let h1 = <Bls12_381 as PairingEngine>::G1Affine::prime_subgroup_generator().mul(Scalar::<Bls12_381>::rand(&mut rng).into_repr());
+
+        let dbs_ctx:Vec<_> = (0..n).map(|i| {
+            let mut ctx = DbsContext::<E>::new(&mut rng, h2,h1, n as usize, t, i, public_keys.clone(), secret_keys[i as usize].clone());
+            ctx.set_proof_mode(ProofMode::SignatureOfKnowledge);
+            ctx
+        }).collect();
+        let indices:Vec<_> = (0..t+1).map(|i| i).collect();
+        for i in 0..t+1 {
+            let pvec = 
+            dbs_ctx[i].generate_shares(1, &dss_kpair[&i], &mut rng);
+            // No signatures are sent with the proofs
+            assert_eq!(true, pvec.proofs.iter().all(|pi| pi.sig.is_empty()));
+            assert_eq!(true, pvec.sig_of_knowledge.sig.is_empty());
+            assert_eq!(None, dbs_ctx[n-1].verify_sharing(i, &pvec, &dss_pk[&i]));
+            // The proofs are pinned to the dealer's key
+            assert_eq!(Some(DbsError::DlogProofCheckFailed(0)), 
+                dbs_ctx[n-1].verify_sharing(i, &pvec, &dss_pk[&(i+1)]));
+            pvecs.push(pvec);
+        }
+
+        // A context expecting signatures rejects these proofs
+        let mut signed_ctx = dbs_ctx[n-1].clone();
+        signed_ctx.set_proof_mode(ProofMode::Signed);
+        assert_eq!(Some(DbsError::DlogProofCheckFailed(0)), 
+            signed_ctx.verify_sharing(0, &pvecs[0], &dss_pk[&0]));
+
+        let (agg_pvss, agg_pi) = dbs_ctx[0].aggregate(&indices, pvecs);
+        for i in 0..n {
+            assert_eq!(None, 
+                dbs_ctx[i as usize].decomp_verify(&agg_pvss, &agg_pi, &dss_pk)
+            );
+        }
+        // Claiming another dealer's sharing fails the decomposition check
+        let mut bad_pi = agg_pi.clone();
+        bad_pi.indices[0] = n-1;
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            dbs_ctx[1].decomp_verify(&agg_pvss, &bad_pi, &dss_pk)
+        );
+        // The proofs for the secrets must prove knowledge of the dealer's PVSS key
+        let mut bad_pi = agg_pi.clone();
+        bad_pi.dleq_proof[0].key_proof = None;
+        assert_eq!(Some(DbsError::InvalidKeyProof), 
+            dbs_ctx[1].decomp_verify(&agg_pvss, &bad_pi, &dss_pk)
+        );
+
+        // A Byzantine node (n-1) relabels its own sharing to an honest dealer (0)
+        // Nothing is signed with the dealer's DSS key in this mode, so only the PVSS key of the dealer pins the sharing
+        let mut forger = DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys.clone(), secret_keys[n-1].clone());
+        forger.set_proof_mode(ProofMode::SignatureOfKnowledge);
+        let forged = forger.generate_shares(1, &dss_kpair[&0], &mut rng);
+        assert_eq!(Some(DbsError::InvalidKeyProof), 
+            dbs_ctx[1].verify_sharing(0, &forged, &dss_pk[&0]));
+        let mut forged_pvecs = vec![forged];
+        forged_pvecs.extend((1..t+1).map(|i| dbs_ctx[i].generate_shares(1, &dss_kpair[&i], &mut rng)));
+        let (forged_agg, forged_pi) = forger.aggregate(&indices, forged_pvecs);
+        assert_eq!(None, dbs_ctx[1].pverify(&forged_agg));
+        assert_eq!(Some(DbsError::InvalidKeyProof), 
+            dbs_ctx[1].decomp_verify(&forged_agg, &forged_pi, &dss_pk)
+        );
+
+        let dec = dbs_ctx[3].decrypt_share(7, &agg_pvss.encs[3], &dss_kpair[&3], &mut rng);
+        assert_eq!(true, dec.proof.sig.is_empty());
+        assert_eq!(None, dbs_ctx[0].verify_share(3, 7, &agg_pvss.encs[3], &dec, &dss_pk[&3]));
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            dbs_ctx[0].verify_share(3, 7, &agg_pvss.encs[3], &dec, &dss_pk[&4]));
+    }
+
+    #[test]
+    fn batch_pverify_test() {
+        let mut rng = std_rng();
+        let n:usize = 21;
+        let t = 10;
+        
+        let mut public_keys: Vec<PublicKey<E>> = Vec::new();
+        let mut secret_keys: Vec<SecretKey<E>> = Vec::new();
+        let mut dss_kpair = Vec::new();
+        let mut pvecs = Vec::new();
+        for _i in 0..n {
+            let kpair = Keypair::<E>::generate_keypair(&mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+            dss_kpair.push(crate::DSSSecretKey::generate_ed25519());
+        }
+        let h2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let h1 = <Bls12_381 as PairingEngine>::G1Affine::prime_subgroup_generator().mul(Scalar::<Bls12_381>::rand(&mut rng).into_repr());
+
+        let dbs_ctx = DbsContext::<E>::new(&mut rng, h2,h1, n, t, 0, public_keys, secret_keys[0]);
+        let indices:Vec<_> = (0..t+1).map(|i| i).collect();
+        for i in 0..t+1 {
+            pvecs.push(dbs_ctx.generate_shares(1, &dss_kpair[i], &mut rng));
+        }
+        let (mut agg_pvss, _agg_pi) = dbs_ctx.aggregate(&indices, pvecs);
+        assert_eq!(true, dbs_ctx.batch_pairing_check(&agg_pvss, &mut rng));
+        assert_eq!(None, dbs_ctx.pverify(&agg_pvss));
+
+        // Tamper with the encryption for node 5
+        agg_pvss.encs[5] += dbs_ctx.optimizations.g2p;
+        assert_eq!(false, dbs_ctx.batch_pairing_check(&agg_pvss, &mut rng));
+        assert_eq!(Some(crate::DbsError::PairingCheckFailed(5)), dbs_ctx.pverify(&agg_pvss));
+    }
+
     #[test]
     fn decryption_test() {
         let mut rng = std_rng();
@@ -326,7 +624,7 @@ mod dbs_tests {
             let kpair = Keypair::<E>::generate_keypair(&mut rng);
             secret_keys.push(kpair.0);
             public_keys.push(kpair.1);
-            let dsskpair = crypto_lib::Keypair::generate_secp256k1();
+            let dsskpair = crate::DSSSecretKey::generate_secp256k1();
             dss_pk.push(dsskpair.public());
             dss_kpair.push(dsskpair);
         }
@@ -337,17 +635,126 @@ mod dbs_tests {
             DbsContext::<E>::new(&mut rng, h2, h1, n, t, i, public_keys.clone(), secret_keys[i])
         }).collect();
         let pvec = 
-            dbs_ctx[0].generate_shares(&dss_kpair[0], &mut rng);
+            dbs_ctx[0].generate_shares(1, &dss_kpair[0], &mut rng);
         
         for j in 0..n {
             let dec = 
-            dbs_ctx[j].decrypt_share(&pvec.encs[j], &dss_kpair[j], &mut rng);
+            dbs_ctx[j].decrypt_share(1, &pvec.encs[j], &dss_kpair[j], &mut rng);
             assert_eq!(None, 
-                dbs_ctx[0].verify_share(j, &pvec.encs[j], &dec, &dss_pk[j])
+                dbs_ctx[0].verify_share(j, 1, &pvec.encs[j], &dec, &dss_pk[j])
             );
         }
     }
 
+    #[test]
+    fn test_optimistic_reconstruction() {
+        let mut rng = std_rng();
+        let n = 7;
+        let t = 3;
+        
+        let mut public_keys = Vec::new();
+        let mut secret_keys = Vec::new();
+        let mut dss_kpair = Vec::new();
+        let mut dss_pk = HashMap::default();
+        for i in 0..n {
+            let kpair = Keypair::<E>::generate_keypair(&mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+            let dsskpair = crate::DSSSecretKey::generate_ed25519();
+            dss_pk.insert(i, dsskpair.public());
+            dss_kpair.push(dsskpair);
+        }
+        let h2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let h1 = <Bls12_381 as PairingEngine>::G1Affine::prime_subgroup_generator().mul(Scalar::<Bls12_381>::rand(&mut rng).into_repr());
+
+        let dbs_ctx:Vec<_> = (0..n).map(|i| {
+            DbsContext::<E>::new(&mut rng, h2,h1, n, t, i, public_keys.clone(), secret_keys[i])
+        }).collect();
+        let indices:Vec<_> = (0..t+1).collect();
+        let pvecs: Vec<_> = (0..t+1).map(|i| dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng)).collect();
+        let (agg_pvss, _) = dbs_ctx[0].aggregate(&indices, pvecs);
+        let mut decs: Vec<_> = (0..n).map(|j| {
+            Some(dbs_ctx[j].decrypt_share(5, &agg_pvss.encs[j], &dss_kpair[j], &mut rng))
+        }).collect();
+
+        // All the shares are good
+        let (beacon, blamed) = dbs_ctx[0].reconstruct_optimistic(5, &agg_pvss, &decs, &dss_pk);
+        let beacon = beacon.unwrap();
+        assert_eq!(Vec::<usize>::new(), blamed);
+        assert_eq!(true, dbs_ctx[0].check_beacon(&beacon, &agg_pvss.comms));
+        let all: Vec<_> = decs.iter().map(|d| d.as_ref().map(|d| d.dec)).collect();
+        assert_eq!(beacon, dbs_ctx[0].reconstruct(&all));
+
+        // A wrong beacon fails the check
+        let mut wrong = beacon.clone();
+        wrong.value = wrong.value.double();
+        wrong.beacon = E::pairing(dbs_ctx[0].h1p, wrong.value);
+        assert_eq!(false, dbs_ctx[0].check_beacon(&wrong, &agg_pvss.comms));
+
+        // Nodes 1 and 2 send bad shares, and node 4 stays silent
+        decs[1].as_mut().unwrap().dec = decs[1].as_ref().unwrap().dec.double();
+        decs[2] = Some(decs[3].clone().unwrap());
+        decs[4] = None;
+        let (beacon2, blamed) = dbs_ctx[0].reconstruct_optimistic(5, &agg_pvss, &decs, &dss_pk);
+        assert_eq!(vec![1, 2], blamed);
+        assert_eq!(Some(beacon.clone()), beacon2);
+
+        // Not enough good shares
+        decs[5] = None;
+        let (beacon3, blamed) = dbs_ctx[0].reconstruct_optimistic(5, &agg_pvss, &decs, &dss_pk);
+        assert_eq!(vec![1, 2], blamed);
+        assert_eq!(None, beacon3);
+    }
+
+    #[test]
+    fn test_check_beacon_subsets() {
+        let mut rng = std_rng();
+        let n = 7;
+        let t = 3;
+        
+        let mut public_keys = Vec::new();
+        let mut secret_keys = Vec::new();
+        let mut dss_kpair = Vec::new();
+        for _i in 0..n {
+            let kpair = Keypair::<E>::generate_keypair(&mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+            dss_kpair.push(crate::DSSSecretKey::generate_ed25519());
+        }
+        let h2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let h1 = <Bls12_381 as PairingEngine>::G1Affine::prime_subgroup_generator().mul(Scalar::<Bls12_381>::rand(&mut rng).into_repr());
+
+        let dbs_ctx:Vec<_> = (0..n).map(|i| {
+            DbsContext::<E>::new(&mut rng, h2,h1, n, t, i, public_keys.clone(), secret_keys[i])
+        }).collect();
+        let indices:Vec<_> = (0..t+1).collect();
+        let pvecs: Vec<_> = (0..t+1).map(|i| dbs_ctx[i].generate_shares(1, &dss_kpair[i], &mut rng)).collect();
+        let (agg_pvss, _) = dbs_ctx[0].aggregate(&indices, pvecs);
+        let shares: Vec<_> = (0..n).map(|j| {
+            Some(dbs_ctx[j].decrypt_share(5, &agg_pvss.encs[j], &dss_kpair[j], &mut rng).dec)
+        }).collect();
+        let beacon = dbs_ctx[0].reconstruct(&shares);
+
+        // Any t+1 commitments can be used
+        assert_eq!(true, dbs_ctx[0].check_beacon(&beacon, &agg_pvss.comms));
+        assert_eq!(true, dbs_ctx[0].check_beacon_for(&beacon, &agg_pvss.comms, &[6, 2, 4, 5]));
+        assert_eq!(true, dbs_ctx[0].check_beacon_for(&beacon, &agg_pvss.comms, &[1, 3, 5, 6, 0]));
+
+        // Not enough, repeated, or unknown nodes
+        assert_eq!(false, dbs_ctx[0].check_beacon_for(&beacon, &agg_pvss.comms, &[1, 3, 5]));
+        assert_eq!(false, dbs_ctx[0].check_beacon_for(&beacon, &agg_pvss.comms, &[1, 3, 5, 3]));
+        assert_eq!(false, dbs_ctx[0].check_beacon_for(&beacon, &agg_pvss.comms, &[1, 3, 5, n]));
+
+        // A wrong value or a wrong beacon fails the check
+        let mut wrong = beacon.clone();
+        wrong.value = wrong.value.double();
+        wrong.beacon = E::pairing(dbs_ctx[0].h1p, wrong.value);
+        assert_eq!(false, dbs_ctx[0].check_beacon_for(&wrong, &agg_pvss.comms, &[6, 2, 4, 5]));
+        let mut wrong = beacon.clone();
+        wrong.beacon = E::pairing(dbs_ctx[0].h1p.double(), wrong.value);
+        assert_eq!(false, dbs_ctx[0].check_beacon_for(&wrong, &agg_pvss.comms, &[6, 2, 4, 5]));
+    }
+
     #[test]
     fn test_reconstruction() {
         let mut rng = std_rng();
@@ -362,7 +769,7 @@ mod dbs_tests {
             let kpair = Keypair::<E>::generate_keypair(&mut rng);
             secret_keys.push(kpair.0);
             public_keys.push(kpair.1);
-            let dsskpair = crypto_lib::Keypair::generate_secp256k1();
+            let dsskpair = crate::DSSSecretKey::generate_secp256k1();
             dss_pk.push(dsskpair.public());
             dss_kpair.push(dsskpair);
         }
@@ -374,13 +781,13 @@ mod dbs_tests {
         }).collect();
         let s = Scalar::<E>::rand(&mut rng);
         let pvec = 
-            dbs_ctx[0].generate_share_for_point(&dss_kpair[0], &mut rng, s);
+            dbs_ctx[0].generate_share_for_point(1, &dss_kpair[0], &mut rng, s);
         assert_eq!(None, 
-            dbs_ctx[1].verify_sharing(&pvec, &dss_pk[0]));
+            dbs_ctx[1].verify_sharing(0, &pvec, &dss_pk[0]));
         let mut decs:Vec<_> = (0..n).map(|j| {
             let dec = 
-            dbs_ctx[j].decrypt_share(&pvec.encs[j], &dss_kpair[j], &mut rng);
-            assert_eq!(None, dbs_ctx[0].verify_share(j, &pvec.encs[j], &dec, &dss_pk[j]));
+            dbs_ctx[j].decrypt_share(1, &pvec.encs[j], &dss_kpair[j], &mut rng);
+            assert_eq!(None, dbs_ctx[0].verify_share(j, 1, &pvec.encs[j], &dec, &dss_pk[j]));
             Some(dec.dec)
         }).collect();
         for i in 0..n-(t+1) {
@@ -393,4 +800,1198 @@ mod dbs_tests {
         assert_eq!(beacon.value, gs_orig);
         assert_eq!(beacon.beacon, E::pairing(dbs_ctx[0].h1p, beacon.value));
     }
+
+    #[test]
+    fn test_check_beacon_generators() {
+        use crate::{Beacon, Polynomial};
+        use ark_poly::{Polynomial as _, UVPolynomial};
+
+        let mut rng = std_rng();
+        let n = 7;
+        let t = 3;
+        let mut public_keys = Vec::new();
+        let mut secret_keys = Vec::new();
+        for _i in 0..n {
+            let kpair = Keypair::<E>::generate_keypair(&mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+        }
+        // h1 is not g1, so checking against the wrong generator fails
+        let h2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let h1 = <E as PairingEngine>::G1Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let ctx = DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys, secret_keys[0]);
+
+        // The commitments for a sharing of s are g1^p(i), and its beacon is e(h1, g2^s)
+        let p = Polynomial::<E>::rand(t, &mut rng);
+        let evals: Vec<_> = (0..n).map(|i| p.evaluate(&Scalar::<E>::from(i as u64 + 1))).collect();
+        let g1 = <E as PairingEngine>::G1Affine::prime_subgroup_generator();
+        let g2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator();
+        let comms: Vec<_> = evals.iter().map(|s| g1.mul(s.into_repr()).into_affine()).collect();
+        let value = g2.mul(p.coeffs[0].into_repr());
+        let beacon = Beacon::<E> { beacon: E::pairing(h1, value), value };
+        assert_eq!(true, ctx.check_beacon(&beacon, &comms));
+
+        // Commitments to h1 are not for this beacon
+        let comms_h1: Vec<_> = evals.iter().map(|s| h1.mul(s.into_repr()).into_affine()).collect();
+        assert_eq!(false, ctx.check_beacon(&beacon, &comms_h1));
+    }
+}
+
+#[cfg(test)]
+mod transcript_tests {
+    use crate::{DbsContext, Dleq, DbsError, Keypair, ProofMode, Scalar, SingleDleq, Transcript, hash::Hash, std_rng};
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::{AffineCurve, PairingEngine};
+    use ark_ff::PrimeField;
+    use ark_std::UniformRand;
+    use std::str::FromStr;
+
+    type E = Bls12_381;
+    type G1P = <E as PairingEngine>::G1Projective;
+    type G2P = <E as PairingEngine>::G2Projective;
+    type Fr = <E as PairingEngine>::Fr;
+
+    fn from_hex(s: &str) -> Hash {
+        let mut h = [0u8; 32];
+        for i in 0..32 {
+            h[i] = u8::from_str_radix(&s[2*i..2*i+2], 16).unwrap();
+        }
+        h
+    }
+
+    #[test]
+    fn transcript_kat() {
+        let mut t = Transcript::new(b"test");
+        t.append_message(b"msg", b"hello");
+        t.append_u64(b"n", 42);
+        let (hash, c) = t.challenge::<Fr>();
+        assert_eq!(hash, from_hex("40fa8a5cdc80de113c63e270d1d2f21465dff7c00d1e88259d294e7ae97e6eb8"));
+        assert_eq!(c, Fr::from_str("35480077815150980018186829959120752479544344199293284943774541412371743031695").unwrap());
+    }
+
+    #[test]
+    fn dleq_challenge_kat() {
+        let g1 = <E as PairingEngine>::G1Affine::prime_subgroup_generator();
+        let g2 = <E as PairingEngine>::G2Affine::prime_subgroup_generator();
+        let (hash, c) = Dleq::<G1P, G2P, Fr>::challenge(
+            &DbsContext::<E>::sharing_transcript(0, 1, 0), 
+            &g1, &g1, &g2, &g2, &g1, &g2
+        );
+        assert_eq!(hash, from_hex("4d0b5eea9ea94dee518165dd3bf5c1248b655d332db619ca13abd35bb36fbe3e"));
+        assert_eq!(c, Fr::from_str("12346272826408682576875921430973029461814409412602483483990679118431747679591").unwrap());
+
+        let (hash, c) = SingleDleq::<G1P, Fr>::challenge(
+            &DbsContext::<E>::secret_transcript(3, 1), 
+            &g1, &g1, &g1
+        );
+        assert_eq!(hash, from_hex("5ce4fd2bfba8cacdf4cb215fd119c5eb60be42b38661373d81c3c697374cd568"));
+        assert_eq!(c, Fr::from_str("1722793248435173591107336356121865992832635405761473252187303293370131146783").unwrap());
+    }
+
+    #[test]
+    fn dleq_context_binding() {
+        let mut rng = std_rng();
+        let dss_kpair = crate::DSSSecretKey::generate_ed25519();
+        let dss_pk = dss_kpair.public();
+        let kpair = Keypair::<E>::generate_keypair(&mut rng);
+        let s = Scalar::<E>::rand(&mut rng);
+        let g1 = <E as PairingEngine>::G1Affine::prime_subgroup_generator().into_projective();
+        let x = g1.mul(s.into_repr());
+        let y = kpair.1.mul(s.into_repr());
+
+        let t = DbsContext::<E>::sharing_transcript(1, 1, 2);
+        let pi = Dleq::<G1P, G2P, Fr>::prove(&t, &s, &g1, &x, &kpair.1, &y, ProofMode::Signed, &dss_kpair, &mut rng);
+        assert_eq!(None, Dleq::verify(&t, &pi, &g1, &x, &kpair.1, &y, ProofMode::Signed, &dss_pk));
+        // The same proof is not valid for another dealer, another epoch, another index or another kind of proof
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            Dleq::verify(&DbsContext::<E>::sharing_transcript(0, 1, 2), &pi, &g1, &x, &kpair.1, &y, ProofMode::Signed, &dss_pk));
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            Dleq::verify(&DbsContext::<E>::sharing_transcript(1, 2, 2), &pi, &g1, &x, &kpair.1, &y, ProofMode::Signed, &dss_pk));
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            Dleq::verify(&DbsContext::<E>::sharing_transcript(1, 1, 3), &pi, &g1, &x, &kpair.1, &y, ProofMode::Signed, &dss_pk));
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            Dleq::verify(&DbsContext::<E>::decryption_transcript(1, 2), &pi, &g1, &x, &kpair.1, &y, ProofMode::Signed, &dss_pk));
+
+        let t = DbsContext::<E>::secret_transcript(1, 1);
+        let pi = SingleDleq::<G1P, Fr>::prove(&t, &s, &g1, &x, ProofMode::Signed, &dss_kpair, &kpair.0, &kpair.1, &mut rng);
+        assert_eq!(None, pi.key_proof);
+        assert_eq!(None, SingleDleq::verify(&t, &pi, &g1, &x, ProofMode::Signed, &dss_pk, &kpair.1));
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            SingleDleq::verify(&DbsContext::<E>::secret_transcript(2, 1), &pi, &g1, &x, ProofMode::Signed, &dss_pk, &kpair.1));
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            SingleDleq::verify(&DbsContext::<E>::secret_transcript(1, 2), &pi, &g1, &x, ProofMode::Signed, &dss_pk, &kpair.1));
+        // A signed proof is not accepted as a signature of knowledge
+        assert_eq!(Some(DbsError::InvalidKeyProof), 
+            SingleDleq::verify(&t, &pi, &g1, &x, ProofMode::SignatureOfKnowledge, &dss_pk, &kpair.1));
+
+        // A signature of knowledge is bound to the prover's PVSS key, and needs its secret key
+        let pi = SingleDleq::<G1P, Fr>::prove(&t, &s, &g1, &x, ProofMode::SignatureOfKnowledge, &dss_kpair, &kpair.0, &kpair.1, &mut rng);
+        assert_eq!(None, SingleDleq::verify(&t, &pi, &g1, &x, ProofMode::SignatureOfKnowledge, &dss_pk, &kpair.1));
+        let other = Keypair::<E>::generate_keypair(&mut rng);
+        assert_eq!(Some(DbsError::InvalidChallenge), 
+            SingleDleq::verify(&t, &pi, &g1, &x, ProofMode::SignatureOfKnowledge, &dss_pk, &other.1));
+        let forged = SingleDleq::<G1P, Fr>::prove(&t, &s, &g1, &x, ProofMode::SignatureOfKnowledge, &dss_kpair, &other.0, &kpair.1, &mut rng);
+        assert_eq!(Some(DbsError::InvalidKeyProof), 
+            SingleDleq::verify(&t, &forged, &g1, &x, ProofMode::SignatureOfKnowledge, &dss_pk, &kpair.1));
+    }
+}
+
+#[cfg(test)]
+mod beacon_tests {
+    use crate::{BEACON_OUTPUT_DOMAIN, Beacon, Scalar, std_rng};
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::{AffineCurve, PairingEngine};
+    use ark_ff::PrimeField;
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::UniformRand;
+    use rand::RngCore;
+    use sha2::{Digest, Sha256};
+
+    type E = Bls12_381;
+
+    fn rand_beacon() -> Beacon<E> {
+        let mut rng = std_rng();
+        let h1 = <E as PairingEngine>::G1Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let value = <E as PairingEngine>::G2Affine::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        Beacon{
+            beacon: E::pairing(h1, value),
+            value,
+        }
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn labelled(buf: &mut Vec<u8>, label: &[u8], msg: &[u8]) {
+        buf.extend_from_slice(&(label.len() as u64).to_le_bytes());
+        buf.extend_from_slice(label);
+        buf.extend_from_slice(&(msg.len() as u64).to_le_bytes());
+        buf.extend_from_slice(msg);
+    }
+
+    #[test]
+    fn test_derivation() {
+        let b = rand_beacon();
+
+        // An external verifier only needs the GT element and the specification
+        let mut gt = Vec::new();
+        b.beacon.serialize(&mut gt).unwrap();
+        let mut buf = Vec::new();
+        labelled(&mut buf, b"protocol", b"optrand-v1");
+        labelled(&mut buf, b"domain", BEACON_OUTPUT_DOMAIN);
+        labelled(&mut buf, b"gt", &gt);
+        let seed = Sha256::digest(&buf);
+        let mut expected = Vec::new();
+        for i in 0..4u64 {
+            let mut hasher = Sha256::new();
+            hasher.update(&seed);
+            hasher.update(&i.to_le_bytes());
+            expected.extend_from_slice(&hasher.finalize());
+        }
+
+        assert_eq!(gt, b.canonical_bytes());
+        assert_eq!(&seed[..], &b.seed()[..]);
+        assert_eq!(&expected[..32], &b.to_bytes32()[..]);
+        assert_eq!(&expected[..64], &b.to_bytes64()[..]);
+
+        // The stream does not depend on how it is read
+        let mut stream = b.stream();
+        let mut out = vec![0u8; 100];
+        stream.fill_bytes(&mut out[..7]);
+        stream.fill_bytes(&mut out[7..40]);
+        stream.fill_bytes(&mut out[40..]);
+        assert_eq!(&expected[..100], &out[..]);
+        assert_eq!(u64::from_le_bytes([expected[100], expected[101], expected[102], expected[103], 
+            expected[104], expected[105], expected[106], expected[107]]), stream.next_u64());
+    }
+
+    /// A known answer vector, computed independently from the specification of the derivation
+    /// The beacon is not a pairing, so that the vector only depends on the encoding of GT
+    #[test]
+    fn test_known_answer() {
+        use ark_bls12_381::{Fq, Fq2, Fq6, Fq12};
+        let fq2 = |a: u64| Fq2::new(Fq::from(a), Fq::from(a + 1));
+        let b = Beacon::<E> {
+            beacon: Fq12::new(Fq6::new(fq2(1), fq2(3), fq2(5)), Fq6::new(fq2(7), fq2(9), fq2(11))),
+            value: <E as PairingEngine>::G2Affine::prime_subgroup_generator().into_projective(),
+        };
+        assert_eq!(hex(&b.seed()), "6dc6701ca87da576e283feb18fdcc66f0e423d86e58a001a18d1cad6467bf459");
+        assert_eq!(hex(&b.to_bytes32()), "1d0146144a10412c662577c50f757d3cf300c4894d65c69fd1005b0f65340729");
+    }
+
+    #[test]
+    fn test_derivation_after_transfer() {
+        let b = rand_beacon();
+        let b2: Beacon<E> = bincode::deserialize(&bincode::serialize(&b).unwrap()).unwrap();
+        assert_eq!(b.to_bytes64()[..], b2.to_bytes64()[..]);
+        assert_ne!(b.to_bytes32(), rand_beacon().to_bytes32());
+    }
+}
+
+#[cfg(test)]
+mod randomness_tests {
+    use crate::randomness::{Randomness, derive_seed};
+    use crate::hash::Hash;
+
+    fn seed() -> Hash {
+        let mut beacon_seed = [0u8; 32];
+        for i in 0..32 {
+            beacon_seed[i] = i as u8;
+        }
+        derive_seed(&beacon_seed, 7, b"test-app")
+    }
+
+    #[test]
+    fn test_seed_vector() {
+        let expected = "233c7e66d136ac0b445be6ade32eaf0bf74e556360de14e78d441809eaba0850";
+        let hex: String = seed().iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(expected, hex);
+    }
+
+    #[test]
+    fn test_uniform_vectors() {
+        let mut r = Randomness::from_seed(seed());
+        let draws: Vec<_> = (0..8).map(|_| r.uniform(10)).collect();
+        assert_eq!(vec![3, 8, 8, 6, 2, 3, 0, 6], draws);
+
+        // For n = 3*2^62, every draw below 2^62 is rejected; the third draw from the stream is one of them
+        let mut r = Randomness::from_seed(seed());
+        let draws: Vec<_> = (0..3).map(|_| r.uniform(3 << 62)).collect();
+        assert_eq!(vec![10181282890821894343, 11980737576138749068, 1366378176341815046], draws);
+
+        let mut r = Randomness::from_seed(seed());
+        assert_eq!(143, r.range(100, 200));
+    }
+
+    #[test]
+    fn test_shuffle_vectors() {
+        let mut r = Randomness::from_seed(seed());
+        assert_eq!(vec![2, 8, 5, 7, 9, 4, 0, 6, 1, 3], r.permutation(10));
+
+        let mut r = Randomness::from_seed(seed());
+        let mut items = vec!['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j'];
+        r.shuffle(&mut items);
+        assert_eq!(vec!['c', 'i', 'f', 'h', 'j', 'e', 'a', 'g', 'b', 'd'], items);
+    }
+
+    #[test]
+    fn test_committee_vectors() {
+        let mut r = Randomness::from_seed(seed());
+        assert_eq!(vec![43, 47, 74, 51, 26], r.committee(100, 5));
+
+        let mut r = Randomness::from_seed(seed());
+        let mut everyone = r.committee(10, 10);
+        everyone.sort();
+        assert_eq!((0..10).collect::<Vec<_>>(), everyone);
+    }
+
+    #[test]
+    fn test_weighted_vectors() {
+        let mut r = Randomness::from_seed(seed());
+        let picks: Vec<_> = (0..8).map(|_| r.weighted(&[1, 2, 3, 4])).collect();
+        assert_eq!(vec![2, 3, 3, 3, 1, 2, 0, 3], picks);
+
+        // Zero weights are never picked
+        let mut r = Randomness::from_seed(seed());
+        assert_eq!(true, (0..100).all(|_| r.weighted(&[0, 5, 0]) == 1));
+    }
+
+    #[test]
+    fn test_labels_are_independent() {
+        let beacon_seed = [1u8; 32];
+        assert_ne!(derive_seed(&beacon_seed, 1, b"a"), derive_seed(&beacon_seed, 1, b"b"));
+        assert_ne!(derive_seed(&beacon_seed, 1, b"a"), derive_seed(&beacon_seed, 2, b"a"));
+    }
+}
+
+#[cfg(test)]
+mod generator_tests {
+    use crate::{DbsContext, DbsError, Keypair, Scalar, check_generators, derive_h1_generator, derive_h2_generator, std_rng};
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::{PairingEngine, ProjectiveCurve};
+    use ark_ff::{PrimeField, ToBytes, Zero};
+    use ark_std::UniformRand;
+
+    type E = Bls12_381;
+
+    fn check_derivation<E: PairingEngine>() {
+        let h1 = derive_h1_generator::<E>(b"chain-1");
+        let h2 = derive_h2_generator::<E>(b"chain-1");
+        // The generators are deterministic, non-trivial, and in the prime order subgroups
+        assert_eq!(h1, derive_h1_generator::<E>(b"chain-1"));
+        assert_eq!(h2, derive_h2_generator::<E>(b"chain-1"));
+        assert_eq!(false, h1.is_zero());
+        assert_eq!(false, h2.is_zero());
+        assert_eq!(true, h1.mul(E::Fr::characteristic()).is_zero());
+        assert_eq!(true, h2.mul(E::Fr::characteristic()).is_zero());
+
+        assert_eq!(true, check_generators::<E>(b"chain-1", &h1, &h2));
+        assert_eq!(false, check_generators::<E>(b"chain-2", &h1, &h2));
+        assert_ne!(h1, derive_h1_generator::<E>(b"chain-2"));
+    }
+
+    #[test]
+    fn test_derivation() {
+        check_derivation::<Bls12_381>();
+        check_derivation::<Bn254>();
+    }
+
+    /// Known answer vectors for BLS12-381, computed independently from the specification in `hash_to_curve`
+    /// They are the `ToBytes` encodings of the affine points, as in the transcripts
+    #[test]
+    fn test_known_answer() {
+        let hex = |bytes: Vec<u8>| -> String { bytes.iter().map(|b| format!("{:02x}", b)).collect() };
+        let mut h1 = Vec::new();
+        derive_h1_generator::<E>(b"chain-1").into_affine().write(&mut h1).unwrap();
+        assert_eq!(hex(h1), "5b004f027ca900fd4e8fe0a6849b765b4ce6b78b1a32c92099c8fb5c81f15527c7eeda8726aadb5f4acb9cd813d96308d1aecf2671abbc4527392bc12175b77aaf848087f84ced3de298ead6bd19879d14db58276425d64cb8013cf7cec6720400");
+        let mut h2 = Vec::new();
+        derive_h2_generator::<E>(b"chain-1").into_affine().write(&mut h2).unwrap();
+        assert_eq!(hex(h2), "5a8143ee2bc665630981fc7d08049dd11246a141379bd14d7fc76c0562fd9440cef5a3e2090091acddce5442ffb667076a546bc131cbadd1137e2536d885f29d3371cd70024bc93df8bfa43da661b8ac29560dd13b853b713ddac434f5e268064549d8149bdfc96fccd2d58ac3c3167ba90e3359f12da04c03a4ce17aadf690267005df052587bc3bf41474126d47f080717626c0043682ad3f1750cf0f0830aa1eb21d26499cc44b0391ef248eb284f7912a6dfc4f62691f5fa47ea992abc1800");
+    }
+
+    #[test]
+    fn test_context_generators() {
+        let mut rng = std_rng();
+        let n = 4;
+        let t = 1;
+        let mut public_keys = Vec::new();
+        let mut secret_keys = Vec::new();
+        for _i in 0..n {
+            let kpair = Keypair::<E>::generate_keypair(&mut rng);
+            secret_keys.push(kpair.0);
+            public_keys.push(kpair.1);
+        }
+        let ctx = DbsContext::<E>::new_from_seed(&mut rng, "chain-1", n, t, 0, public_keys.clone(), secret_keys[0]);
+        assert_eq!(Some("chain-1"), ctx.generator_seed());
+        assert_eq!(None, ctx.check_generators());
+
+        // The seed survives a round trip through a config file
+        let bytes = crate::export_secret(|| bincode::serialize(&ctx)).unwrap();
+        let mut ctx2: DbsContext<E> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(None, ctx2.check_generators());
+
+        // Generators that do not match the seed
+        ctx2.h1p = ctx2.h1p.double();
+        assert_eq!(Some(DbsError::GeneratorMismatch), ctx2.check_generators());
+
+        // Random generators have a trapdoor
+        let h1 = crate::rand_h1_generator::<_, E>(&mut rng);
+        let h2 = <E as PairingEngine>::G2Projective::prime_subgroup_generator().mul(Scalar::<E>::rand(&mut rng).into_repr());
+        let ctx3 = DbsContext::<E>::new(&mut rng, h2, h1, n, t, 0, public_keys, secret_keys[0]);
+        assert_eq!(Some(DbsError::MissingGeneratorSeed), ctx3.check_generators());
+    }
+}
+
+#[cfg(test)]
+mod precompute_tests {
+    use super::fixtures::dbs_contexts;
+    use crate::{DbsContext, PrecomputeConfig, std_rng};
+    use ark_bls12_381::{Bls12_381, Fq, G1Affine};
+    use ark_ec::{ProjectiveCurve, msm::FixedBaseMSM};
+    use once_cell::sync::OnceCell;
+    use rand::Rng;
+    use std::sync::Arc;
+
+    type E = Bls12_381;
+
+    fn contexts(n: usize, t: usize) -> (Vec<DbsContext<E>>, Vec<crate::DSSSecretKey>) {
+        let (ctx, dss, _) = dbs_contexts(n, t, &mut std_rng());
+        (ctx, dss)
+    }
+
+    fn temp_file() -> String {
+        let id: u64 = std_rng().gen();
+        std::env::temp_dir()
+            .join(format!("optrand-precompute-{}.bin", id))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_lazy_tables() {
+        let (ctx, dss) = contexts(7, 3);
+        assert_eq!(true, ctx[0].optimizations.g1_table.get().is_none());
+        assert_eq!(true, ctx[0].optimizations.pk_tables.iter().all(|t| t.get().is_none()));
+
+        let pvec = ctx[0].generate_shares(1, &dss[0], &mut std_rng());
+        assert_eq!(true, ctx[0].optimizations.g1_table.get().is_some());
+        assert_eq!(true, ctx[0].optimizations.pk_tables.iter().all(|t| t.get().is_some()));
+        assert_eq!(None, ctx[1].verify_sharing(0, &pvec, &dss[0].public()));
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let (mut ctx, dss) = contexts(7, 3);
+        let config = PrecomputeConfig {
+            cache_path: None,
+            memory_budget: Some(0),
+        };
+        ctx[0].init_with(&config, &mut std_rng());
+        assert_eq!(0, ctx[0].optimizations.pk_table_limit);
+
+        // Encryptions without tables are the same
+        let pvec = ctx[0].generate_shares(1, &dss[0], &mut std_rng());
+        assert_eq!(true, ctx[0].optimizations.pk_tables.iter().all(|t| t.get().is_none()));
+        assert_eq!(None, ctx[1].verify_sharing(0, &pvec, &dss[0].public()));
+    }
+
+    #[test]
+    fn test_cache() {
+        let path = temp_file();
+        let config = PrecomputeConfig {
+            cache_path: Some(path.clone()),
+            memory_budget: None,
+        };
+        let (mut ctx, dss) = contexts(7, 3);
+        let mut rng = std_rng();
+
+        // The first node builds the tables and writes the cache
+        ctx[0].init_with(&config, &mut rng);
+        assert_eq!(true, std::path::Path::new(&path).exists());
+        assert_eq!(true, ctx[0].optimizations.pk_tables.iter().all(|t| t.get().is_some()));
+
+        // The second node loads them
+        let mut opt = ctx[1].optimizations.clone();
+        assert_eq!(true, opt.load(&path).unwrap());
+        assert_eq!(ctx[0].optimizations.g1_table.get(), opt.g1_table.get());
+        assert_eq!(ctx[0].optimizations.g2_table.get(), opt.g2_table.get());
+        assert_eq!(ctx[0].optimizations.pk_tables[6].get(), opt.pk_tables[6].get());
+
+        ctx[1].init_with(&config, &mut rng);
+        let pvec = ctx[1].generate_shares(1, &dss[1], &mut rng);
+        assert_eq!(None, ctx[0].verify_sharing(1, &pvec, &dss[1].public()));
+
+        // The cache is stale for other parameters
+        let (other, _) = contexts(7, 3);
+        assert_eq!(false, other[0].optimizations.clone().load(&path).unwrap());
+
+        // A corrupted cache is not loaded
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        std::fs::write(&path, bytes).unwrap();
+        assert_eq!(true, ctx[2].optimizations.clone().load(&path).is_err());
+
+        // Neither is a well formed cache whose tables are for other generators
+        let mut forged = ctx[0].optimizations.clone();
+        let g1 = forged.g1p.double();
+        forged.g1_table = Arc::new(OnceCell::from(FixedBaseMSM::get_window_table(forged.scalar_bits, forged.window_size, g1)));
+        forged.save(&path).unwrap();
+        assert_eq!(true, ctx[2].optimizations.clone().load(&path).is_err());
+
+        // Nor a cache with a point outside the subgroup, even with the right hash
+        let mut forged = ctx[0].optimizations.clone();
+        let mut g1_table = forged.g1_table().clone();
+        g1_table[0][1] = (1u64..)
+            .filter_map(|x| G1Affine::get_point_from_x(Fq::from(x), true))
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+        forged.g1_table = Arc::new(OnceCell::from(g1_table));
+        forged.save(&path).unwrap();
+        assert_eq!(true, ctx[2].optimizations.clone().load(&path).is_err());
+
+        // The cache of the first node is loaded again after it is rebuilt
+        ctx[0].init_with(&config, &mut rng);
+        assert_eq!(true, ctx[2].optimizations.clone().load(&path).unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod lagrange_tests {
+    use crate::{Polynomial, Scalar, lagrange_coefficients, lagrange_coefficients_for, precomputes::LagrangeCache, std_rng};
+    use ark_bls12_381::Bls12_381;
+    use ark_ff::{Field, One, Zero};
+    use ark_poly::{Polynomial as Poly, UVPolynomial};
+    use ark_std::UniformRand;
+    use rand::seq::SliceRandom;
+    use std::sync::Arc;
+
+    type E = Bls12_381;
+
+    #[test]
+    fn test_interpolation() {
+        let mut rng = std_rng();
+        let n = 129;
+        let t = 64;
+        let polynomial = Polynomial::<E>::rand(t, &mut rng);
+
+        // Any t+1 of the n shares
+        let mut indices: Vec<_> = (0..n).collect();
+        indices.shuffle(&mut rng);
+        indices.truncate(t+1);
+        let lagranges = lagrange_coefficients_for::<E>(&indices);
+        let secret = indices.iter()
+            .zip(lagranges.iter())
+            .fold(Scalar::<E>::zero(), |acc, (&i, l)| {
+                acc + *l * polynomial.evaluate(&Scalar::<E>::from(i as u64 + 1))
+            });
+        assert_eq!(polynomial.coeffs[0], secret);
+    }
+
+    #[test]
+    fn test_naive() {
+        let mut rng = std_rng();
+        let points: Vec<_> = (0..10).map(|_| Scalar::<E>::rand(&mut rng)).collect();
+        let naive: Vec<_> = (0..points.len()).map(|i| {
+            (0..points.len())
+                .filter(|&j| j != i)
+                .fold(Scalar::<E>::one(), |acc, j| {
+                    acc * points[j] * (points[j] - points[i]).inverse().unwrap()
+                })
+        }).collect();
+        assert_eq!(naive, lagrange_coefficients(&points));
+        assert_eq!(vec![Scalar::<E>::one()], lagrange_coefficients(&points[0..1]));
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = LagrangeCache::<Scalar<E>>::new();
+        let first = cache.get(&[4, 0, 7]);
+        assert_eq!(lagrange_coefficients_for::<E>(&[4, 0, 7]), *first);
+        // The same set in the same order is a lookup
+        assert!(Arc::ptr_eq(&first, &cache.get(&[4, 0, 7])));
+        assert_eq!(lagrange_coefficients_for::<E>(&[0, 4, 7]), *cache.get(&[0, 4, 7]));
+
+        // A full cache starts over
+        for i in 0..100 {
+            assert_eq!(lagrange_coefficients_for::<E>(&[i, i + 1]), *cache.get(&[i, i + 1]));
+        }
+        assert_eq!(*first, *cache.get(&[4, 0, 7]));
+    }
+}
+
+#[cfg(test)]
+mod scheme_tests {
+    use super::fixtures::dbs_contexts;
+    use crate::{PvssScheme, ScrapeContext, std_rng};
+    use ark_ec::ProjectiveCurve;
+    use ark_ed_on_bls12_381::EdwardsProjective;
+    use fnv::FnvHashMap as HashMap;
+
+    /// Runs an epoch of the protocol using only the PvssScheme interface
+    /// Returns the beacon reconstructed by every node
+    pub(crate) fn run_epoch<S>(ctx: &[S], dss_kpair: &[crate::DSSSecretKey], t: usize) -> Vec<S::Beacon>
+    where S: PvssScheme,
+    {
+        let mut rng = std_rng();
+        let n = ctx.len();
+        let pk_map: HashMap<_,_> = (0..n).map(|i| (i, dss_kpair[i].public())).collect();
+
+        let indices: Vec<_> = (0..t+1).collect();
+        let pvecs: Vec<_> = indices.iter().map(|&i| {
+            let pvec = ctx[i].generate_shares(1, &dss_kpair[i], &mut rng);
+            assert_eq!(None, ctx[0].verify_sharing(i, &pvec, &pk_map[&i]));
+            pvec
+        }).collect();
+        let (agg, proof) = ctx[0].aggregate(&indices, pvecs);
+
+        let decs: Vec<_> = (0..n).map(|j| {
+            assert_eq!(None, ctx[j].pverify(&agg));
+            assert_eq!(None, ctx[j].decomp_verify(&agg, &proof, &pk_map));
+            let e = ctx[j].encryption(&agg, j).unwrap();
+            let dec = ctx[j].decrypt_share(7, e, &dss_kpair[j], &mut rng);
+            assert_eq!(None, ctx[0].verify_share(j, 7, e, &dec, &pk_map[&j]));
+            Some(dec)
+        }).collect();
+
+        // There is no share for a node beyond the n nodes
+        assert!(ctx[0].encryption(&agg, n).is_none());
+
+        (0..n).map(|j| {
+            let (beacon, blamed) = ctx[j].reconstruct_optimistic(7, &agg, &decs, &pk_map);
+            assert!(blamed.is_empty());
+            let beacon = beacon.unwrap();
+            assert!(ctx[j].check_beacon(&beacon, &agg));
+            beacon
+        }).collect()
+    }
+
+    #[test]
+    fn test_dbs_scheme() {
+        let n = 7;
+        let t = 3;
+        let (ctx, dss, _) = dbs_contexts(n, t, &mut std_rng());
+
+        let beacons = run_epoch(&ctx, &dss, t);
+        for b in &beacons {
+            assert_eq!(&beacons[0], b);
+        }
+    }
+
+    #[test]
+    fn test_scrape_scheme() {
+        type G = EdwardsProjective;
+        let mut rng = std_rng();
+        let n = 7;
+        let t = 3;
+        let keypairs: Vec<_> = (0..n)
+            .map(|_| ScrapeContext::<G>::generate_keypair(&mut rng))
+            .collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let ctx: Vec<_> = (0..n).map(|i| {
+            ScrapeContext::<G>::new_from_seed(&mut rng, "chain-1", n, t, i, public_keys.clone(), keypairs[i].0)
+        }).collect();
+        let dss: Vec<_> = (0..n).map(|_| crate::DSSSecretKey::generate_ed25519()).collect();
+
+        let beacons = run_epoch(&ctx, &dss, t);
+        for b in &beacons {
+            assert_eq!(beacons[0].to_bytes32(), b.to_bytes32());
+        }
+    }
+
+    #[test]
+    fn test_scrape_bad_inputs() {
+        type G = EdwardsProjective;
+        let mut rng = std_rng();
+        let n = 4;
+        let t = 1;
+        let keypairs: Vec<_> = (0..n)
+            .map(|_| ScrapeContext::<G>::generate_keypair(&mut rng))
+            .collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let ctx: Vec<_> = (0..n).map(|i| {
+            ScrapeContext::<G>::new_from_seed(&mut rng, "chain-1", n, t, i, public_keys.clone(), keypairs[i].0)
+        }).collect();
+        let dss: Vec<_> = (0..n).map(|_| crate::DSSSecretKey::generate_ed25519()).collect();
+        let pk_map: HashMap<_,_> = (0..n).map(|i| (i, dss[i].public())).collect();
+
+        // A sharing from node 0 does not verify as a sharing from node 1
+        let pvec = ctx[0].generate_shares(1, &dss[0], &mut rng);
+        assert_eq!(None, ctx[1].verify_sharing(0, &pvec, &pk_map[&0]));
+        assert!(ctx[1].verify_sharing(1, &pvec, &pk_map[&1]).is_some());
+
+        // A tampered encryption is caught
+        let mut bad = pvec.clone();
+        bad.encs[2] = bad.encs[2].double();
+        assert!(ctx[1].verify_sharing(0, &bad, &pk_map[&0]).is_some());
+
+        // A tampered part is caught by the decomposition check
+        let pvecs = vec![pvec, ctx[1].generate_shares(1, &dss[1], &mut rng)];
+        let (agg, proof) = ctx[0].aggregate(&[0, 1], pvecs);
+        let mut bad = agg.clone();
+        bad.parts[1].encs[0] = bad.parts[1].encs[0].double();
+        assert!(ctx[2].pverify(&bad).is_some());
+
+        // A wrong decryption is blamed, and a beacon with it is rejected
+        let mut decs: Vec<_> = (0..n)
+            .map(|j| Some(ctx[j].decrypt_share(1, &agg.encs[j], &dss[j], &mut rng)))
+            .collect();
+        decs[0].as_mut().unwrap().dec = decs[0].as_ref().unwrap().dec.double();
+        let (beacon, blamed) = ctx[3].reconstruct_optimistic(1, &agg, &decs, &pk_map);
+        assert_eq!(vec![0], blamed);
+        let beacon = beacon.unwrap();
+        assert!(ctx[3].check_beacon(&beacon, &agg));
+        let mut wrong = beacon.clone();
+        wrong.shares[0] = (0, decs[0].clone().unwrap());
+        assert!(!ctx[3].check_beacon(&wrong, &agg));
+        assert_eq!(None, ctx[3].decomp_verify(&agg, &proof, &pk_map));
+    }
+}
+
+#[cfg(test)]
+mod malformed_tests {
+    use super::fixtures::dbs_contexts;
+    use crate::{AggregatePVSS, DbsContext, DbsError, DecompositionProof, PVSSVec, std_rng};
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::Zero;
+    use fnv::FnvHashMap as HashMap;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    type E = Bls12_381;
+    const ROUNDS: usize = 64;
+
+    struct Setup {
+        ctx: Vec<DbsContext<E>>,
+        pk_map: HashMap<usize, crypto_lib::PublicKey>,
+        pvec: PVSSVec<E>,
+        agg: AggregatePVSS<E>,
+        decomp: DecompositionProof<E>,
+    }
+
+    fn setup(n: usize, t: usize) -> Setup {
+        let mut rng = std_rng();
+        let (ctx, dss, pk_map) = dbs_contexts(n, t, &mut rng);
+        let pvec = ctx[0].generate_shares(1, &dss[0], &mut rng);
+        let indices: Vec<_> = (0..t+1).collect();
+        let pvecs = indices.iter().map(|&i| ctx[i].generate_shares(1, &dss[i], &mut rng)).collect();
+        let (agg, decomp) = ctx[0].aggregate(&indices, pvecs);
+        Setup { ctx, pk_map, pvec, agg, decomp }
+    }
+
+    /// Truncates, extends, or replaces a random entry of v with the identity or a copy of another entry
+    fn mutate<T: Clone>(v: &mut Vec<T>, zero: T, rng: &mut StdRng) {
+        match rng.gen_range(0..4) {
+            0 => {
+                let len = rng.gen_range(0..v.len()+1);
+                v.truncate(len);
+            }
+            1 => {
+                let extra = v.last().cloned().unwrap_or_else(|| zero.clone());
+                v.push(extra);
+            }
+            2 if !v.is_empty() => {
+                let i = rng.gen_range(0..v.len());
+                v[i] = zero;
+            }
+            _ if !v.is_empty() => {
+                let i = rng.gen_range(0..v.len());
+                let j = rng.gen_range(0..v.len());
+                v[i] = v[j].clone();
+            }
+            _ => v.clear(),
+        }
+    }
+
+    #[test]
+    fn test_malformed_sharings() {
+        let s = setup(7, 3);
+        let mut rng = StdRng::seed_from_u64(16);
+        let g1 = <E as ark_ec::PairingEngine>::G1Affine::zero();
+        for _ in 0..ROUNDS {
+            let mut pvec = s.pvec.clone();
+            match rng.gen_range(0..3) {
+                0 => mutate(&mut pvec.comms, g1, &mut rng),
+                1 => mutate(&mut pvec.encs, Zero::zero(), &mut rng),
+                _ => {
+                    let proof = pvec.proofs[0].clone();
+                    mutate(&mut pvec.proofs, proof, &mut rng)
+                }
+            }
+            // Copying an entry onto itself is the only mutation that may leave the sharing valid
+            if pvec == s.pvec {
+                continue;
+            }
+            assert!(s.ctx[1].verify_sharing(0, &pvec, &s.pk_map[&0]).is_some());
+        }
+
+        let mut pvec = s.pvec.clone();
+        pvec.comms.truncate(2);
+        assert_eq!(Some(DbsError::WrongNumberOfCommitments(2)), s.ctx[1].verify_sharing(0, &pvec, &s.pk_map[&0]));
+        let mut pvec = s.pvec.clone();
+        pvec.proofs.pop();
+        assert_eq!(Some(DbsError::WrongNumberOfProofs(6)), s.ctx[1].verify_sharing(0, &pvec, &s.pk_map[&0]));
+        let mut pvec = s.pvec.clone();
+        pvec.encs[4] = Zero::zero();
+        assert_eq!(Some(DbsError::IdentityEncryption(4)), s.ctx[1].verify_sharing(0, &pvec, &s.pk_map[&0]));
+        let mut pvec = s.pvec.clone();
+        pvec.gs = Zero::zero();
+        assert_eq!(Some(DbsError::IdentityGs), s.ctx[1].verify_sharing(0, &pvec, &s.pk_map[&0]));
+    }
+
+    #[test]
+    fn test_malformed_aggregates() {
+        let s = setup(7, 3);
+        let mut rng = StdRng::seed_from_u64(16);
+        let g1 = <E as ark_ec::PairingEngine>::G1Affine::zero();
+        for _ in 0..ROUNDS {
+            let mut agg = s.agg.clone();
+            if rng.gen() {
+                mutate(&mut agg.comms, g1, &mut rng);
+            } else {
+                mutate(&mut agg.encs, Zero::zero(), &mut rng);
+            }
+            // pverify does not check our own entry, so only look at the lengths and the other entries
+            let changed = agg.comms.len() != s.agg.comms.len() || 
+                agg.encs.len() != s.agg.encs.len() ||
+                (1..7).any(|i| agg.comms[i] != s.agg.comms[i] || agg.encs[i] != s.agg.encs[i]);
+            let res = s.ctx[0].pverify(&agg);
+            if changed {
+                assert!(res.is_some());
+            }
+            // Must not panic either
+            let _ = s.ctx[0].decomp_verify(&agg, &s.decomp, &s.pk_map);
+            let _ = s.ctx[0].reconstruct_optimistic(1, &agg, &vec![None; 9], &s.pk_map);
+        }
+
+        let mut agg = s.agg.clone();
+        agg.encs.truncate(3);
+        assert_eq!(Some(DbsError::WrongNumberOfEncryptions(3)), s.ctx[0].pverify(&agg));
+        let mut agg = s.agg.clone();
+        agg.comms[5] = g1;
+        assert_eq!(Some(DbsError::IdentityCommitment(5)), s.ctx[0].pverify(&agg));
+        let mut agg = s.agg.clone();
+        agg.comms.push(agg.comms[0].into_projective().double().into_affine());
+        assert_eq!(Some(DbsError::WrongNumberOfCommitments(8)), s.ctx[0].decomp_verify(&agg, &s.decomp, &s.pk_map));
+    }
+
+    #[test]
+    fn test_malformed_decompositions() {
+        let s = setup(7, 3);
+        let mut rng = StdRng::seed_from_u64(16);
+        for _ in 0..ROUNDS {
+            let mut decomp = s.decomp.clone();
+            match rng.gen_range(0..4) {
+                0 => mutate(&mut decomp.indices, rng.gen_range(0..20), &mut rng),
+                1 => {
+                    let proof = decomp.dleq_proof[0].clone();
+                    mutate(&mut decomp.dleq_proof, proof, &mut rng)
+                }
+                2 => mutate(&mut decomp.gs_vec, Zero::zero(), &mut rng),
+                _ => {
+                    let i = rng.gen_range(0..decomp.indices.len());
+                    decomp.indices[i] = rng.gen();
+                }
+            }
+            let same = decomp.indices == s.decomp.indices && 
+                decomp.dleq_proof == s.decomp.dleq_proof && 
+                decomp.gs_vec == s.decomp.gs_vec;
+            if same {
+                continue;
+            }
+            assert!(s.ctx[2].decomp_verify(&s.agg, &decomp, &s.pk_map).is_some());
+        }
+
+        let mut decomp = s.decomp.clone();
+        decomp.indices[1] = 7;
+        assert_eq!(Some(DbsError::IndexOutOfRange(7)), s.ctx[2].decomp_verify(&s.agg, &decomp, &s.pk_map));
+        let mut decomp = s.decomp.clone();
+        decomp.indices[1] = usize::MAX;
+        assert_eq!(Some(DbsError::IndexOutOfRange(usize::MAX)), s.ctx[2].decomp_verify(&s.agg, &decomp, &s.pk_map));
+        let mut decomp = s.decomp.clone();
+        decomp.indices[2] = decomp.indices[0];
+        assert_eq!(Some(DbsError::DuplicateIndex(0)), s.ctx[2].decomp_verify(&s.agg, &decomp, &s.pk_map));
+        let mut decomp = s.decomp.clone();
+        decomp.gs_vec.pop();
+        assert_eq!(Some(DbsError::DecompositionLengthMismatch), s.ctx[2].decomp_verify(&s.agg, &decomp, &s.pk_map));
+        let mut pk_map = s.pk_map.clone();
+        pk_map.remove(&3);
+        assert_eq!(Some(DbsError::UnknownNode(3)), s.ctx[2].decomp_verify(&s.agg, &s.decomp, &pk_map));
+    }
+}
+
+#[cfg(test)]
+mod seed_tests {
+    use crate::{DbsContext, Keypair, rng_for, seeded_rng};
+    use ark_bls12_381::Bls12_381;
+    use rand::RngCore;
+
+    type E = Bls12_381;
+
+    fn sharing_bytes(seed: u64) -> Vec<u8> {
+        let (n, t) = (4, 1);
+        let mut rng = seeded_rng(seed, 0, b"setup");
+        let keypairs: Vec<_> = (0..n).map(|_| Keypair::<E>::generate_keypair(&mut rng)).collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|k| k.1).collect();
+        let ctx = DbsContext::<E>::new_from_seed(&mut rng, "chain-1", n, t, 0, public_keys, keypairs[0].0);
+        let mut dss_bytes = [0u8; 32];
+        rng.fill_bytes(&mut dss_bytes);
+        let dss_sk = crypto_lib::ed25519::SecretKey::from_bytes(&mut dss_bytes).unwrap();
+        let dss = crate::DSSSecretKey::new(crypto_lib::Keypair::Ed25519(dss_sk.into()));
+        let pvec = ctx.generate_shares(1, &dss, &mut seeded_rng(seed, 0, b"sharing"));
+        bincode::serialize(&pvec).unwrap()
+    }
+
+    #[test]
+    fn test_seeded_rng() {
+        let mut a = seeded_rng(7, 1, b"label");
+        let mut b = seeded_rng(7, 1, b"label");
+        assert_eq!(a.next_u64(), b.next_u64());
+        assert_ne!(seeded_rng(7, 1, b"label").next_u64(), seeded_rng(8, 1, b"label").next_u64());
+        assert_ne!(seeded_rng(7, 1, b"label").next_u64(), seeded_rng(7, 2, b"label").next_u64());
+        assert_ne!(seeded_rng(7, 1, b"label").next_u64(), seeded_rng(7, 1, b"other").next_u64());
+        assert_eq!(seeded_rng(7, 1, b"label").next_u64(), rng_for(Some(7), 1, b"label").next_u64());
+    }
+
+    #[test]
+    fn test_seeded_sharing() {
+        // The signature over the sharing is deterministic (ed25519), so the bytes must match exactly
+        assert_eq!(sharing_bytes(42), sharing_bytes(42));
+        assert_ne!(sharing_bytes(42), sharing_bytes(43));
+    }
+}
+
+#[cfg(test)]
+mod rotation_tests {
+    use super::fixtures::dbs_contexts;
+    use super::scheme_tests::run_epoch;
+    use crate::{DbsContext, DbsError, export_secret, std_rng};
+    use ark_bls12_381::Bls12_381;
+    use std::sync::Arc;
+
+    type E = Bls12_381;
+
+    #[test]
+    fn test_key_rotation() {
+        let mut rng = std_rng();
+        let (n, t) = (4, 1);
+        let (mut ctx, dss, pk_map) = dbs_contexts(n, t, &mut std_rng());
+
+        // A sharing and an aggregate from before the rotation
+        let indices: Vec<_> = (0..t+1).collect();
+        let pvecs: Vec<_> = indices.iter().map(|&i| ctx[i].generate_shares(1, &dss[i], &mut rng)).collect();
+        let old_pvec = pvecs[0].clone();
+        let (old_agg, _) = ctx[0].aggregate(&indices, pvecs);
+
+        let switch_epoch = ctx[1].min_switch_epoch(5);
+        let rot = ctx[1].announce_rotation(switch_epoch, &mut rng);
+        assert_eq!(Some(switch_epoch), ctx[1].pending_rotation());
+        for c in ctx.iter_mut() {
+            assert_eq!(None, c.verify_rotation(&rot));
+            assert_eq!(None, c.apply_rotations(&[rot.clone()]));
+            assert_eq!(1, c.key_version());
+        }
+        assert_eq!(None, ctx[1].pending_rotation());
+
+        // The tables that the rotation did not change are shared with the old context
+        let (old_opt, new_opt) = (&ctx[2].for_key_version(0).unwrap().optimizations, &ctx[2].optimizations);
+        assert!(Arc::ptr_eq(&old_opt.g1_table, &new_opt.g1_table));
+        assert!(Arc::ptr_eq(&old_opt.g2_table, &new_opt.g2_table));
+        assert!(Arc::ptr_eq(&old_opt.pk_tables[0], &new_opt.pk_tables[0]));
+        assert!(!Arc::ptr_eq(&old_opt.pk_tables[1], &new_opt.pk_tables[1]));
+
+        // The old sharings are checked and decrypted with the old keys
+        assert_eq!(None, ctx[2].verify_sharing(0, &old_pvec, &pk_map[&0]));
+        assert_eq!(None, ctx[2].pverify(&old_agg));
+        let decs: Vec<_> = (0..n).map(|j| {
+            let old_ctx = ctx[j].for_key_version(0).unwrap();
+            Some(old_ctx.decrypt_share(9, &old_agg.encs[j], &dss[j], &mut rng))
+        }).collect();
+        let (beacon, blamed) = ctx[3].reconstruct_optimistic(9, &old_agg, &decs, &pk_map);
+        assert!(blamed.is_empty());
+        assert!(beacon.is_some());
+
+        // The new sharings use the new keys
+        let beacons = run_epoch(&ctx, &dss, t);
+        for b in &beacons {
+            assert_eq!(&beacons[0], b);
+        }
+
+        // The old keys are kept for t epochs after the switch, and while some aggregate needs them
+        ctx[2].retire_keys(switch_epoch + t as u64, 1);
+        assert!(ctx[2].for_key_version(0).is_some());
+        ctx[2].retire_keys(switch_epoch + t as u64 + 1, 0);
+        assert!(ctx[2].for_key_version(0).is_some());
+        ctx[2].retire_keys(switch_epoch + t as u64 + 1, 1);
+        assert!(ctx[2].for_key_version(0).is_none());
+        assert_eq!(Some(DbsError::UnknownKeyVersion(0)), ctx[2].verify_sharing(0, &old_pvec, &pk_map[&0]));
+        assert_eq!(Some(DbsError::UnknownKeyVersion(0)), ctx[2].pverify(&old_agg));
+    }
+
+    #[test]
+    fn test_bad_rotations() {
+        let mut rng = std_rng();
+        let (n, t) = (4, 1);
+        let (mut ctx, _dss, _pk_map) = dbs_contexts(n, t, &mut std_rng());
+
+        // Only the current key of a node can rotate it
+        let mut rot = ctx[1].announce_rotation(10, &mut rng);
+        rot.id = 2;
+        assert_eq!(Some(DbsError::InvalidRotation(2)), ctx[0].verify_rotation(&rot));
+        rot.id = n;
+        assert_eq!(Some(DbsError::IndexOutOfRange(n)), ctx[0].verify_rotation(&rot));
+
+        // The endorsement covers the switch epoch and the new key
+        let mut rot = ctx[1].announce_rotation(10, &mut rng);
+        rot.switch_epoch = 11;
+        assert_eq!(Some(DbsError::InvalidRotation(1)), ctx[0].verify_rotation(&rot));
+        let other = ctx[2].announce_rotation(10, &mut rng);
+        let mut rot = ctx[1].announce_rotation(10, &mut rng);
+        rot.new_pk = other.new_pk;
+        assert_eq!(Some(DbsError::InvalidRotation(1)), ctx[0].verify_rotation(&rot));
+
+        // Rotations applied together must be distinct and switch in the same epoch
+        let rot1 = ctx[1].announce_rotation(10, &mut rng);
+        let rot2 = ctx[2].announce_rotation(11, &mut rng);
+        assert_eq!(Some(DbsError::SwitchEpochMismatch), ctx[0].apply_rotations(&[rot1.clone(), rot2]));
+        assert_eq!(Some(DbsError::DuplicateIndex(1)), ctx[0].apply_rotations(&[rot1.clone(), rot1.clone()]));
+        assert_eq!(0, ctx[0].key_version());
+
+        // Announcing again replaces the pending key, so the earlier rotation leaves me without my key
+        let rot3 = ctx[1].announce_rotation(10, &mut rng);
+        assert_ne!(rot1.new_pk, rot3.new_pk);
+        assert_eq!(Some(DbsError::MissingRotationKey), ctx[1].apply_rotations(&[rot1.clone()]));
+        assert_eq!(1, ctx[1].key_version());
+        assert_eq!(None, ctx[0].apply_rotations(&[rot1]));
+        assert_eq!(1, ctx[0].key_version());
+    }
+
+    #[test]
+    fn test_rotation_after_restart() {
+        let mut rng = std_rng();
+        let (n, t) = (4, 1);
+        let (mut ctx, dss, _) = dbs_contexts(n, t, &mut std_rng());
+
+        // The announced key is exported with the context, so restarting before the switch keeps it
+        let rot = ctx[1].announce_rotation(10, &mut rng);
+        let bytes = export_secret(|| bincode::serialize(&ctx[1])).unwrap();
+        let mut restarted: DbsContext<E> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(Some(10), restarted.pending_rotation());
+        restarted.init(&mut rng);
+        ctx[1] = restarted;
+        for c in ctx.iter_mut() {
+            assert_eq!(None, c.apply_rotations(&[rot.clone()]));
+        }
+        let beacons = run_epoch(&ctx, &dss, t);
+        for b in &beacons {
+            assert_eq!(&beacons[0], b);
+        }
+
+        // The retired keys are exported too, so restarting during the transition keeps them
+        let bytes = export_secret(|| bincode::serialize(&ctx[1])).unwrap();
+        let mut restarted: DbsContext<E> = bincode::deserialize(&bytes).unwrap();
+        restarted.init(&mut rng);
+        assert_eq!(1, restarted.key_version());
+        let old = restarted.for_key_version(0).unwrap();
+        let prev = ctx[1].for_key_version(0).unwrap();
+        assert_eq!(prev.public_keys, old.public_keys);
+        assert_eq!(prev.my_key.expose_secret(), old.my_key.expose_secret());
+    }
+}
+
+#[cfg(test)]
+mod bls_tests {
+    use crate::{BlsKeypair, DbsError, KeyPop, bls_aggregate, bls_aggregate_keys, bls_verify, std_rng};
+    use ark_bls12_381::Bls12_381;
+
+    type E = Bls12_381;
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = std_rng();
+        let (kp, pop) = BlsKeypair::<E>::generate_with_pop(0, &mut rng);
+        assert_eq!(None, pop.verify_bls(0, &kp.pk));
+        let sig = kp.sign(b"msg");
+        assert!(bls_verify::<E>(b"msg", &sig, &kp.pk));
+        assert!(!bls_verify::<E>(b"other msg", &sig, &kp.pk));
+
+        let (kp2, _) = BlsKeypair::<E>::generate_with_pop(1, &mut rng);
+        assert!(!bls_verify::<E>(b"msg", &sig, &kp2.pk));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let mut rng = std_rng();
+        let n = 7;
+        let kps: Vec<_> = (0..n).map(|i| BlsKeypair::<E>::generate_with_pop(i, &mut rng).0).collect();
+        let sigs: Vec<_> = kps.iter().map(|kp| kp.sign(b"msg")).collect();
+
+        let signers = [0, 2, 3, 6];
+        let sig = bls_aggregate::<E, _>(signers.iter().map(|&i| &sigs[i]));
+        let apk = bls_aggregate_keys::<E, _>(signers.iter().map(|&i| &kps[i].pk));
+        assert!(bls_verify::<E>(b"msg", &sig, &apk));
+
+        // Claiming an extra signer, or dropping one, fails
+        let apk2 = apk + kps[1].pk;
+        assert!(!bls_verify::<E>(b"msg", &sig, &apk2));
+        let apk3 = apk - kps[0].pk;
+        assert!(!bls_verify::<E>(b"msg", &sig, &apk3));
+
+        // Nobody signed
+        let empty = bls_aggregate_keys::<E, _>(std::iter::empty());
+        assert!(!bls_verify::<E>(b"msg", &bls_aggregate::<E, _>(std::iter::empty()), &empty));
+    }
+
+    #[test]
+    fn test_pops_are_separate() {
+        let mut rng = std_rng();
+        let (kp, pop) = BlsKeypair::<E>::generate_with_pop(2, &mut rng);
+        assert_eq!(Some(DbsError::InvalidKeyPop(3)), pop.verify_bls(3, &kp.pk));
+        // A PVSS proof of possession is not a BLS one, and vice versa
+        assert_eq!(Some(DbsError::InvalidKeyPop(2)), pop.verify(2, &kp.pk));
+        let pvss_pop = KeyPop::<E>::prove_in(&KeyPop::<E>::transcript(2), kp.secret().expose_secret(), &kp.pk, &mut rng);
+        assert_eq!(Some(DbsError::InvalidKeyPop(2)), pvss_pop.verify_bls(2, &kp.pk));
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod parallel_tests {
+    use super::fixtures::dbs_contexts;
+    use crate::seeded_rng;
+
+    /// Runs the sharing, the verifications and the aggregation with k threads
+    /// Returns the serialized sharings and aggregate
+    fn run_with_threads(k: usize) -> (Vec<u8>, Vec<u8>) {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(k).build().unwrap();
+        pool.install(|| {
+            let (n, t) = (10, 4);
+            let (ctx, dss, _) = dbs_contexts(n, t, &mut seeded_rng(3, 0, b"setup"));
+
+            let mut rng = seeded_rng(3, 0, b"sharing");
+            let indices: Vec<_> = (0..t+1).collect();
+            let pvecs: Vec<_> = indices.iter().map(|&i| {
+                let pvec = ctx[i].generate_shares(1, &dss[i], &mut rng);
+                assert_eq!(None, ctx[n-1].verify_sharing(i, &pvec, &dss[i].public()));
+                pvec
+            }).collect();
+            let pvec_bytes = bincode::serialize(&pvecs).unwrap();
+            let (agg, _) = ctx[0].aggregate(&indices, pvecs);
+            assert_eq!(None, ctx[n-1].pverify(&agg));
+
+            // A bad encryption is caught by the threads
+            let mut bad = agg.clone();
+            bad.encs[n-2] = bad.encs[n-3];
+            assert!(ctx[0].pverify(&bad).is_some());
+            (pvec_bytes, bincode::serialize(&agg).unwrap())
+        })
+    }
+
+    #[test]
+    fn test_thread_counts() {
+        let (pvecs, agg) = run_with_threads(1);
+        for k in [2, 4, 8].iter() {
+            let (pvecs_k, agg_k) = run_with_threads(*k);
+            assert_eq!(pvecs, pvecs_k);
+            assert_eq!(agg, agg_k);
+        }
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use crate::{DSSSecretKey, batch_verify_sigs};
+
+    /// The encoding of the identity, which has a small order
+    fn identity() -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 1;
+        bytes
+    }
+
+    #[test]
+    fn test_valid_batch() {
+        let sks: Vec<_> = (0..4).map(|_| DSSSecretKey::generate_ed25519()).collect();
+        let pks: Vec<_> = sks.iter().map(|sk| sk.public()).collect();
+        let msgs: Vec<&[u8]> = vec![b"a", b"b", b"c", b"d"];
+        let sigs: Vec<_> = sks.iter().zip(&msgs).map(|(sk, m)| sk.sign(m).unwrap()).collect();
+        let sig_refs: Vec<&[u8]> = sigs.iter().map(|s| s.as_slice()).collect();
+        let pk_refs: Vec<_> = pks.iter().collect();
+        assert!(batch_verify_sigs(&msgs, &sig_refs, &pk_refs));
+
+        // A signature on another message
+        let mut bad = sig_refs.clone();
+        bad.swap(0, 1);
+        assert!(!batch_verify_sigs(&msgs, &bad, &pk_refs));
+    }
+
+    #[test]
+    fn test_small_order() {
+        let sk = DSSSecretKey::generate_ed25519();
+        let pk = sk.public();
+        let msg: &[u8] = b"msg";
+        let sig = sk.sign(msg).unwrap();
+
+        // With the identity as the key and as R, s = 0 satisfies the verification equation for any message
+        let weak = crypto_lib::PublicKey::Ed25519(crypto_lib::ed25519::PublicKey::decode(&identity()).unwrap());
+        let mut forged = identity().to_vec();
+        forged.extend_from_slice(&[0u8; 32]);
+        // The batch accepts the signatures exactly when the individual checks do
+        assert_eq!(
+            pk.verify(msg, &sig) && weak.verify(msg, &forged),
+            batch_verify_sigs(&[msg, msg], &[&sig, &forged], &[&pk, &weak])
+        );
+
+        // A small order R with a valid key
+        let mut bad_r = identity().to_vec();
+        bad_r.extend_from_slice(&sig[32..]);
+        assert_eq!(
+            pk.verify(msg, &bad_r),
+            batch_verify_sigs(&[msg, msg], &[&sig, &bad_r], &[&pk, &pk])
+        );
+    }
 }
@@ -0,0 +1,84 @@
+use ark_ff::{PrimeField, ToBytes};
+use sha2::{Digest, Sha256};
+use crate::hash::{Hash, do_hash};
+
+/// The protocol label that every transcript starts with
+/// Bump the version whenever the proof format changes
+pub const PROTOCOL_LABEL: &[u8] = b"optrand-v1";
+
+/// A Fiat-Shamir transcript
+///
+/// The transcript is a byte string built from labelled messages, where a message `msg` with label `label` is encoded as
+/// `le64(len(label)) || label || le64(len(msg)) || msg`
+///
+/// Every transcript starts with the messages ("protocol", PROTOCOL_LABEL) and ("domain", domain)
+/// - Integers are appended as 8 byte little-endian strings
+/// - Group elements are appended as affine points using `ToBytes`, i.e., the x and y co-ordinates in little-endian followed by one byte for the point at infinity
+///
+/// The challenge hash is h = SHA256(transcript) and the challenge scalar is the 64 byte string SHA256(h || 0x00) || SHA256(h || 0x01) interpreted as a little-endian integer modulo the group order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    buf: Vec<u8>,
+}
+
+impl Transcript {
+    /// Starts a new transcript for the domain
+    pub fn new(domain: &[u8]) -> Self {
+        let mut t = Self {
+            buf: Vec::new(),
+        };
+        t.append_message(b"protocol", PROTOCOL_LABEL);
+        t.append_message(b"domain", domain);
+        t
+    }
+
+    /// Appends a labelled message to the transcript
+    pub fn append_message(&mut self, label: &[u8], msg: &[u8]) {
+        self.buf.extend_from_slice(&(label.len() as u64).to_le_bytes());
+        self.buf.extend_from_slice(label);
+        self.buf.extend_from_slice(&(msg.len() as u64).to_le_bytes());
+        self.buf.extend_from_slice(msg);
+    }
+
+    /// Appends a labelled integer to the transcript
+    pub fn append_u64(&mut self, label: &[u8], x: u64) {
+        self.append_message(label, &x.to_le_bytes());
+    }
+
+    /// Appends a labelled group element (in affine form) to the transcript
+    pub fn append_point<G>(&mut self, label: &[u8], p: &G)
+    where G: ToBytes,
+    {
+        let mut bytes = Vec::new();
+        p.write(&mut bytes)
+            .expect("Writing a point to a vector cannot fail");
+        self.append_message(label, &bytes);
+    }
+
+    /// Appends a labelled scalar to the transcript, in little-endian using `ToBytes`
+    pub fn append_scalar<S>(&mut self, label: &[u8], s: &S)
+    where S: PrimeField,
+    {
+        self.append_point(label, s);
+    }
+
+    /// Returns SHA256(transcript)
+    pub fn hash(&self) -> Hash {
+        do_hash(&self.buf)
+    }
+
+    /// Returns the challenge hash and the challenge scalar for this transcript
+    pub fn challenge<S>(&self) -> (Hash, S)
+    where S: PrimeField,
+    {
+        let hash = self.hash();
+        let mut wide = Vec::with_capacity(64);
+        for i in 0..2u8 {
+            let mut hasher = Sha256::new();
+            hasher.update(&hash);
+            hasher.update(&[i]);
+            wide.extend_from_slice(&hasher.finalize());
+        }
+        (hash, S::from_le_bytes_mod_order(&wide))
+    }
+}
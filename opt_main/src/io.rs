@@ -23,10 +23,14 @@ pub fn load_config() -> Node {
         _ => panic!("Invalid config file extension"),
     };
     config.validate().expect("The decoded config is not valid");
+    config.config_file = Some(conf_str.to_string());
 
     if let Some(d) = m.value_of("delta") {
         config.delta = d.parse().unwrap();
     }
+    if let Some(s) = m.value_of("test_seed") {
+        config.test_seed = Some(s.parse().unwrap());
+    }
     if let Some(f) = m.value_of("ip") {
         config.update_config(util::io::file_to_ips(f.to_string()));
     }
@@ -27,7 +27,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             config.num_nodes,
             config.root_cert.clone(), 
             config.my_cert.clone(), 
-            config.my_cert_key.clone() 
+            config.my_cert_key.expose_secret().clone() 
         );
 
     // Build protocol networks
@@ -36,7 +36,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         config.num_nodes, 
         config.root_cert.clone(), 
         config.my_cert.clone(), 
-        config.my_cert_key.clone()
+        config.my_cert_key.expose_secret().clone()
     );
     let (cli_send, cli_recv) = 
     net_rt.block_on(
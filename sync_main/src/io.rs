@@ -23,6 +23,7 @@ pub fn load_config() -> Node {
         _ => panic!("Invalid config file extension"),
     };
     config.validate().expect("The decoded config is not valid");
+    config.config_file = Some(conf_str.to_string());
 
     if let Some(d) = m.value_of("delta") {
         config.delta = d.parse().unwrap();
@@ -23,7 +23,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         config.num_nodes,
         config.root_cert.clone(),
         config.my_cert.clone(),
-        config.my_cert_key.clone()
+        config.my_cert_key.expose_secret().clone()
     );
 
     // Start the protocol network
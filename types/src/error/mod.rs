@@ -10,12 +10,18 @@ pub enum Error {
     ShardMerkleError,
     ShardAccumulatorTreeMismatch,
     ShardAccumulatorMismatch,
+    ShardInvalidFrame,
     ReedSolomon(RSError),
     BinaryCodec(bincode::Error),
     BuilderUnsetField(&'static str),
     CertificateHashMismatch,
     CertificateUnknownOrigin(Replica),
     CertificateTooManySigs,
+    CertificateInvalidSig(Replica),
+    CertificateNoSigners,
+    CertificateOverlappingSigners,
+    CertificateInvalidAggregate,
+    CertificateInvalidKeyPop(Replica),
     DERConversionError(ErrorStack),
     ParseInvalidMapLen(usize, usize),
     ParseIncorrectFaults(usize, usize),
@@ -23,8 +29,14 @@ pub enum Error {
     ParseInvalidPkSize(usize),
     ParseInvalidSkSize(usize),
     ParseUnimplemented(&'static str),
+    ParseCurveMismatch(&'static str, String),
+    ParseInvalidGenerators(String),
+    ParseInvalidKeyPops(String),
+    ParseInvalidBlsKeys(String),
     Generic(String),
     EquivocationDetected(Epoch),
+    WireEmptyMessage,
+    WireVersionMismatch(u8, u8),
 }
 
 impl From<SigningError> for Error {
@@ -65,20 +77,32 @@ impl std::fmt::Display for Error {
             Self::ShardMerkleError => write!(f, "The merkle proof for the witness is invalid")?,
             Self::ShardAccumulatorTreeMismatch => write!(f, "Shard does not belong to the accumulator")?,
             Self::ShardAccumulatorMismatch => write!(f, "Shard Merkle tree does not match the root")?,
+            Self::ShardInvalidFrame => write!(f, "The reconstructed shards do not hold a length delimited message")?,
             Self::ReedSolomon(e) => write!(f, "Failed to reconstruct the shard with RS error: {}", e)?,
             Self::BinaryCodec(e) => write!(f, "Binary codec error: {}", e)?,
             Self::BuilderUnsetField(s) => write!(f, "Field {} is unset for the builder", s)?,
             Self::CertificateHashMismatch => write!(f, "Hash of the message is not the hash in the certificate")?,
             Self::CertificateUnknownOrigin(from) => write!(f, "Unknown signer {} for the certificate", from)?,
             Self::CertificateTooManySigs => write!(f, "Too many signatures in the certificate")?,
+            Self::CertificateInvalidSig(from) => write!(f, "Invalid signature from {} in the certificate", from)?,
+            Self::CertificateNoSigners => write!(f, "The certificate has no signers")?,
+            Self::CertificateOverlappingSigners => write!(f, "The certificates to merge have signers in common")?,
+            Self::CertificateInvalidAggregate => write!(f, "The aggregate signature in the certificate is invalid")?,
+            Self::CertificateInvalidKeyPop(from) => write!(f, "Invalid proof of possession for the BLS key of {}", from)?,
             Self::ParseIncorrectFaults(fault, n) => write!(f, "2*{} >= {}", *fault, *n)?,
             Self::ParseInvalidMapEntry(entry) => write!(f, "Invalid map entry: {}", entry)?,
             Self::ParseInvalidMapLen(exp, got) => write!(f, "Invalid map length - Expected {}, Got {}", exp, got)?,
             Self::ParseInvalidPkSize(s) => write!(f, "Invalid PK size - Got {}", s)?,
             Self::ParseInvalidSkSize(s) => write!(f, "Invalid SK size - Got {}", s)?,
             Self::ParseUnimplemented(unimp) => write!(f, "Unimplemented algorithm: {}", unimp)?,
+            Self::ParseCurveMismatch(exp, got) => write!(f, "Invalid pairing curve - Expected {}, Got {}", exp, got)?,
+            Self::ParseInvalidGenerators(e) => write!(f, "Invalid PVSS generators: {}", e)?,
+            Self::ParseInvalidKeyPops(e) => write!(f, "Invalid proofs of possession for the PVSS keys: {}", e)?,
+            Self::ParseInvalidBlsKeys(e) => write!(f, "Invalid BLS keys: {}", e)?,
             Self::DERConversionError(e) => write!(f, "DER Error: {}", e)?,
             Self::EquivocationDetected(e) => write!(f, "Equivocation detected in {}", e)?,
+            Self::WireEmptyMessage => write!(f, "Empty message from the network")?,
+            Self::WireVersionMismatch(exp, got) => write!(f, "Invalid protocol version - Expected {}, Got {}", exp, got)?,
             Self::Generic(s) => write!(f, "Generic Error: {}", s)?,
         }
         Ok(())
@@ -93,20 +117,32 @@ impl std::error::Error for Error {
             Self::ShardMerkleError => "Shard Merkle Error",
             Self::ShardAccumulatorTreeMismatch => "Shard Accumulator Tree Mismatch",
             Self::ShardAccumulatorMismatch => "Shard Accumulator Mismatch",
+            Self::ShardInvalidFrame => "Shard Invalid Frame",
             Self::ReedSolomon(..) => "Reed Solomon Error",
             Self::BinaryCodec(..) => "Binary Codec Error",
             Self::BuilderUnsetField(..) => "Builder Field Unset",
             Self::CertificateHashMismatch => "Certificate Hash Mismatch",
             Self::CertificateUnknownOrigin(..) => "Certificate Unknown Origin",
             Self::CertificateTooManySigs => "Certificate Too Many Sigs",
+            Self::CertificateInvalidSig(..) => "Certificate Invalid Sig",
+            Self::CertificateNoSigners => "Certificate No Signers",
+            Self::CertificateOverlappingSigners => "Certificate Overlapping Signers",
+            Self::CertificateInvalidAggregate => "Certificate Invalid Aggregate",
+            Self::CertificateInvalidKeyPop(..) => "Certificate Invalid Key Pop",
             Self::ParseIncorrectFaults(..) => "Parse Incorrect Faults",
             Self::ParseInvalidMapEntry(..) => "Parse Invalid Map Entry",
             Self::ParseInvalidMapLen(..) => "Parse Invalid Map Length",
             Self::ParseInvalidPkSize(..) => "Parse Invalid PK Size",
             Self::ParseInvalidSkSize(..) => "Parse Invalid SK Size",
             Self::ParseUnimplemented(..) => "Parse Unimplemented Algorithm",
+            Self::ParseCurveMismatch(..) => "Parse Curve Mismatch",
+            Self::ParseInvalidGenerators(..) => "Parse Invalid Generators",
+            Self::ParseInvalidKeyPops(..) => "Parse Invalid Key Pops",
+            Self::ParseInvalidBlsKeys(..) => "Parse Invalid BLS Keys",
             Self::DERConversionError(..) => "DER Error",
             Self::EquivocationDetected(..) => "Equivocation Error",
+            Self::WireEmptyMessage => "Wire Empty Message",
+            Self::WireVersionMismatch(..) => "Wire Version Mismatch",
             Self::Generic(..) => "Generic Error",
         }
     }
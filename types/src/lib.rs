@@ -14,7 +14,10 @@ pub type Epoch = usize;
 /// The first epoch is 1
 pub const START_EPOCH: Epoch = 1;
 
-/// We will use Bls12_381 for OptRand
+/// The pairing curve is chosen at compile time using cargo features
+/// - `bls12-381` (default): Bls12_381
+/// - `bn254`: Bn254 (EVM-verifiable)
+/// Since `bls12-381` is a default feature, enabling `bn254` takes precedence over it
 /// Other users of the crypto library can change the pairing curve accordingly
 /// Available options: (Source: https://github.com/arkworks-rs/curves)
 /// - Bls12_371
@@ -26,19 +29,39 @@ pub const START_EPOCH: Epoch = 1;
 /// - mnt4_753
 /// - mnt6_298
 /// - mnt6_753
-// pub type E = crypto::Bn254;
+#[cfg(feature = "bn254")]
+pub type E = crypto::Bn254;
+#[cfg(not(feature = "bn254"))]
 pub type E = crypto::Bls12_381;
 
+/// The name of the pairing curve this build uses
+/// It is recorded in the configs and the genesis block so that nodes using different curves refuse to work together
+#[cfg(feature = "bn254")]
+pub const CURVE: &str = "bn254";
+#[cfg(not(feature = "bn254"))]
+pub const CURVE: &str = "bls12-381";
+
+#[cfg(not(any(feature = "bls12-381", feature = "bn254")))]
+compile_error!("Select a pairing curve using either the `bls12-381` or the `bn254` feature");
+
 // Instantiate specific types for use in the rest of the codebase
 pub type AggregatePVSS = crypto::AggregatePVSS<E>;
 pub type DecompositionProof = crypto::DecompositionProof<E>;
 pub type Decryption = crypto::Decryption<E>;
 pub type PVSSVec = crypto::PVSSVec<E>;
 pub type Beacon = crypto::Beacon<E>;
+/// The PVSS scheme used by the protocol
+/// The messages and blocks carry its types, while the consensus threads only use it through `crypto::PvssScheme`
 pub type DbsContext = crypto::DbsContext<E>;
 pub type BeaconShare = crypto::Share<E>;
 pub type Keypair = crypto::Keypair<E>;
+pub type KeyPop = crypto::KeyPop<E>;
+pub type KeyRotation = crypto::KeyRotation<E>;
 pub type Share = crypto::Share<E>;
+pub type BlsKeypair = crypto::BlsKeypair<E>;
+pub type BlsPublicKey = crypto::PublicKey<E>;
+pub type BlsSecretKey = crypto::SecretKey<E>;
+pub type BlsSignature = crypto::BlsSignature<E>;
 
 pub type DirectProposal = Proposal<ProposalData>;
 pub type SyncCertProposal = Proposal<SyncCertData>;
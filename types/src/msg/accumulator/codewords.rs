@@ -1,10 +1,10 @@
 use std::marker::PhantomData;
 use bytes::{Bytes, BytesMut};
-use reed_solomon_erasure::galois_8::ReedSolomon;
+use reed_solomon_erasure::{Error as RSError, galois_8::ReedSolomon};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
 
-use crate::error::Error;
+use crate::{decode_versioned, error::Error};
 
 use super::Shard;
 
@@ -31,6 +31,7 @@ impl<T> std::fmt::Debug for Codeword<T> {
 }
 
 /// This function takes a data and the number of nodes n and creates a (n,n/4) erasure coding 
+/// The data is the `encode_versioned` encoding of the object, so that `from_codewords` can decode it
 pub fn generate_codewords<T>(data: &[u8], 
                                 num_nodes: usize, 
                                 num_faults: usize
@@ -75,39 +76,33 @@ pub fn generate_codewords<T>(data: &[u8],
 }
 
 /// From Shards: takes a vector of shares and number of nodes n and reconstructs the original message
+///
+/// The shards come from the network, so malformed shards are an error and never a panic
 pub fn from_codewords<T>(data: Vec<Option<Codeword<T>>>, 
                             num_nodes: usize, 
                             num_faults: usize
                         ) -> Result<T, Error> 
 where T: DeserializeOwned
 {
-    let num_data_shards = num_nodes - num_faults;
+    let num_data_shards = num_nodes.checked_sub(num_faults)
+        .ok_or(RSError::TooFewDataShards)?;
     let new_data = {
-        let mut data_vec = Vec::with_capacity(num_nodes);
-        for code in data {
-            if let Some(x) = code {
-                data_vec.push(Some(x.data));
-            } else {
-                data_vec.push(None);
-            }
-        }
+        let mut data_vec: Vec<_> = data.into_iter()
+            .map(|code| code.map(|x| x.data))
+            .collect();
         let r = ReedSolomon::new(num_data_shards, num_faults)?;
         r.reconstruct(&mut data_vec)?;
-        let mut result = Vec::with_capacity(
-            num_data_shards * data_vec[0]
-                .as_ref()
-                .map(|val| val.len())
-                .unwrap()
-        );
-        for shard in 0..num_data_shards {
-            result.append(data_vec[shard].as_mut().unwrap());
-            // result.append(&mut data_vec[shard].clone().unwrap());
+        let mut result = Vec::new();
+        for shard in data_vec.iter_mut().take(num_data_shards) {
+            result.append(shard.as_mut().ok_or(RSError::TooFewShardsPresent)?);
         }
         let mut decoder = LengthDelimitedCodec::new();
         let mut buf = BytesMut::from(&result[..]);
-        decoder.decode(&mut buf).unwrap().unwrap().freeze().to_vec()
+        decoder.decode(&mut buf)
+            .ok()
+            .flatten()
+            .ok_or(Error::ShardInvalidFrame)?
     };
-    let obj = bincode::deserialize(&new_data)?;
-    Ok(obj)
+    decode_versioned(&new_data)
 }
 
@@ -1,7 +1,7 @@
 use std::{fmt::Debug, marker::PhantomData};
-use crypto::hash;
+use crypto::Transcript;
 use serde::{Deserialize, Serialize};
-use crate::{Codeword, Replica, Witness, error::Error, generate_codewords};
+use crate::{CanonicalHash, Codeword, Epoch, Replica, Witness, encode_versioned, error::Error, generate_codewords, merkle_leaf_hash, merkle_node_hash};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct MTAccumulator<T> {
@@ -12,6 +12,16 @@ pub struct MTAccumulator<T> {
 
 impl<T> Eq for MTAccumulator<T> where T: PartialEq {}
 
+/// The proposer signs the accumulator of its proposal for the epoch
+impl<T> CanonicalHash for (Epoch, MTAccumulator<T>) {
+    const DOMAIN: &'static [u8] = b"certified-accumulator";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_u64(b"epoch", self.0 as u64);
+        t.append_message(b"root", &self.1.hash);
+    }
+}
+
 impl<T> std::fmt::Display for MTAccumulator<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "0x")?;
@@ -50,7 +60,7 @@ where T: Serialize,
     }
 
     pub fn build(&self, obj: &T) -> Result<(MTAccumulator<T>, Vec<Codeword<T>>, Vec<Witness<T>>), Error> {
-        let bytes = bincode::serialize(obj)?;
+        let bytes = encode_versioned(obj)?;
         let n = *self.n.as_ref().ok_or(Error::BuilderUnsetField("n"))?;
         let f = *self.f.as_ref().ok_or(Error::BuilderUnsetField("f"))?;
 
@@ -65,7 +75,7 @@ where T: Serialize,
     }
 
     pub fn check(&self, obj: &T, acc: &MTAccumulator<T>) -> Result<(), Error> {
-        let bytes = bincode::serialize(obj)?;
+        let bytes = encode_versioned(obj)?;
         let n = *self.n.as_ref().ok_or(Error::BuilderUnsetField("n"))?;
         let f = *self.f.as_ref().ok_or(Error::BuilderUnsetField("f"))?;
 
@@ -87,7 +97,7 @@ where T: Serialize,
     ) -> Result<(), Error> 
     {
         // The hash of the code should be the hash in the witness Merkle proof
-        if hash::ser_and_hash(code).to_vec() != wit.start() {
+        if merkle_leaf_hash(code.shard()).to_vec() != wit.start() {
             return Err(Error::ShardLeafError);
         }
         // Check the Merkle proof
@@ -95,9 +105,9 @@ where T: Serialize,
         change >>= 1;
         for i in 0..wit.chain().len() - 1 {
             let h = if change & 1 == 0 {
-                hash::ser_and_hash(&(wit.chain()[i].1.clone(), wit.chain()[i + 1].0.clone())).to_vec()
+                merkle_node_hash(&wit.chain()[i].1, &wit.chain()[i + 1].0).to_vec()
             } else {
-                hash::ser_and_hash(&(wit.chain()[i + 1].0.clone(), wit.chain()[i].1.clone())).to_vec()
+                merkle_node_hash(&wit.chain()[i + 1].0, &wit.chain()[i].1).to_vec()
             };
             if h != wit.chain()[i + 1].1 {
                 return Err(Error::ShardMerkleError);
@@ -1,4 +1,4 @@
-use crate::{MTAccumulatorBuilder, Replica, error::Error, from_codewords};
+use crate::{MTAccumulatorBuilder, Replica, encode_versioned, error::Error, from_codewords};
 use super::get_size as gs;
 
 const SIZE: usize = 1025;
@@ -17,7 +17,7 @@ fn shards() -> Result<(), Error> {
     fill_random_data(&mut data);
     let data = (data.to_vec(), 0);
     type DATA = (Vec<u8>, usize);
-    let bytes = bincode::serialize(&data)?;
+    let bytes = encode_versioned(&data)?;
     let shards = super::generate_codewords::<DATA>(&bytes, NUM_NODES, NUM_FAULTS)?;
     let mut received: Vec<_> = shards.iter().cloned().map(Some).collect();
     received[0] = None;
@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 use serde::{Serialize, Deserialize};
-use crypto::hash::{self};
+use crypto::hash;
 
-use crate::{Codeword, MTAccumulatorBuilder, Replica, error::Error, get_size};
+use crate::{Codeword, MTAccumulatorBuilder, Replica, error::Error, get_size, merkle_leaf_hash, merkle_node_hash};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Witness<T> {
@@ -58,12 +58,12 @@ pub fn get_tree(shards: &[Vec<u8>]) -> Result<Tree, Error> {
     let size = get_size(n);
     let mut tree = vec![Vec::with_capacity(hash::HASH_SIZE); (1 << size) + 1];
     for i in 0..n {
-        tree[1 << size - 1 | i] = hash::ser_and_hash(&shards[i]).to_vec();
+        tree[1 << size - 1 | i] = merkle_leaf_hash(&shards[i]).to_vec();
     }
     for i in 0..(1 << size - 1) - 1 {
         let index = (1 << size - 1) - 1 - i;
         tree[index] =
-            hash::ser_and_hash(&(tree[index << 1].clone(), tree[index << 1 | 1].clone())).to_vec();
+            merkle_node_hash(&tree[index << 1], &tree[index << 1 | 1]).to_vec();
     }
     Ok(tree)
 }
@@ -74,12 +74,12 @@ impl<T> MTAccumulatorBuilder<T> {
         let size = get_size(n);
         let mut tree = vec![Vec::with_capacity(hash::HASH_SIZE); (1 << size) + 1];
         for i in 0..n {
-            tree[1 << size - 1 | i] = hash::ser_and_hash(&codes[i].shard()).to_vec();
+            tree[1 << size - 1 | i] = merkle_leaf_hash(codes[i].shard()).to_vec();
         }
         for i in 0..(1 << size - 1) - 1 {
             let index = (1 << size - 1) - 1 - i;
             tree[index] =
-                hash::ser_and_hash(&(tree[index << 1].clone(), tree[index << 1 | 1].clone())).to_vec();
+                merkle_node_hash(&tree[index << 1], &tree[index << 1 | 1]).to_vec();
         }
         Ok(tree)
     }
@@ -1,5 +1,5 @@
-use crate::{Epoch, Proof, RespCertProposal};
-use crypto::hash::Hash;
+use crate::{CanonicalHash, Epoch, Proof, RespCertProposal, append_hash_of};
+use crypto::{Transcript, hash::Hash};
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Builder)]
@@ -21,4 +21,14 @@ impl AckData {
     pub fn proof(&self) -> &Proof<RespCertProposal> {
         &self.proof
     }
-}
\ No newline at end of file
+}
+
+impl CanonicalHash for AckData {
+    const DOMAIN: &'static [u8] = b"ack";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_u64(b"epoch", self.e as u64);
+        t.append_message(b"proposal-hash", &self.prop_hash);
+        append_hash_of(t, b"proof", &self.proof);
+    }
+}
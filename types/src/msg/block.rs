@@ -1,7 +1,7 @@
-use crypto::{DSSPublicKey, hash::{Hash, EMPTY_HASH}};
+use crypto::{DSSPublicKey, Transcript, hash::{Hash, EMPTY_HASH}};
 use fnv::FnvHashMap;
 use serde::{Deserialize, Serialize};
-use crate::{AggregatePVSS, DbsContext, DecompositionProof, Height, Replica, Storage, error::Error};
+use crate::{CanonicalHash, append_hash_of, AggregatePVSS, DbsContext, DecompositionProof, Epoch, Height, KeyRotation, Replica, Storage, Wire, decode_versioned, error::Error};
 
 #[derive(Serialize, Deserialize, Clone, Builder)]
 #[builder(build_fn(skip))]
@@ -13,6 +13,10 @@ pub struct Block {
     aggregate_pvss: AggregatePVSS,
     aggregate_proof: DecompositionProof,
 
+    /// A rotation of the PVSS key of the proposer, applied by everyone once the block is committed
+    #[serde(default)]
+    key_rotation: Option<KeyRotation>,
+
     /// The hash of the block, do not serialize, init will update it automatically
     #[serde(skip)]
     hash: Hash,
@@ -33,21 +37,35 @@ impl Block {
         aggregate_pvss: AggregatePVSS{
             comms: vec![],
             encs: vec![],
+            key_version: 0,
+            epoch: 0,
         },
         aggregate_proof: DecompositionProof{
             dleq_proof: vec![],
             gs_vec: vec![],
             indices: vec![],
         },
+        key_rotation: None,
     };
 
+    /// The genesis block
+    /// Its hash is derived from the pairing curve, so the first block proposed by a node using a different curve has an unknown parent
     pub fn genesis() -> Self {
-        Self::GENESIS_BLOCK
+        let mut genesis = Self::GENESIS_BLOCK;
+        genesis.hash = Self::genesis_hash();
+        genesis
+    }
+
+    /// The hash of the genesis block for the curve in this build
+    pub fn genesis_hash() -> Hash {
+        let mut t = Transcript::new(b"genesis");
+        t.append_message(b"curve", crate::CURVE.as_bytes());
+        t.hash()
     }
 
     /// Compute the hash of the block, it does not set the block hash
     pub fn compute_hash(&self) -> Hash {
-        crypto::hash::ser_and_hash(self)
+        self.canonical_hash()
     }
 
     /// This will check for:
@@ -55,9 +73,11 @@ impl Block {
     /// 2. The height is correct
     /// 3. The aggregate pvss is correct
     /// 4. The decomposition proof is correct
+    /// 5. The key rotation (if any) is valid for a block proposed in epoch e (see `check_key_rotation`)
     pub fn is_valid(&self, 
+        e: Epoch,
         storage: &Storage, 
-        _dbs_ctx: &DbsContext, 
+        dbs_ctx: &DbsContext, 
         _pk_map: &FnvHashMap<Replica, DSSPublicKey>
     ) -> Result<(), String> 
     {
@@ -69,6 +89,7 @@ impl Block {
         if parent.height + 1 != self.height {
             return Err("Invalid Height".to_string());
         }
+        self.check_key_rotation(e, dbs_ctx)?;
         // We will do this in the optimization
         // if let Some(err) = dbs_ctx.pverify(&self.aggregate_pvss) {
         //     return Err(format!("Pverify failed with {:?}", err));
@@ -81,6 +102,26 @@ impl Block {
         Ok(())
     }
 
+    /// Checks that the key rotation (if any) is for the proposer, is endorsed by its current key,
+    /// and switches late enough for everyone to commit the block proposed in epoch e before
+    /// Every block must pass this before it is delivered, since the rotations are applied once it is committed
+    pub fn check_key_rotation(&self, e: Epoch, dbs_ctx: &DbsContext) -> Result<(), String> {
+        let rot = match &self.key_rotation {
+            None => return Ok(()),
+            Some(rot) => rot,
+        };
+        if rot.id != self.proposer {
+            return Err(format!("Key rotation for {} in a block from {}", rot.id, self.proposer));
+        }
+        if rot.switch_epoch < dbs_ctx.min_switch_epoch(e as u64) {
+            return Err(format!("The key rotation in epoch {} switches too early at {}", e, rot.switch_epoch));
+        }
+        if let Some(err) = dbs_ctx.verify_rotation(rot) {
+            return Err(format!("Invalid key rotation: {:?}", err));
+        }
+        Ok(())
+    }
+
     pub fn pvss(&self) -> &AggregatePVSS {
         &self.aggregate_pvss
     } 
@@ -100,6 +141,29 @@ impl Block {
     pub fn proposer(&self) -> &Replica {
         &self.proposer
     }
+
+    pub fn key_rotation(&self) -> Option<&KeyRotation> {
+        self.key_rotation.as_ref()
+    }
+}
+
+impl CanonicalHash for Block {
+    const DOMAIN: &'static [u8] = b"block";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_message(b"parent", &self.parent_hash);
+        t.append_u64(b"proposer", self.proposer as u64);
+        t.append_u64(b"height", self.height as u64);
+        append_hash_of(t, b"aggregate-pvss", &self.aggregate_pvss);
+        append_hash_of(t, b"decomposition-proof", &self.aggregate_proof);
+        match &self.key_rotation {
+            None => t.append_u64(b"has-key-rotation", 0),
+            Some(rot) => {
+                t.append_u64(b"has-key-rotation", 1);
+                append_hash_of(t, b"key-rotation", rot);
+            }
+        }
+    }
 }
 
 impl std::fmt::Debug for Block {
@@ -120,14 +184,22 @@ impl types_upstream::WireReady for Block {
         self
     }
 
+    /// WARNING: Panics on malformed data, use `Wire::from_wire` for data from the network
     fn from_bytes(data: &[u8]) -> Self {
-        let c: Block = bincode::deserialize(data)
-            .expect("failed to decode the block");
-        return c.init();
+        Self::from_wire(data)
+            .expect("failed to decode the block")
     }
 
     fn to_bytes(self: &Self) -> Vec<u8> {
-        bincode::serialize(self).expect(format!("Failed to serialize {:?}", self).as_str())
+        self.to_wire().expect(format!("Failed to serialize {:?}", self).as_str())
+    }
+}
+
+impl Wire for Block {
+    /// Also computes the hash of the block, which is not sent
+    fn from_wire(data: &[u8]) -> crate::Result<Self> {
+        let b: Block = decode_versioned(data)?;
+        Ok(types_upstream::WireReady::init(b))
     }
 }
 
@@ -149,6 +221,7 @@ impl BlockBuilder {
             proposer: Clone::clone(self.proposer
                 .as_ref()
                 .ok_or(Error::BuilderUnsetField("Proposer"))?),
+            key_rotation: self.key_rotation.clone().flatten(),
             hash: EMPTY_HASH,
         };
         block.hash = block.compute_hash();
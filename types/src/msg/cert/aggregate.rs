@@ -0,0 +1,153 @@
+use std::marker::PhantomData;
+use crypto::{bls_aggregate_keys, bls_verify, canonical_deserialize, canonical_serialize, hash::{EMPTY_HASH, Hash}};
+use fnv::FnvHashMap;
+use serde::{Serialize, Deserialize};
+use crate::{CanonicalHash, BlsKeypair, BlsPublicKey, BlsSignature, KeyPop, Replica, SignerBitmap, error::Error};
+
+/// The BLS public keys of the nodes, each checked against its proof of possession
+/// Aggregate certificates are only checked against these keys: without the proofs, a node could pick its key as its own key minus the keys of others, and alone sign for all of them
+#[derive(Debug, Clone, Default)]
+pub struct BlsKeyMap {
+    pks: FnvHashMap<Replica, BlsPublicKey>,
+}
+
+impl BlsKeyMap {
+    /// Checks that node i knows the secret key for pks[i], using the proof of possession pops[i]
+    pub fn new(pks: &[BlsPublicKey], pops: &[KeyPop]) -> Result<Self, Error> {
+        if pks.len() != pops.len() {
+            return Err(Error::ParseInvalidMapLen(pks.len(), pops.len()));
+        }
+        let mut map = FnvHashMap::default();
+        for (id, (pk, pop)) in pks.iter().zip(pops.iter()).enumerate() {
+            if pop.verify_bls(id, pk).is_some() {
+                return Err(Error::CertificateInvalidKeyPop(id));
+            }
+            map.insert(id, *pk);
+        }
+        Ok(Self { pks: map })
+    }
+
+    pub fn get(&self, id: Replica) -> Option<&BlsPublicKey> {
+        self.pks.get(&id)
+    }
+
+    /// The number of nodes with a key
+    pub fn len(&self) -> usize {
+        self.pks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pks.is_empty()
+    }
+}
+
+/// A certificate backed by a BLS multi-signature: the set of signers and the product of their signatures on the message hash
+/// This is an alternative to `Certificate`, whose size and verification cost grow with the number of signatures
+/// Here the size only grows by a bit per node, and checking the certificate takes a single pairing check
+/// A vote is a certificate with one signer, and votes (or certificates) with disjoint signers are combined with `merge`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AggregateCertificate<T> {
+    hash: Hash,
+    signers: SignerBitmap,
+    #[serde(serialize_with = "canonical_serialize")]
+    #[serde(deserialize_with = "canonical_deserialize")]
+    sig: BlsSignature,
+    _x: PhantomData<T>,
+}
+
+impl<T> std::default::Default for AggregateCertificate<T> {
+    fn default() -> Self {
+        Self {
+            hash: EMPTY_HASH,
+            signers: SignerBitmap::default(),
+            sig: BlsSignature::default(),
+            _x: PhantomData,
+        }
+    }
+}
+
+impl<T> AggregateCertificate<T>
+where T: CanonicalHash,
+{
+    pub fn get_hash(&self) -> &Hash {
+        &self.hash
+    }
+
+    /// The nodes that signed this certificate
+    pub fn signers(&self) -> &SignerBitmap {
+        &self.signers
+    }
+
+    pub fn new_cert(msg: &T, myid: Replica, kp: &BlsKeypair) -> Self {
+        let hash = msg.canonical_hash();
+        let mut signers = SignerBitmap::default();
+        signers.insert(myid);
+        Self {
+            sig: kp.sign(&hash),
+            hash,
+            signers,
+            _x: PhantomData,
+        }
+    }
+
+    /// Adds the signatures in other to this certificate
+    /// Both certificates must be for the same message and have no signers in common, since a signature cannot be removed from the aggregate
+    ///
+    /// WARNING: Check other with `is_valid` first, a single invalid vote makes the whole certificate invalid
+    pub fn merge(&mut self, other: &Self) -> Result<(), Error> {
+        if self.hash != other.hash {
+            return Err(Error::CertificateHashMismatch);
+        }
+        if self.signers.intersects(&other.signers) {
+            return Err(Error::CertificateOverlappingSigners);
+        }
+        self.signers.union_with(&other.signers);
+        self.sig += &other.sig;
+        Ok(())
+    }
+
+    /// The number of votes in the certificate
+    pub fn len(&self) -> usize {
+        self.signers.len()
+    }
+
+    /// Check whether this is a vote, i.e., there is only one signer in this certificate
+    pub fn is_vote(&self) -> bool {
+        self.len() == 1
+    }
+
+    /// `is_valid` checks if:
+    /// 1. The certificate is for this message
+    /// 2. The aggregate signature is valid under the sum of the BLS public keys of the signers
+    ///
+    /// The public keys come with valid proofs of possession (see `BlsKeyMap::new`)
+    pub fn is_valid(&self,
+        msg: &T,
+        pks: &BlsKeyMap,
+    ) -> Result<(), Error> {
+        if self.hash != msg.canonical_hash() {
+            return Err(Error::CertificateHashMismatch);
+        }
+        if self.signers.is_empty() {
+            return Err(Error::CertificateNoSigners);
+        }
+        if pks.len() < self.len() {
+            return Err(Error::CertificateTooManySigs);
+        }
+        // The keys are for nodes 0..n, so this rules out a bitmap longer than n bits before going through it
+        if let Some(id) = self.signers.max().filter(|id| *id >= pks.len()) {
+            return Err(Error::CertificateUnknownOrigin(id));
+        }
+        let mut signer_pks = Vec::with_capacity(self.len());
+        for from in self.signers.iter() {
+            let pk = pks.get(from)
+                .ok_or(Error::CertificateUnknownOrigin(from))?;
+            signer_pks.push(pk);
+        }
+        let apk = bls_aggregate_keys::<crate::E, _>(signer_pks);
+        if !bls_verify::<crate::E>(&self.hash, &self.sig, &apk) {
+            return Err(Error::CertificateInvalidAggregate);
+        }
+        Ok(())
+    }
+}
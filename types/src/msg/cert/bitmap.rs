@@ -0,0 +1,85 @@
+use std::convert::TryFrom;
+use serde::{Serialize, Deserialize};
+use crate::Replica;
+
+/// A set of replicas, with one bit per replica
+/// The bitmap ends at the byte with the highest replica, so every set has a single encoding
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+pub struct SignerBitmap {
+    bits: Vec<u8>,
+}
+
+impl TryFrom<Vec<u8>> for SignerBitmap {
+    type Error = String;
+
+    fn try_from(bits: Vec<u8>) -> Result<Self, Self::Error> {
+        if bits.last() == Some(&0) {
+            return Err("The signer bitmap has trailing zero bytes".to_string());
+        }
+        Ok(Self { bits })
+    }
+}
+
+impl From<SignerBitmap> for Vec<u8> {
+    fn from(signers: SignerBitmap) -> Self {
+        signers.bits
+    }
+}
+
+impl SignerBitmap {
+    /// Adds the replica to the set, and returns false if it was already there
+    pub fn insert(&mut self, id: Replica) -> bool {
+        let (byte, mask) = (id / 8, 1u8 << (id % 8));
+        if self.bits.len() <= byte {
+            self.bits.resize(byte + 1, 0);
+        }
+        let new = self.bits[byte] & mask == 0;
+        self.bits[byte] |= mask;
+        new
+    }
+
+    pub fn contains(&self, id: Replica) -> bool {
+        self.bits.get(id / 8)
+            .map_or(false, |b| b & (1u8 << (id % 8)) != 0)
+    }
+
+    /// The number of replicas in the set
+    pub fn len(&self) -> usize {
+        self.bits.iter()
+            .map(|b| b.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|b| *b == 0)
+    }
+
+    /// The highest replica in the set
+    pub fn max(&self) -> Option<Replica> {
+        let last = self.bits.iter().rposition(|b| *b != 0)?;
+        Some(last * 8 + 7 - self.bits[last].leading_zeros() as usize)
+    }
+
+    /// Checks whether the two sets have a replica in common
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.bits.iter()
+            .zip(other.bits.iter())
+            .any(|(a, b)| a & b != 0)
+    }
+
+    /// Adds the replicas in other to the set
+    pub fn union_with(&mut self, other: &Self) {
+        if self.bits.len() < other.bits.len() {
+            self.bits.resize(other.bits.len(), 0);
+        }
+        for (a, b) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *a |= b;
+        }
+    }
+
+    /// The replicas in the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = Replica> + '_ {
+        (0..self.bits.len() * 8).filter(move |id| self.contains(*id))
+    }
+}
@@ -1,57 +1,107 @@
-use crypto::{DSSPublicKey, DSSSecretKey, hash::{EMPTY_HASH, Hash, ser_and_hash}};
+use crypto::{DSSPublicKey, DSSSecretKey, Transcript, batch_verify_sigs, hash::Hash};
 use fnv::FnvHashMap;
-use serde::{Serialize, Deserialize};
-use crate::{Replica, Signature, Storage, error::Error};
+use serde::{Serialize, Deserialize, Deserializer, Serializer, de, ser};
+use crate::{CanonicalHash, Replica, SIG_SIZE, Signature, SignerBitmap, Storage, error::Error};
 
 /// A certificate contains several signatures on a message
 /// A vote is a special case of certificate with one vote
 /// A sync certificate is a certificate containing vote from n/2+1 nodes
 /// A responsive certificate is a certificate containing votes from 3n/4 nodes
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// On the wire, a certificate is a bitmap of the signers followed by their signatures (see `CertificateWire`)
+/// The certificate does not carry the hash of the message: `is_valid` checks the signatures against the hash of the message it is given
+#[derive(Debug, Clone)]
 pub struct Certificate<T> {
-    hash: Hash,
     pub sigs: FnvHashMap<Replica, Signature<T>>,
 }
 
+/// The wire form of a certificate
+/// The signatures are in increasing order of the signers, back to back, with `SIG_SIZE` bytes each
+#[derive(Serialize, Deserialize)]
+struct CertificateWire {
+    signers: SignerBitmap,
+    sigs: Vec<u8>,
+}
+
+impl<T> Serialize for Certificate<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer,
+    {
+        let mut ids: Vec<_> = self.sigs.keys().copied().collect();
+        ids.sort_unstable();
+        let mut wire = CertificateWire {
+            signers: SignerBitmap::default(),
+            sigs: Vec::with_capacity(SIG_SIZE * ids.len()),
+        };
+        for id in ids {
+            let sig = self.sigs[&id].as_bytes();
+            if sig.len() != SIG_SIZE {
+                return Err(ser::Error::custom("A signature in the certificate has the wrong length"));
+            }
+            wire.signers.insert(id);
+            wire.sigs.extend_from_slice(sig);
+        }
+        wire.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Certificate<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de>,
+    {
+        let wire = CertificateWire::deserialize(deserializer)?;
+        let ids: Vec<_> = wire.signers.iter().collect();
+        if wire.sigs.len() != SIG_SIZE * ids.len() {
+            return Err(de::Error::custom("The signatures do not match the signers in the certificate"));
+        }
+        let mut cert = Self::default();
+        for (id, sig) in ids.into_iter().zip(wire.sigs.chunks(SIG_SIZE)) {
+            cert.sigs.insert(id, Signature::from_bytes(sig.to_vec()));
+        }
+        Ok(cert)
+    }
+}
+
 impl<T> std::default::Default for Certificate<T> {
     fn default() -> Self {
         Self {
-            hash: EMPTY_HASH, 
             sigs: FnvHashMap::default(),
         }
     }
 }
 
-impl<T> Certificate<T> 
-where T: Serialize,
-{
+/// The signers in increasing order, each followed by its signature
+impl<T> CanonicalHash for Certificate<T> {
+    const DOMAIN: &'static [u8] = b"certificate";
 
-    pub fn get_hash(&self) -> &Hash {
-        &self.hash
+    fn append_fields(&self, t: &mut Transcript) {
+        let mut ids: Vec<_> = self.sigs.keys().copied().collect();
+        ids.sort_unstable();
+        t.append_u64(b"signers", ids.len() as u64);
+        for id in ids {
+            t.append_u64(b"signer", id as u64);
+            t.append_message(b"signature", self.sigs[&id].as_bytes());
+        }
     }
+}
+
+impl<T> Certificate<T> 
+where T: CanonicalHash,
+{
 
     /// Converts a signature into a certificate
-    pub fn from_signature_and_msg_hash(
+    pub fn from_signature(
         id: Replica, 
-        h: Hash, 
         sig: Signature<T>
     ) -> Self {
-        let mut cert = Self {
-            hash: h,
-            sigs: FnvHashMap::default(),
-        };
+        let mut cert = Self::default();
         cert.sigs.insert(id, sig);
         cert
     }
 
     pub fn new_cert(msg: &T, myid: Replica, sk: &DSSSecretKey) -> Result<Self, Error> {
-        let mut cert = Self {
-            hash: ser_and_hash(msg),
-            sigs: FnvHashMap::default(),
-        };
-        let sig = Signature::new_signature_from_hash(&cert.hash, sk)?;
-        cert.sigs.insert(myid, sig);
-        Ok(cert) 
+        let sig = Signature::new_signature(msg, sk)?;
+        Ok(Self::from_signature(myid, sig))
     }
 
     /// Adds a signature to the certificate
@@ -69,23 +119,42 @@ where T: Serialize,
         self.sigs.len() == 1
     }
 
-    /// `is_valid` checks if:
-    /// 1. The certificate is for this message
-    /// 2. Every vote is vaild against this message
+    /// `is_valid` checks if every vote is vaild against this message
     pub fn is_valid(&self, 
         msg: &T, 
         pks: &FnvHashMap<Replica, DSSPublicKey>
     ) -> Result<(), Error> {
-        if self.hash != ser_and_hash(msg) {
-            return Err(Error::CertificateHashMismatch);
-        }
+        let hash = msg.canonical_hash();
         if pks.len() < self.len() {
             return Err(Error::CertificateTooManySigs);
         }
-        for (from, sig) in &self.sigs {
-            let pk = pks.get(from)
-                .ok_or(Error::CertificateUnknownOrigin(*from))?;
-            sig.is_valid_with_hash(&self.hash, pk)?;
+        let sigs: Vec<_> = self.sigs.iter().collect();
+        Self::verify_sigs(&hash, &sigs, pks)
+    }
+
+    /// Checks the signatures from the signers on the hash
+    ///
+    /// OPTIMIZATION: We check all the signatures at once with `crypto::batch_verify_sigs`
+    /// Only if that fails, we check them one by one to find the bad signer
+    fn verify_sigs(hash: &Hash, 
+        sigs: &[(&Replica, &Signature<T>)],
+        pks: &FnvHashMap<Replica, DSSPublicKey>,
+    ) -> Result<(), Error> {
+        let mut signer_pks = Vec::with_capacity(sigs.len());
+        for (from, _) in sigs {
+            let pk = pks.get(*from)
+                .ok_or(Error::CertificateUnknownOrigin(**from))?;
+            signer_pks.push(pk);
+        }
+        let msgs = vec![&hash[..]; sigs.len()];
+        let sig_bytes: Vec<_> = sigs.iter().map(|(_, sig)| sig.as_bytes()).collect();
+        if batch_verify_sigs(&msgs, &sig_bytes, &signer_pks) {
+            return Ok(());
+        }
+        for ((from, sig), pk) in sigs.iter().zip(signer_pks) {
+            if sig.is_valid_with_hash(hash, pk).is_err() {
+                return Err(Error::CertificateInvalidSig(**from));
+            }
         }
         Ok(())
     }
@@ -96,20 +165,20 @@ where T: Serialize,
         pks: &FnvHashMap<Replica, DSSPublicKey>,
         storage: &mut Storage,
     ) -> Result<(), Error> {
-        if self.hash != ser_and_hash(msg) {
-            return Err(Error::CertificateHashMismatch);
-        }
+        let hash = msg.canonical_hash();
         if pks.len() < self.len() {
             return Err(Error::CertificateTooManySigs);
         }
-        for (from, sig) in &self.sigs {
-            if storage.is_already_verified(*from, &self.hash) {
-                continue;
-            }
-            let pk = pks.get(from)
-                .ok_or(Error::CertificateUnknownOrigin(*from))?;
-            sig.is_valid_with_hash(&self.hash, pk)?;
-            storage.add_verified_sig(*from, self.hash, sig.get_sig());
+        // The storage only has room for the known signers
+        if let Some(from) = self.sigs.keys().find(|from| !pks.contains_key(*from)) {
+            return Err(Error::CertificateUnknownOrigin(*from));
+        }
+        let sigs: Vec<_> = self.sigs.iter()
+            .filter(|(from, _)| !storage.is_already_verified(**from, &hash))
+            .collect();
+        Self::verify_sigs(&hash, &sigs, pks)?;
+        for (from, sig) in sigs {
+            storage.add_verified_sig(*from, hash, sig.get_sig());
         }
         Ok(())
     }
@@ -1,6 +1,12 @@
 mod certificate;
 pub use certificate::*;
 
+mod aggregate;
+pub use aggregate::*;
+
+mod bitmap;
+pub use bitmap::*;
+
 // mod crypto_cert;
 // pub use crypto_cert::*;
 
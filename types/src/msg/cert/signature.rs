@@ -1,9 +1,12 @@
 use std::marker::PhantomData;
-use crypto::{DSSPublicKey, DSSSecretKey, hash::{Hash, ser_and_hash}};
+use crypto::{DSSPublicKey, DSSSecretKey, hash::Hash};
 use serde::{Serialize, Deserialize};
 
-use crate::error::Error;
+use crate::{CanonicalHash, error::Error};
 
+/// The size of a signature in bytes
+/// Certificates pack the signatures back to back, so all the nodes must use ED25519 keys
+pub const SIG_SIZE: usize = 64;
 
 /// A signature on type T is actually a signature on the hash of T along with information about the origin
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,15 +15,28 @@ pub struct Signature<T> {
     _x: PhantomData<T>,
 }
 
+impl<T> Signature<T> {
+    pub fn from_bytes(sig: Vec<u8>) -> Self {
+        Self {
+            sig,
+            _x: PhantomData,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.sig
+    }
+}
+
 impl<T> Signature<T> 
-where T:Serialize,
+where T: CanonicalHash,
 {
     pub fn get_sig(&self) -> Vec<u8> {
         self.sig.clone()
     }
 
     pub fn is_valid(&self, data: &T, pk: &DSSPublicKey) -> Result<(), String> {
-        let hash = ser_and_hash(data);
+        let hash = data.canonical_hash();
         self.is_valid_with_hash(&hash, pk)
     }
 
@@ -39,7 +55,7 @@ where T:Serialize,
     }
 
     pub fn new_signature(msg: &T, sk: &DSSSecretKey) -> Result<Self, Error> {
-        let h = ser_and_hash(msg);
+        let h = msg.canonical_hash();
         Self::new_signature_from_hash(&h, sk)
     }
 }
@@ -50,7 +66,7 @@ pub struct SignatureBuilder<T> {
 }
 
 impl<T> SignatureBuilder<T> 
-where T: Serialize,
+where T: CanonicalHash,
 {
     pub fn new(sk: DSSSecretKey) -> Self {
         Self {
@@ -60,7 +76,7 @@ where T: Serialize,
     }
 
     pub fn new_sig(&self, data: &T) -> Result<Signature<T>, Error> {
-        let hash = ser_and_hash(data);
+        let hash = data.canonical_hash();
         self.new_sig_with_hash(&hash)
     }
 
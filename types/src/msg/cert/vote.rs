@@ -1,6 +1,6 @@
-use crypto::hash::{EMPTY_HASH, Hash};
+use crypto::{Transcript, hash::{EMPTY_HASH, Hash}};
 use serde::{Serialize, Deserialize};
-use crate::{Epoch, resp_threshold, sync_threshold};
+use crate::{CanonicalHash, Epoch, resp_threshold, sync_threshold};
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -53,3 +53,16 @@ impl Vote {
     }
 }
 
+impl CanonicalHash for Vote {
+    const DOMAIN: &'static [u8] = b"vote";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_u64(b"epoch", self.epoch as u64);
+        t.append_message(b"proposal-hash", &self.prop_hash);
+        let tp = match self.tp {
+            Type::Sync => 0,
+            Type::Responsive => 1,
+        };
+        t.append_u64(b"type", tp);
+    }
+}
@@ -0,0 +1,115 @@
+use ark_ec::ProjectiveCurve;
+use crypto::{Transcript, hash::Hash};
+use crate::{AggregatePVSS, DecompositionProof, KeyPop, KeyRotation};
+
+#[cfg(test)]
+mod test;
+
+/// Objects that the protocol hashes (blocks, votes, signed messages, ...)
+///
+/// The hash is SHA256 of a `crypto::Transcript` (see there for the byte encoding) with the domain `DOMAIN`, to which `append_fields` appends the fields of the object in a fixed order:
+/// - Integers (epochs, heights, replicas, lengths) with `append_u64`, i.e., as 8 byte little-endian strings
+/// - Hashes and byte strings with `append_message`, i.e., length prefixed
+/// - Group elements in affine form with `append_point`, and scalars with `append_scalar`
+/// - Nested objects as their canonical hash
+/// - Lists as their length followed by the elements, and options as 0 (none) or 1 followed by the value
+///
+/// Unlike `crypto::hash::ser_and_hash`, the hash does not depend on bincode or on the layout of the Rust types, so others can recompute it from this description
+/// The golden vectors in the tests pin the encoding, which must only change along with `crypto::PROTOCOL_LABEL`
+pub trait CanonicalHash {
+    /// The domain of the transcript, different for every type
+    const DOMAIN: &'static [u8];
+
+    /// Appends the fields of the object to the transcript
+    fn append_fields(&self, t: &mut Transcript);
+
+    fn canonical_hash(&self) -> Hash {
+        let mut t = Transcript::new(Self::DOMAIN);
+        self.append_fields(&mut t);
+        t.hash()
+    }
+}
+
+/// Appends the canonical hash of the nested object
+pub fn append_hash_of(t: &mut Transcript, label: &[u8], obj: &impl CanonicalHash) {
+    t.append_message(label, &obj.canonical_hash());
+}
+
+/// The hash of a leaf of the Merkle tree of an accumulator
+pub fn merkle_leaf_hash(shard: &[u8]) -> Hash {
+    let mut t = Transcript::new(b"merkle-leaf");
+    t.append_message(b"shard", shard);
+    t.hash()
+}
+
+/// The hash of an inner node of the Merkle tree of an accumulator
+pub fn merkle_node_hash(left: &[u8], right: &[u8]) -> Hash {
+    let mut t = Transcript::new(b"merkle-node");
+    t.append_message(b"left", left);
+    t.append_message(b"right", right);
+    t.hash()
+}
+
+impl CanonicalHash for AggregatePVSS {
+    const DOMAIN: &'static [u8] = b"aggregate-pvss";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_u64(b"key-version", self.key_version);
+        t.append_u64(b"epoch", self.epoch);
+        t.append_u64(b"commitments", self.comms.len() as u64);
+        for c in &self.comms {
+            t.append_point(b"commitment", c);
+        }
+        t.append_u64(b"encryptions", self.encs.len() as u64);
+        for e in &self.encs {
+            t.append_point(b"encryption", &e.into_affine());
+        }
+    }
+}
+
+impl CanonicalHash for DecompositionProof {
+    const DOMAIN: &'static [u8] = b"decomposition-proof";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_u64(b"indices", self.indices.len() as u64);
+        for i in &self.indices {
+            t.append_u64(b"index", *i as u64);
+        }
+        t.append_u64(b"proofs", self.dleq_proof.len() as u64);
+        for p in &self.dleq_proof {
+            t.append_point(b"a", &p.a.into_affine());
+            t.append_scalar(b"c", &p.c);
+            t.append_scalar(b"r", &p.r);
+            t.append_message(b"sig", &p.sig);
+            match &p.key_proof {
+                None => t.append_u64(b"has-key-proof", 0),
+                Some(key_proof) => {
+                    t.append_u64(b"has-key-proof", 1);
+                    t.append_point(b"key-a", &key_proof.a.into_affine());
+                    t.append_scalar(b"key-r", &key_proof.r);
+                }
+            }
+        }
+        t.append_u64(b"gs", self.gs_vec.len() as u64);
+        for gs in &self.gs_vec {
+            t.append_point(b"g-s", &gs.into_affine());
+        }
+    }
+}
+
+fn append_key_pop(t: &mut Transcript, label_a: &[u8], label_r: &[u8], pop: &KeyPop) {
+    t.append_point(label_a, &pop.a.into_affine());
+    t.append_scalar(label_r, &pop.r);
+}
+
+impl CanonicalHash for KeyRotation {
+    const DOMAIN: &'static [u8] = b"key-rotation";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_u64(b"id", self.id as u64);
+        t.append_u64(b"switch-epoch", self.switch_epoch);
+        t.append_point(b"new-pk", &self.new_pk.into_affine());
+        append_key_pop(t, b"pop-a", b"pop-r", &self.pop);
+        append_key_pop(t, b"endorsement-a", b"endorsement-r", &self.endorsement);
+    }
+}
@@ -0,0 +1,101 @@
+// Golden vectors for the canonical hashes
+// They were computed independently from the encoding in `CanonicalHash`, so a change here breaks every external verifier
+use crypto::hash::Hash;
+use crate::{AckDataBuilder, Block, CanonicalHash, Certificate, MTAccumulator, Proof, Signature, Type, Vote, VoteBuilder, merkle_leaf_hash, merkle_node_hash};
+
+fn hex(h: &Hash) -> String {
+    h.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_vote() {
+    let vote: Vote = VoteBuilder::default()
+        .epoch(5)
+        .prop_hash([7u8; 32])
+        .tp(Type::Responsive)
+        .build()
+        .unwrap();
+    assert_eq!(hex(&vote.canonical_hash()), "807573b16aca646cd70b3682bc4f5b06854d3d9f41e4f939ed57acc87de809ef");
+}
+
+#[test]
+fn test_certified_accumulator() {
+    let msg = (3, MTAccumulator::<Vote>::default());
+    assert_eq!(hex(&msg.canonical_hash()), "c055068af82adfb3b3286b0aa012c63fb18e3baefb985932dde924463be6d078");
+}
+
+#[test]
+fn test_merkle() {
+    assert_eq!(hex(&merkle_leaf_hash(b"shard")), "6fbe3e4ecb8c635c79fca232f200a63d5e742255ed43a0f19d64168a1735a961");
+    assert_eq!(hex(&merkle_node_hash(&[1u8; 32], &[2u8; 32])), "c375d2da8f59eb08c8bc480e7db6b097ee54390e6a411e218d7b6c38bae7e268");
+}
+
+#[test]
+fn test_certificate() {
+    let mut cert = Certificate::<Vote>::default();
+    assert_eq!(hex(&cert.canonical_hash()), "a747cc9e90392787438b169c5bc6db4f3fb7467abecf215386078a116c05a128");
+    // The signers are hashed in increasing order, whatever the order of the map
+    cert.add_signature(2, Signature::from_bytes(vec![9u8; 64]));
+    cert.add_signature(0, Signature::from_bytes(vec![8u8; 64]));
+    assert_eq!(hex(&cert.canonical_hash()), "b6e21aaf7e1e543e4691f753448c5a1ef2ac789a7a7f5b883839c9db0012c95a");
+}
+
+#[test]
+fn test_ack() {
+    let ack = AckDataBuilder::default()
+        .e(4)
+        .prop_hash([3u8; 32])
+        .proof(Proof::default())
+        .build()
+        .unwrap();
+    assert_eq!(hex(&ack.canonical_hash()), "5918700518b37dcfd9caacebfd44871a44b85242742847285f1c989973e4f914");
+}
+
+#[test]
+fn test_block() {
+    assert_eq!(hex(&Block::GENESIS_BLOCK.compute_hash()), "79ef5bca61588fee40cf1181361f31262f6302724d40eb6fdf3115b48bec9048");
+}
+
+/// The hash of the genesis block depends on the curve, so that nodes on different curves do not share a chain
+#[test]
+fn test_genesis() {
+    #[cfg(not(feature = "bn254"))]
+    let expected = "3231abe6eff447449a3e57537aa784b0c285c64d28807cf7ace708c75f266b9f";
+    #[cfg(feature = "bn254")]
+    let expected = "a39c8de8bf24cb25076ae347c9fbee8c9861e35cfb64090bbf135f3c623c11fa";
+    assert_eq!(hex(&Block::genesis_hash()), expected);
+    assert_eq!(Block::genesis().hash(), &Block::genesis_hash());
+}
+
+/// A block with real points in its aggregate PVSS and decomposition proof, so that the encoding of the points is covered
+#[cfg(not(feature = "bn254"))]
+#[test]
+fn test_block_with_pvss() {
+    use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+    use crate::{AggregatePVSS, BlockBuilder, DecompositionProof};
+    let g1 = <crate::E as PairingEngine>::G1Projective::prime_subgroup_generator();
+    let g2 = <crate::E as PairingEngine>::G2Projective::prime_subgroup_generator();
+    let agg = AggregatePVSS {
+        comms: vec![g1.into_affine(), g1.double().into_affine()],
+        encs: vec![g2, g2.double() + g2],
+        key_version: 1,
+        epoch: 5,
+    };
+    assert_eq!(agg.comms[0], <crate::E as PairingEngine>::G1Affine::prime_subgroup_generator());
+    assert_eq!(hex(&agg.canonical_hash()), "8c4cbbe5deeebad84284e16623cf2b97c5e132ed7632d6bd9f3e85e98353c661");
+    let proof = DecompositionProof {
+        indices: vec![0, 2],
+        dleq_proof: vec![],
+        gs_vec: vec![g1.double()],
+    };
+    assert_eq!(hex(&proof.canonical_hash()), "1e07d203b518f4579f32e8f2aa6d94569e102e273696d15147c8c7b387976e25");
+    let block = BlockBuilder::default()
+        .parent_hash([1u8; 32])
+        .proposer(2)
+        .height(7)
+        .aggregate_pvss(agg)
+        .aggregate_proof(proof)
+        .build()
+        .unwrap();
+    assert_eq!(hex(&block.compute_hash()), "4d2c4bce5f95c23955c07d035050dbb6f79c0447b5b9ad3d355cb043cc8db9e4");
+}
@@ -0,0 +1,69 @@
+use crypto::rand::Rng;
+use crate::{DbsContext, Epoch, KeyRotation, START_EPOCH, Storage};
+
+/// Drives the rotations of the PVSS keys of a node: proposing rotations of my key, and switching to the committed ones
+#[derive(Debug, Clone)]
+pub struct KeyRotations {
+    /// The number of epochs between two rotations of my key (None if we never rotate)
+    interval: Option<Epoch>,
+    /// The epoch in which I last proposed a rotation of my key
+    last_rotation: Epoch,
+}
+
+impl KeyRotations {
+    pub fn new(interval: Option<Epoch>) -> Self {
+        Self {
+            interval,
+            last_rotation: START_EPOCH,
+        }
+    }
+
+    /// Switches to the PVSS keys rotated in the committed blocks for epoch e (or earlier), and forgets the old keys that we no longer need
+    /// Returns true if the keys changed, so that the other copies of the context must be replaced
+    pub fn switch_keys(&self, storage: &mut Storage, pvss_ctx: &mut DbsContext, e: Epoch) -> bool {
+        let mut switched = false;
+        for (switch_epoch, mut rots) in storage.take_key_rotations(e) {
+            // A rotation may have become invalid if the node rotated its key again in the meantime
+            rots.retain(|rot| match pvss_ctx.verify_rotation(rot) {
+                None => true,
+                Some(err) => {
+                    log::warn!("Ignoring the key rotation of {} for {}: {:?}", rot.id, switch_epoch, err);
+                    false
+                }
+            });
+            if rots.is_empty() {
+                continue;
+            }
+            if let Some(err) = pvss_ctx.apply_rotations(&rots) {
+                log::error!("Failed to switch to my new PVSS key in {}: {:?}", switch_epoch, err);
+            }
+            log::info!("Switched to PVSS key version {} in {}", pvss_ctx.key_version(), e);
+            switched = true;
+        }
+        let oldest_in_use = storage
+            .oldest_key_version()
+            .unwrap_or(pvss_ctx.key_version());
+        pvss_ctx.retire_keys(e as u64, oldest_in_use);
+        switched
+    }
+
+    /// Returns a rotation of my PVSS key for the block that I propose in the epoch, if it is time to rotate
+    pub fn next_rotation<R>(&mut self, pvss_ctx: &mut DbsContext, epoch: Epoch, rng: &mut R) -> Option<KeyRotation>
+    where R: Rng + ?Sized,
+    {
+        let interval = self.interval?;
+        if epoch < self.last_rotation + interval {
+            return None;
+        }
+        // Wait for the rotation that we announced earlier, unless its block was not committed in time
+        if let Some(switch_epoch) = pvss_ctx.pending_rotation() {
+            if switch_epoch >= epoch as u64 {
+                return None;
+            }
+        }
+        self.last_rotation = epoch;
+        let switch_epoch = pvss_ctx.min_switch_epoch(epoch as u64);
+        log::info!("Rotating my PVSS key in {}", switch_epoch);
+        Some(pvss_ctx.announce_rotation(switch_epoch, rng))
+    }
+}
@@ -10,6 +10,9 @@ pub use cert::*;
 mod storage;
 pub use storage::*;
 
+mod key_rotation;
+pub use key_rotation::*;
+
 mod propose;
 pub use propose::*;
 
@@ -23,4 +26,10 @@ mod ack;
 pub use ack::*;
 
 mod reconfig;
-pub use reconfig::*;
\ No newline at end of file
+pub use reconfig::*;
+
+mod wire;
+pub use wire::*;
+
+mod hashing;
+pub use hashing::*;
\ No newline at end of file
@@ -1,6 +1,6 @@
 use super::{Block, accumulator};
-use crate::{Certificate, Codeword, DbsContext, DirectProposal, Epoch, MTAccumulator, MTAccumulatorBuilder, Replica, RespCertData, RespCertProposal, Storage, SyncCertData, SyncCertProposal, Vote, Witness, error::Error};
-use crypto::{DSSPublicKey, hash::{Hash, ser_and_hash, EMPTY_HASH}};
+use crate::{CanonicalHash, Certificate, Codeword, Wire, append_hash_of, encode_versioned, DbsContext, DirectProposal, Epoch, MTAccumulator, MTAccumulatorBuilder, Replica, RespCertData, RespCertProposal, Storage, SyncCertData, SyncCertProposal, Vote, Witness, error::Error};
+use crypto::{DSSPublicKey, Transcript, hash::{Hash, EMPTY_HASH}};
 use fnv::FnvHashMap;
 use types_upstream::WireReady;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
@@ -71,16 +71,17 @@ impl<T> Proof<T> {
 impl WireReady for DirectProposal {
     fn init(mut self) -> Self {
         self.data.block = self.data.block.init();
-        self.hash = ser_and_hash(&self);
+        self.hash = self.canonical_hash();
         self
     }
 
+    /// WARNING: Panics on malformed data, use `Wire::from_wire` for data from the network
     fn from_bytes(data: &[u8]) -> Self {
-        bincode::deserialize(data).expect("failed to deserialize proposal")
+        Self::from_wire(data).expect("failed to deserialize proposal")
     }
 
     fn to_bytes(&self) -> Vec<u8> {
-        bincode::serialize(self).expect("Failed to serialize proposal")
+        self.to_wire().expect("Failed to serialize proposal")
     }
 }
 
@@ -89,12 +90,13 @@ impl WireReady for Proposal<SyncCertData> {
         self
     }
 
+    /// WARNING: Panics on malformed data, use `Wire::from_wire` for data from the network
     fn from_bytes(data: &[u8]) -> Self {
-        bincode::deserialize(data).expect("failed to deserialize proposal")
+        Self::from_wire(data).expect("failed to deserialize proposal")
     }
 
     fn to_bytes(&self) -> Vec<u8> {
-        bincode::serialize(self).expect("Failed to serialize proposal")
+        self.to_wire().expect("Failed to serialize proposal")
     }
 }
 
@@ -103,12 +105,13 @@ impl WireReady for Proposal<RespCertData> {
         self
     }
 
+    /// WARNING: Panics on malformed data, use `Wire::from_wire` for data from the network
     fn from_bytes(data: &[u8]) -> Self {
-        bincode::deserialize(data).expect("failed to deserialize proposal")
+        Self::from_wire(data).expect("failed to deserialize proposal")
     }
 
     fn to_bytes(&self) -> Vec<u8> {
-        bincode::serialize(self).expect("Failed to serialize proposal")
+        self.to_wire().expect("Failed to serialize proposal")
     }
 }
 
@@ -116,12 +119,13 @@ impl WireReady for Proposal<RespCertData> {
 impl<T> WireReady for Proof<T> 
 where T:Clone + Send + Sync + DeserializeOwned + Serialize,
 {
+    /// WARNING: Panics on malformed data, use `Wire::from_wire` for data from the network
     fn from_bytes(data: &[u8]) -> Self {
-        bincode::deserialize(data).expect("failed to deserialize proposal")
+        Self::from_wire(data).expect("failed to deserialize proposal")
     }
 
     fn to_bytes(&self) -> Vec<u8> {
-        bincode::serialize(self).expect("Failed to serialize proposal")
+        self.to_wire().expect("Failed to serialize proposal")
     }
 
     fn init(self) -> Self {
@@ -129,6 +133,35 @@ where T:Clone + Send + Sync + DeserializeOwned + Serialize,
     }
 }
 
+impl<T> Wire for Proposal<T>
+where T: DeserializeOwned + Serialize,
+{}
+
+/// The codewords and witnesses are derived from the proposal, so they are not part of the hash
+impl CanonicalHash for DirectProposal {
+    const DOMAIN: &'static [u8] = b"proposal";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_u64(b"epoch", self.data.epoch as u64);
+        append_hash_of(t, b"block", &self.data.block);
+        append_hash_of(t, b"highest-vote", &self.data.highest_cert_data);
+        append_hash_of(t, b"highest-cert", &self.data.highest_cert);
+    }
+}
+
+impl<T> CanonicalHash for Proof<T> {
+    const DOMAIN: &'static [u8] = b"proposal-proof";
+
+    fn append_fields(&self, t: &mut Transcript) {
+        t.append_message(b"accumulator", &self.acc.hash);
+        append_hash_of(t, b"certificate", &self.sign);
+    }
+}
+
+impl<T> Wire for Proof<T>
+where T: DeserializeOwned + Serialize,
+{}
+
 impl DirectProposal {
     pub fn epoch(&self) -> Epoch {
         self.data.epoch
@@ -168,7 +201,8 @@ impl DirectProposal {
     ) -> Result<(), Error> {
         // Is the block valid on its own?
         self.block()
-            .is_valid(&storage, 
+            .is_valid(e,
+                &storage, 
                 pvss_ctx, 
                 &pk_map,
             )?; 
@@ -269,7 +303,7 @@ where T:Serialize + Clone
 
     pub fn get_codewords(&mut self, prop_acc_builder: &MTAccumulatorBuilder<Self>) -> Result<Vec<Codeword<Self>>, Error> {
         if let None = self.codewords {
-            let bytes = bincode::serialize(self)?;
+            let bytes = encode_versioned(self)?;
             let n = prop_acc_builder.n.ok_or(Error::BuilderUnsetField("n"))?;
             let f = prop_acc_builder.f.ok_or(Error::BuilderUnsetField("f"))?;
             self.codewords = Some(accumulator::generate_codewords(&bytes, n, f)?);
@@ -286,7 +320,7 @@ where T:Serialize + Clone
 
     pub fn get_witnesses(&mut self, prop_acc_builder: &MTAccumulatorBuilder<Self>) -> Result<Vec<Witness<Self>>, Error> {
         if let None = self.codewords {
-            let bytes = bincode::serialize(self)?;
+            let bytes = encode_versioned(self)?;
             let n = prop_acc_builder.n.ok_or(Error::BuilderUnsetField("n"))?;
             let f = prop_acc_builder.f.ok_or(Error::BuilderUnsetField("f"))?;
             self.codewords = Some(accumulator::generate_codewords(&bytes, n, f)?);
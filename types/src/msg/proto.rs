@@ -89,19 +89,24 @@ impl WireReady for ProtocolMsg {
             ProtocolMsg::Ack(..) => self,
             ProtocolMsg::AggregateReady(..) => self,
             ProtocolMsg::Sync => self,
-            _ => todo!("Implement state transition for protocolmsg: {:?}", self),
+            // Only we turn a RawPropose into a Propose, and we do not relay equivocation proofs yet
+            ProtocolMsg::Propose(..) |
+            ProtocolMsg::EquivocationProposal(..) |
+            ProtocolMsg::EquivocationSyncCert(..) |
+            ProtocolMsg::InvalidMessage => ProtocolMsg::InvalidMessage,
         }
     }
 
+    /// Malformed messages are turned into `InvalidMessage`
+    /// Prefer `Wire::from_wire` to see why decoding failed
     fn from_bytes(data: &[u8]) -> Self {
-        let c: ProtocolMsg =
-            bincode::deserialize(&data)
-                .expect("failed to decode the protocol message");
-        c
+        Self::from_wire(data).unwrap_or(ProtocolMsg::InvalidMessage)
     }
 
     fn to_bytes(self: &Self) -> Vec<u8> {
-        bincode::serialize(self).expect(format!("Failed to serialize {:?}", self).as_str())
+        self.to_wire().expect(format!("Failed to serialize {:?}", self).as_str())
     }
 }
 
+impl Wire for ProtocolMsg {}
+
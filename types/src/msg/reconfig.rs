@@ -1,5 +1,6 @@
 use serde::{Serialize, Deserialize};
 use types_upstream::WireReady;
+use crate::Wire;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum ReconfigurationMsg {
@@ -14,14 +15,15 @@ impl WireReady for ReconfigurationMsg {
         self
     }
 
+    /// WARNING: Panics on malformed data, use `Wire::from_wire` for data from the network
     fn from_bytes(data: &[u8]) -> Self {
-        let c: Self =
-            bincode::deserialize(&data)
-                .expect("failed to decode the protocol message");
-        c
+        Self::from_wire(data)
+            .expect("failed to decode the protocol message")
     }
 
     fn to_bytes(self: &Self) -> Vec<u8> {
-        bincode::serialize(self).expect(format!("Failed to serialize {:?}", self).as_str())
+        self.to_wire().expect(format!("Failed to serialize {:?}", self).as_str())
     }
-}
\ No newline at end of file
+}
+
+impl Wire for ReconfigurationMsg {}
\ No newline at end of file
@@ -1,7 +1,7 @@
 use fnv::{FnvHashMap as HashMap, FnvHashSet};
 use super::Block;
 use crate::*;
-use crypto::hash::{Hash, ser_and_hash};
+use crypto::hash::Hash;
 use std::{collections::VecDeque, sync::Arc};
 use crate::AggregatePVSS;
 
@@ -55,6 +55,9 @@ pub struct Storage {
     /// Store beacon PVSS vectors here
     rand_beacon_pvss: HashMap<Replica, VecDeque<AggregatePVSS>>,
 
+    /// The key rotations in the committed blocks, by the switch epoch, along with the height of their blocks
+    key_rotations: HashMap<Epoch, Vec<(Height, KeyRotation)>>,
+
 }
 
 impl Storage {
@@ -74,7 +77,7 @@ impl Storage {
         for i in 0..num_nodes {
             storage.verified_sigs.insert(i, HashMap::default());
         }
-        storage.add_delivered_block(Block::GENESIS_BLOCK);
+        storage.add_delivered_block(Block::genesis());
         let gen_arc = storage.get_delivered_block_by_height(Block::GENESIS_BLOCK.height()).expect("Could not find genesis block even after adding it to the storage");
         storage.committed_blocks_by_hash.insert(
             *gen_arc.hash(), 
@@ -107,6 +110,12 @@ impl Storage {
                     format!("We can't have committed a block while having an empty randombeacon queue")
                 )?;
             queue.push_back(b_arc.pvss().clone());
+            if let Some(rot) = b_arc.key_rotation() {
+                self.key_rotations
+                    .entry(rot.switch_epoch as Epoch)
+                    .or_default()
+                    .push((b_arc.height(), rot.clone()));
+            }
             self.committed_blocks_by_hash.insert(
                 b_arc.hash().clone(), b_arc.clone());
             self.committed_blocks_by_ht.insert(b_arc.height(), b_arc);
@@ -143,7 +152,8 @@ impl Storage {
     }
 
     pub fn is_already_verified(&self, from: Replica, msg_hash: &Hash) -> bool {
-        self.verified_sigs[from].contains_key(msg_hash)
+        self.verified_sigs.get(from)
+            .map_or(false, |sigs| sigs.contains_key(msg_hash))
     }
 
     /// Ensure that equivocations are checked for, before adding the proposal
@@ -163,7 +173,7 @@ impl Storage {
                 )?;
             Arc::new(proof)
         };
-        let hash = ser_and_hash(p_arc.as_ref());
+        let hash = p_arc.canonical_hash();
         self.prop_hash_map.insert(hash, (p_arc.clone(), proof_arc));
         self.prop_eq_epoch_map.insert(p_arc.epoch(), (acc, sign));
         self.prop_epoch_map.insert(p_arc.epoch(), p_arc);
@@ -194,6 +204,36 @@ impl Storage {
             .ok_or(format!("Cleaving an empty buffer").into())
     }
 
+    /// Removes and returns the committed key rotations that switch in epoch e or earlier, grouped by the switch epoch in increasing order, and ordered by the node in a group
+    /// The rotations that switch before e were committed after their switch epoch, and must still be applied before the ones for e
+    /// If a node has several in a group, we keep the one in the lowest block, so that every node picks the same one regardless of the order of commits
+    pub fn take_key_rotations(&mut self, e: Epoch) -> Vec<(Epoch, Vec<KeyRotation>)> {
+        let mut switch_epochs: Vec<_> = self.key_rotations
+            .keys()
+            .copied()
+            .filter(|&switch_epoch| switch_epoch <= e)
+            .collect();
+        switch_epochs.sort_unstable();
+        switch_epochs
+            .into_iter()
+            .map(|switch_epoch| {
+                let mut rots = self.key_rotations.remove(&switch_epoch).unwrap_or_default();
+                rots.sort_by_key(|(ht, rot)| (rot.id, *ht));
+                rots.dedup_by_key(|(_, rot)| rot.id);
+                (switch_epoch, rots.into_iter().map(|(_, rot)| rot).collect())
+            })
+            .collect()
+    }
+
+    /// The oldest key version of the PVSS vectors that are waiting to be decrypted, if any
+    pub fn oldest_key_version(&self) -> Option<u64> {
+        self.rand_beacon_pvss
+            .values()
+            .flat_map(|queue| queue.iter())
+            .map(|pvss| pvss.key_version)
+            .min()
+    }
+
     /// Checks if we received an equivocating proposal
     /// Check the validity of the certificate first
     pub fn is_equivocation_prop(&self, 
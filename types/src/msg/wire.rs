@@ -0,0 +1,47 @@
+use bincode::Options;
+use serde::{Serialize, de::DeserializeOwned};
+use crate::{Result, error::Error};
+
+/// The version of the wire format, sent as the first byte of every message
+/// Bump it on every incompatible change to a message, so that nodes reject the messages of incompatible peers during upgrades instead of misreading them
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// The bincode options of `bincode::serialize`, except that bytes after the message are an error
+fn wire_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+}
+
+/// Encodes the object as the protocol version followed by its bincode encoding
+pub fn encode_versioned(obj: &impl Serialize) -> Result<Vec<u8>> {
+    let mut bytes = vec![PROTOCOL_VERSION];
+    bincode::serialize_into(&mut bytes, obj)?;
+    Ok(bytes)
+}
+
+/// Decodes an object encoded by `encode_versioned`
+pub fn decode_versioned<T>(data: &[u8]) -> Result<T>
+where T: DeserializeOwned,
+{
+    let (version, body) = data.split_first()
+        .ok_or(Error::WireEmptyMessage)?;
+    if *version != PROTOCOL_VERSION {
+        return Err(Error::WireVersionMismatch(PROTOCOL_VERSION, *version));
+    }
+    Ok(wire_options().deserialize(body)?)
+}
+
+/// A message that we send over the network
+///
+/// Unlike `WireReady::from_bytes`, decoding returns an error for malformed or incompatible messages instead of panicking,
+/// so decode data from peers with `from_wire`, since a single bad frame must not crash the node
+pub trait Wire: Serialize + DeserializeOwned {
+    fn to_wire(&self) -> Result<Vec<u8>> {
+        encode_versioned(self)
+    }
+
+    fn from_wire(data: &[u8]) -> Result<Self> {
+        decode_versioned(data)
+    }
+}
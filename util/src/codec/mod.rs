@@ -1,7 +1,13 @@
 pub mod proto;
 pub mod reconfig;
 
-use tokio_util::codec::LengthDelimitedCodec;
+use std::io;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, LengthDelimitedCodec};
+use types::Wire;
+
+/// We disconnect from a peer after it sends this many malformed frames
+pub const MAX_MALFORMED_FRAMES: usize = 100;
 
 #[derive(Debug)]
 pub struct EnCodec(pub LengthDelimitedCodec);
@@ -17,3 +23,36 @@ impl std::clone::Clone for EnCodec {
         EnCodec::new()
     }
 }
+
+/// Encodes the message for the wire (see `types::Wire`)
+fn encode_msg(item: &impl Wire) -> io::Result<bytes::Bytes> {
+    item.to_wire()
+        .map(bytes::Bytes::from)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
+
+/// Returns the next message from the peer, if there is a complete frame
+/// A frame that does not decode (see `types::Wire::from_wire`) is dropped and counted as misbehavior of the peer in `malformed`
+/// After MAX_MALFORMED_FRAMES of them, we fail so that the connection to the peer is dropped
+fn decode_msg<T>(inner: &mut LengthDelimitedCodec, 
+    malformed: &mut usize, 
+    src: &mut BytesMut,
+) -> io::Result<Option<T>>
+where T: Wire,
+{
+    while let Some(in_data) = inner.decode(src)? {
+        match T::from_wire(&in_data) {
+            Ok(msg) => return Ok(Some(msg)),
+            Err(e) => {
+                *malformed += 1;
+                log::warn!("Dropping a malformed frame ({} so far from this peer): {}", malformed, e);
+                if *malformed >= MAX_MALFORMED_FRAMES {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, 
+                        format!("The peer sent {} malformed frames", malformed)
+                    ));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
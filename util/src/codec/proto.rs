@@ -1,18 +1,29 @@
-use bytes::{Bytes, BytesMut};
+use bytes::BytesMut;
 use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
 use types::{ProtocolMsg, ReconfigurationMsg};
-use types_upstream::WireReady;
 
 use std::{borrow::Borrow, io, sync::Arc};
 
-use crate::io::to_bytes;
+use super::{decode_msg, encode_msg};
 
 #[derive(Debug)]
-pub struct Codec(pub LengthDelimitedCodec);
+pub struct Codec {
+    inner: LengthDelimitedCodec,
+    /// The number of malformed frames from the peer
+    malformed: usize,
+}
 
 impl Codec {
     pub fn new() -> Self {
-        Codec(LengthDelimitedCodec::new())
+        Codec {
+            inner: LengthDelimitedCodec::new(),
+            malformed: 0,
+        }
+    }
+
+    /// The number of malformed frames that the peer sent so far
+    pub fn malformed_frames(&self) -> usize {
+        self.malformed
     }
 }
 
@@ -21,10 +32,7 @@ impl Decoder for Codec {
     type Error = io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match self.0.decode(src)? {
-            Some(in_data) => Ok(Some(ProtocolMsg::from_bytes(&in_data))),
-            None => Ok(None),
-        }
+        decode_msg(&mut self.inner, &mut self.malformed, src)
     }
 }
 
@@ -32,8 +40,7 @@ impl Encoder<ProtocolMsg> for super::EnCodec {
     type Error = io::Error;
 
     fn encode(&mut self, item: ProtocolMsg, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let data = to_bytes(&item);
-        let buf = Bytes::from(data);
+        let buf = encode_msg(&item)?;
         return self.0.encode(buf, dst);
     }
 }
@@ -41,8 +48,7 @@ impl Encoder<ReconfigurationMsg> for super::EnCodec {
     type Error = io::Error;
 
     fn encode(&mut self, item: ReconfigurationMsg, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let data = to_bytes(&item);
-        let buf = Bytes::from(data);
+        let buf = encode_msg(&item)?;
         return self.0.encode(buf, dst);
     }
 }
@@ -51,8 +57,7 @@ impl Encoder<Arc<ReconfigurationMsg>> for super::EnCodec {
     type Error = io::Error;
 
     fn encode(&mut self, item: Arc<ReconfigurationMsg>, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let data = to_bytes(item.as_ref());
-        let buf = Bytes::from(data);
+        let buf = encode_msg(item.as_ref())?;
         return self.0.encode(buf, dst);
     }
 }
@@ -62,8 +67,7 @@ impl Encoder<Arc<ProtocolMsg>> for super::EnCodec {
 
     fn encode(&mut self, item: Arc<ProtocolMsg>, dst: &mut BytesMut) -> Result<(), Self::Error> {
         let bor: &ProtocolMsg = item.borrow();
-        let data = to_bytes(bor);
-        let buf = Bytes::from(data);
+        let buf = encode_msg(bor)?;
         return self.0.encode(buf, dst);
     }
 }
@@ -3,15 +3,27 @@ use std::io;
 use bytes::BytesMut;
 use tokio_util::codec::{Decoder, LengthDelimitedCodec};
 use types::ReconfigurationMsg;
-use types_upstream::WireReady;
 
+use super::decode_msg;
 
 #[derive(Debug)]
-pub struct Codec(pub LengthDelimitedCodec);
+pub struct Codec {
+    inner: LengthDelimitedCodec,
+    /// The number of malformed frames from the peer
+    malformed: usize,
+}
 
 impl Codec {
     pub fn new() -> Self {
-        Codec(LengthDelimitedCodec::new())
+        Codec {
+            inner: LengthDelimitedCodec::new(),
+            malformed: 0,
+        }
+    }
+
+    /// The number of malformed frames that the peer sent so far
+    pub fn malformed_frames(&self) -> usize {
+        self.malformed
     }
 }
 
@@ -20,10 +32,7 @@ impl Decoder for Codec {
     type Error = io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match self.0.decode(src)? {
-            Some(in_data) => Ok(Some(ReconfigurationMsg::from_bytes(&in_data))),
-            None => Ok(None),
-        }
+        decode_msg(&mut self.inner, &mut self.malformed, src)
     }
 }
 